@@ -0,0 +1,346 @@
+-- Update version
+UPDATE _info SET version = 4;
+
+-- Persist user and event sessions
+CREATE TABLE `sessions` (
+  `token` char(30) NOT NULL,
+  `user_id` mediumint(9) DEFAULT NULL,
+  `event_id` int(11) DEFAULT NULL,
+  `rights` text DEFAULT NULL,
+  `client` varchar(45) DEFAULT NULL,
+  `created` datetime NOT NULL,
+  `expiry` datetime NOT NULL
+);
+
+ALTER TABLE `sessions`
+ADD PRIMARY KEY (`token`),
+ADD KEY `REF_user` (`user_id`),
+ADD KEY `REF_event` (`event_id`);
+
+ALTER TABLE `sessions`
+ADD CONSTRAINT `sessions_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON DELETE CASCADE ON UPDATE CASCADE,
+ADD CONSTRAINT `sessions_ibfk_2` FOREIGN KEY (`event_id`) REFERENCES `events` (`event_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- Add hash algorithm to user credentials, existing hashes are upgraded on the next login
+ALTER TABLE `user_credentials` ADD `algorithm` ENUM('SHA256','ARGON2ID') NOT NULL DEFAULT 'SHA256' AFTER `sp_hash`;
+ALTER TABLE `user_credentials` ALTER `algorithm` SET DEFAULT 'ARGON2ID';
+
+-- Track failed logins for lockouts
+CREATE TABLE `login_failures` (
+  `category` enum('USER','EVENT','IP') NOT NULL,
+  `subject` varchar(45) NOT NULL,
+  `attempts` smallint(6) NOT NULL DEFAULT 0,
+  `last_attempt` datetime NOT NULL,
+  `locked_until` datetime DEFAULT NULL
+);
+
+ALTER TABLE `login_failures`
+ADD PRIMARY KEY (`category`,`subject`);
+
+-- Hash event passwords, existing clear passwords are hashed with SHA256 and upgraded on the next login
+ALTER TABLE `events`
+ADD `pwd_pepper` binary(16) NOT NULL AFTER `pwd`,
+ADD `pwd_hash` binary(32) NOT NULL AFTER `pwd_pepper`,
+ADD `pwd_algorithm` enum('SHA256','ARGON2ID') NOT NULL DEFAULT 'SHA256' AFTER `pwd_hash`;
+
+UPDATE `events` SET `pwd_pepper` = UNHEX(MD5(RAND()));
+UPDATE `events` SET `pwd_hash` = UNHEX(SHA2(CONCAT(`pwd`, `pwd_pepper`), 256));
+
+ALTER TABLE `events` ALTER `pwd_algorithm` SET DEFAULT 'ARGON2ID';
+ALTER TABLE `events` DROP `pwd`;
+
+-- Password reset tokens
+CREATE TABLE `password_resets` (
+  `token_hash` binary(32) NOT NULL,
+  `user_id` mediumint(9) NOT NULL,
+  `created` datetime NOT NULL,
+  `expiry` datetime NOT NULL
+);
+
+ALTER TABLE `password_resets`
+ADD PRIMARY KEY (`token_hash`),
+ADD KEY `REF_user` (`user_id`);
+
+ALTER TABLE `password_resets`
+ADD CONSTRAINT `password_resets_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- TOTP second factor with recovery codes
+ALTER TABLE `user_credentials`
+ADD `totp_secret` binary(20) DEFAULT NULL AFTER `algorithm`,
+ADD `totp_enabled` tinyint(1) NOT NULL DEFAULT 0 AFTER `totp_secret`,
+ADD `totp_step` bigint(20) DEFAULT NULL AFTER `totp_enabled`;
+
+CREATE TABLE `user_recovery_codes` (
+  `credential_id` mediumint(9) NOT NULL,
+  `code_hash` binary(32) NOT NULL
+);
+
+ALTER TABLE `user_recovery_codes`
+ADD PRIMARY KEY (`credential_id`,`code_hash`);
+
+ALTER TABLE `user_recovery_codes`
+ADD CONSTRAINT `user_recovery_codes_ibfk_1` FOREIGN KEY (`credential_id`) REFERENCES `user_credentials` (`credential_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE `teams` ADD `mfa_required` tinyint(1) NOT NULL DEFAULT 0 AFTER `description`;
+
+-- Long-lived API tokens
+CREATE TABLE `api_tokens` (
+  `token_id` mediumint(9) NOT NULL,
+  `user_id` mediumint(9) NOT NULL,
+  `name` varchar(40) NOT NULL,
+  `token_hash` binary(32) NOT NULL,
+  `rights` text NOT NULL,
+  `created` datetime NOT NULL,
+  `expiry` datetime DEFAULT NULL,
+  `last_used` datetime DEFAULT NULL
+);
+
+ALTER TABLE `api_tokens`
+ADD PRIMARY KEY (`token_id`),
+ADD UNIQUE KEY `token_hash` (`token_hash`),
+ADD KEY `REF_user` (`user_id`);
+
+ALTER TABLE `api_tokens`
+MODIFY `token_id` mediumint(9) NOT NULL AUTO_INCREMENT;
+
+ALTER TABLE `api_tokens`
+ADD CONSTRAINT `api_tokens_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- Pending OpenID Connect logins
+CREATE TABLE `oidc_states` (
+  `state` char(30) NOT NULL,
+  `expiry` datetime NOT NULL
+);
+
+ALTER TABLE `oidc_states`
+ADD PRIMARY KEY (`state`);
+
+-- Superusers replace the configured admin
+ALTER TABLE `users`
+ADD `superuser` tinyint(1) NOT NULL DEFAULT 0 AFTER `enabled`;
+
+-- Audit trail of write operations, kept without foreign keys to outlive its actors and subjects
+CREATE TABLE `audit_log` (
+  `audit_id` bigint(20) NOT NULL,
+  `user_id` mediumint(9) DEFAULT NULL,
+  `event_id` int(11) DEFAULT NULL,
+  `route` varchar(60) NOT NULL,
+  `entity` varchar(40) NOT NULL,
+  `entity_id` bigint(20) NOT NULL,
+  `diff` text DEFAULT NULL,
+  `time` datetime NOT NULL
+);
+
+ALTER TABLE `audit_log`
+ADD PRIMARY KEY (`audit_id`),
+ADD KEY `REF_user` (`user_id`),
+ADD KEY `REF_event` (`event_id`),
+ADD KEY `entity` (`entity`,`entity_id`),
+ADD KEY `time` (`time`);
+
+ALTER TABLE `audit_log`
+MODIFY `audit_id` bigint(20) NOT NULL AUTO_INCREMENT;
+
+-- Team rights scoped to a club
+ALTER TABLE `teams`
+ADD `club_id` tinyint(4) DEFAULT NULL AFTER `mfa_required`,
+ADD KEY `REF_club` (`club_id`);
+
+ALTER TABLE `teams`
+ADD CONSTRAINT `teams_ibfk_1` FOREIGN KEY (`club_id`) REFERENCES `clubs` (`club_id`) ON UPDATE CASCADE;
+
+ALTER TABLE `sessions`
+ADD `club_rights` text DEFAULT NULL AFTER `rights`;
+
+-- Named team permissions replace the right columns
+CREATE TABLE `team_permissions` (
+  `team_id` mediumint(9) NOT NULL,
+  `permission` varchar(60) NOT NULL
+);
+
+ALTER TABLE `team_permissions`
+ADD PRIMARY KEY (`team_id`,`permission`);
+
+ALTER TABLE `team_permissions`
+ADD CONSTRAINT `team_permissions_ibfk_1` FOREIGN KEY (`team_id`) REFERENCES `teams` (`team_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+INSERT INTO `team_permissions` (`team_id`, `permission`)
+SELECT team_id, 'club.write' FROM teams WHERE `right_club_write`
+UNION SELECT team_id, 'club.read' FROM teams WHERE `right_club_read`
+UNION SELECT team_id, 'competence.write' FROM teams WHERE `right_competence_write`
+UNION SELECT team_id, 'competence.read' FROM teams WHERE `right_competence_read`
+UNION SELECT team_id, 'course.write' FROM teams WHERE `right_course_write`
+UNION SELECT team_id, 'course.read' FROM teams WHERE `right_course_read`
+UNION SELECT team_id, 'event.write' FROM teams WHERE `right_event_write`
+UNION SELECT team_id, 'event.accept' FROM teams WHERE `right_event_write`
+UNION SELECT team_id, 'event.read' FROM teams WHERE `right_event_read`
+UNION SELECT team_id, 'inventory.write' FROM teams WHERE `right_inventory_write`
+UNION SELECT team_id, 'inventory.loan' FROM teams WHERE `right_inventory_write`
+UNION SELECT team_id, 'inventory.read' FROM teams WHERE `right_inventory_read`
+UNION SELECT team_id, 'location.write' FROM teams WHERE `right_location_write`
+UNION SELECT team_id, 'location.read' FROM teams WHERE `right_location_read`
+UNION SELECT team_id, 'organisation.write' FROM teams WHERE `right_organisation_write`
+UNION SELECT team_id, 'organisation.read' FROM teams WHERE `right_organisation_read`
+UNION SELECT team_id, 'team.write' FROM teams WHERE `right_team_write`
+UNION SELECT team_id, 'team.read' FROM teams WHERE `right_team_read`
+UNION SELECT team_id, 'user.write' FROM teams WHERE `right_user_write`
+UNION SELECT team_id, 'user.bank_account.write' FROM teams WHERE `right_user_write`
+UNION SELECT team_id, 'user.license.write' FROM teams WHERE `right_user_write`
+UNION SELECT team_id, 'user.read' FROM teams WHERE `right_user_read`
+UNION SELECT team_id, 'user.bank_account.read' FROM teams WHERE `right_user_read`
+UNION SELECT team_id, 'user.license.read' FROM teams WHERE `right_user_read`;
+
+UPDATE `api_tokens`
+SET `rights` = CONCAT('[', CONCAT_WS(',',
+  IF(JSON_EXTRACT(`rights`, '$.right_club_write') = 'true', '"club.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_club_read') = 'true', '"club.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_competence_write') = 'true', '"competence.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_competence_read') = 'true', '"competence.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_course_write') = 'true', '"course.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_course_read') = 'true', '"course.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_event_write') = 'true', '"event.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_event_write') = 'true', '"event.accept"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_event_read') = 'true', '"event.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_inventory_write') = 'true', '"inventory.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_inventory_write') = 'true', '"inventory.loan"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_inventory_read') = 'true', '"inventory.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_location_write') = 'true', '"location.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_location_read') = 'true', '"location.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_organisation_write') = 'true', '"organisation.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_organisation_read') = 'true', '"organisation.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_team_write') = 'true', '"team.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_team_read') = 'true', '"team.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_user_write') = 'true', '"user.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_user_write') = 'true', '"user.bank_account.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_user_write') = 'true', '"user.license.write"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_user_read') = 'true', '"user.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_user_read') = 'true', '"user.bank_account.read"', NULL),
+  IF(JSON_EXTRACT(`rights`, '$.right_user_read') = 'true', '"user.license.read"', NULL)
+), ']');
+
+-- Sessions carry rights in the former format and are renewed on the next login
+DELETE FROM `sessions` WHERE `user_id` IS NOT NULL;
+
+ALTER TABLE `teams`
+DROP `right_club_write`,
+DROP `right_club_read`,
+DROP `right_competence_write`,
+DROP `right_competence_read`,
+DROP `right_course_write`,
+DROP `right_course_read`,
+DROP `right_event_write`,
+DROP `right_event_read`,
+DROP `right_inventory_write`,
+DROP `right_inventory_read`,
+DROP `right_location_write`,
+DROP `right_location_read`,
+DROP `right_organisation_write`,
+DROP `right_organisation_read`,
+DROP `right_team_write`,
+DROP `right_team_read`,
+DROP `right_user_write`,
+DROP `right_user_read`;
+
+-- Sensitive personal data is read with separate permissions
+INSERT INTO `team_permissions` (`team_id`, `permission`)
+SELECT team_id, 'user.contact.read' FROM team_permissions WHERE permission = 'user.read'
+UNION SELECT team_id, 'user.identity.read' FROM team_permissions WHERE permission = 'user.read'
+UNION SELECT team_id, 'user.physical.read' FROM team_permissions WHERE permission = 'user.read';
+
+UPDATE `api_tokens`
+SET `rights` = JSON_ARRAY_APPEND(`rights`, '$', 'user.contact.read', '$', 'user.identity.read', '$', 'user.physical.read')
+WHERE JSON_CONTAINS(`rights`, '"user.read"');
+
+-- Time of anonymisation, which excludes the user from retention
+ALTER TABLE `users`
+ADD `anonymised` datetime DEFAULT NULL AFTER `note`;
+
+-- Recurring event series of courses, which materialise into events
+CREATE TABLE `event_series` (
+  `series_id` mediumint(9) NOT NULL,
+  `course_id` mediumint(9) NOT NULL,
+  `title` varchar(100) NOT NULL,
+  `location_id` smallint(6) NOT NULL,
+  `time` time NOT NULL,
+  `duration` smallint(6) NOT NULL,
+  `weekdays` tinyint(3) UNSIGNED NOT NULL,
+  `week_interval` tinyint(3) UNSIGNED NOT NULL DEFAULT 1,
+  `begin` date NOT NULL,
+  `until` date NOT NULL
+);
+
+ALTER TABLE `event_series`
+ADD PRIMARY KEY (`series_id`),
+ADD KEY `REF_course` (`course_id`),
+ADD KEY `REF_location` (`location_id`);
+
+ALTER TABLE `event_series`
+MODIFY `series_id` mediumint(9) NOT NULL AUTO_INCREMENT;
+
+ALTER TABLE `event_series`
+ADD CONSTRAINT `event_series_ibfk_1` FOREIGN KEY (`course_id`) REFERENCES `courses` (`course_id`) ON UPDATE CASCADE,
+ADD CONSTRAINT `event_series_ibfk_2` FOREIGN KEY (`location_id`) REFERENCES `locations` (`location_id`) ON UPDATE CASCADE;
+
+CREATE TABLE `event_series_exceptions` (
+  `series_id` mediumint(9) NOT NULL,
+  `date` date NOT NULL
+);
+
+ALTER TABLE `event_series_exceptions`
+ADD PRIMARY KEY (`series_id`,`date`);
+
+ALTER TABLE `event_series_exceptions`
+ADD CONSTRAINT `event_series_exceptions_ibfk_1` FOREIGN KEY (`series_id`) REFERENCES `event_series` (`series_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+ALTER TABLE `events`
+ADD `series_id` mediumint(9) DEFAULT NULL AFTER `course_id`,
+ADD KEY `REF_series` (`series_id`),
+ADD CONSTRAINT `events_ibfk_3` FOREIGN KEY (`series_id`) REFERENCES `event_series` (`series_id`) ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- Personal calendar feeds are authenticated by a token
+ALTER TABLE `users`
+ADD `feed_token_hash` binary(32) DEFAULT NULL AFTER `anonymised`,
+ADD UNIQUE KEY `feed_token_hash` (`feed_token_hash`);
+
+-- Locations have a capacity and can be divided into sub-areas, which events book partially
+ALTER TABLE `locations`
+ADD `capacity` smallint(6) DEFAULT NULL AFTER `description`,
+ADD `areas` tinyint(3) UNSIGNED NOT NULL DEFAULT 1 AFTER `capacity`;
+
+ALTER TABLE `events`
+ADD `areas` tinyint(3) UNSIGNED DEFAULT NULL AFTER `location_id`;
+
+ALTER TABLE `event_series`
+ADD `areas` tinyint(3) UNSIGNED DEFAULT NULL AFTER `location_id`;
+
+-- OpenID Connect logins are bound to a PKCE verifier, subjects can be linked to users explicitly
+ALTER TABLE `oidc_states`
+ADD `verifier` char(64) NOT NULL AFTER `state`;
+
+ALTER TABLE `users`
+ADD `oidc_subject` varchar(255) DEFAULT NULL AFTER `feed_token_hash`,
+ADD UNIQUE KEY `oidc_subject` (`oidc_subject`);
+
+-- Sessions opened without the second factor of the user lack the permissions of teams requiring one
+ALTER TABLE `sessions`
+ADD `second_factor` tinyint(1) NOT NULL DEFAULT 1 AFTER `club_rights`;
+
+-- Internal notes about users are read with a separate permission, which editors of users keep
+INSERT INTO `team_permissions` (`team_id`, `permission`)
+SELECT team_id, 'user.note.read' FROM team_permissions WHERE permission = 'user.write';
+
+UPDATE `api_tokens`
+SET `rights` = JSON_ARRAY_APPEND(`rights`, '$', 'user.note.read')
+WHERE JSON_CONTAINS(`rights`, '"user.write"');
+
+-- Logins count as activity of users for the retention policy
+ALTER TABLE `users`
+ADD `last_login` datetime DEFAULT NULL AFTER `oidc_subject`;
+
+-- Occurrences edited on their own or removed from a series are kept apart from edits of the series
+ALTER TABLE `events`
+ADD `series_state` enum('ATTACHED','DETACHED','REMOVED') NOT NULL DEFAULT 'ATTACHED' AFTER `series_id`;
+
+-- Password reset requests are throttled like logins
+ALTER TABLE `login_failures`
+MODIFY `category` enum('USER','EVENT','IP','RESET_USER','RESET_IP') NOT NULL;
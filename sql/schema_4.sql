@@ -23,6 +23,40 @@ SET time_zone = "+00:00";
 
 -- --------------------------------------------------------
 
+--
+-- Table structure for table `api_tokens`
+--
+
+CREATE TABLE `api_tokens` (
+  `token_id` mediumint(9) NOT NULL,
+  `user_id` mediumint(9) NOT NULL,
+  `name` varchar(40) NOT NULL,
+  `token_hash` binary(32) NOT NULL,
+  `rights` text NOT NULL,
+  `created` datetime NOT NULL,
+  `expiry` datetime DEFAULT NULL,
+  `last_used` datetime DEFAULT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Table structure for table `audit_log`
+--
+
+CREATE TABLE `audit_log` (
+  `audit_id` bigint(20) NOT NULL,
+  `user_id` mediumint(9) DEFAULT NULL,
+  `event_id` int(11) DEFAULT NULL,
+  `route` varchar(60) NOT NULL,
+  `entity` varchar(40) NOT NULL,
+  `entity_id` bigint(20) NOT NULL,
+  `diff` text DEFAULT NULL,
+  `time` datetime NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- --------------------------------------------------------
+
 --
 -- Table structure for table `bank_accounts`
 --
@@ -138,17 +172,22 @@ CREATE TABLE `course_requirements` (
 CREATE TABLE `events` (
   `event_id` int(11) NOT NULL,
   `event_key` char(12) NOT NULL,
-  `pwd` tinytext NOT NULL,
+  `pwd_pepper` binary(16) NOT NULL,
+  `pwd_hash` binary(32) NOT NULL,
+  `pwd_algorithm` enum('SHA256','ARGON2ID') NOT NULL DEFAULT 'ARGON2ID',
   `title` varchar(100) NOT NULL,
   `begin` datetime NOT NULL,
   `end` datetime NOT NULL,
   `location_id` smallint(6) NOT NULL,
+  `areas` tinyint(3) UNSIGNED DEFAULT NULL,
   `occurrence` enum('OCCURRING','CANCELED','VOIDED') NOT NULL DEFAULT 'OCCURRING',
   `acceptance` enum('DRAFT','PENDING','ACCEPTED','REJECTED') NOT NULL DEFAULT 'DRAFT',
   `public` tinyint(1) NOT NULL DEFAULT 0,
   `scrutable` tinyint(1) NOT NULL DEFAULT 1,
   `note` text NOT NULL DEFAULT '',
-  `course_id` mediumint(9) DEFAULT NULL
+  `course_id` mediumint(9) DEFAULT NULL,
+  `series_id` mediumint(9) DEFAULT NULL,
+  `series_state` enum('ATTACHED','DETACHED','REMOVED') NOT NULL DEFAULT 'ATTACHED'
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 -- --------------------------------------------------------
@@ -213,6 +252,37 @@ CREATE TABLE `event_owners` (
 
 -- --------------------------------------------------------
 
+--
+-- Table structure for table `event_series`
+--
+
+CREATE TABLE `event_series` (
+  `series_id` mediumint(9) NOT NULL,
+  `course_id` mediumint(9) NOT NULL,
+  `title` varchar(100) NOT NULL,
+  `location_id` smallint(6) NOT NULL,
+  `areas` tinyint(3) UNSIGNED DEFAULT NULL,
+  `time` time NOT NULL,
+  `duration` smallint(6) NOT NULL,
+  `weekdays` tinyint(3) UNSIGNED NOT NULL,
+  `week_interval` tinyint(3) UNSIGNED NOT NULL DEFAULT 1,
+  `begin` date NOT NULL,
+  `until` date NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Table structure for table `event_series_exceptions`
+--
+
+CREATE TABLE `event_series_exceptions` (
+  `series_id` mediumint(9) NOT NULL,
+  `date` date NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- --------------------------------------------------------
+
 --
 -- Table structure for table `items`
 --
@@ -258,11 +328,39 @@ CREATE TABLE `locations` (
   `location_id` smallint(6) NOT NULL,
   `location_key` char(10) NOT NULL,
   `name` varchar(100) NOT NULL,
-  `description` varchar(100) NOT NULL
+  `description` varchar(100) NOT NULL,
+  `capacity` smallint(6) DEFAULT NULL,
+  `areas` tinyint(3) UNSIGNED NOT NULL DEFAULT 1
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 -- --------------------------------------------------------
 
+--
+-- Table structure for table `login_failures`
+--
+
+CREATE TABLE `login_failures` (
+  `category` enum('USER','EVENT','IP','RESET_USER','RESET_IP') NOT NULL,
+  `subject` varchar(45) NOT NULL,
+  `attempts` smallint(6) NOT NULL DEFAULT 0,
+  `last_attempt` datetime NOT NULL,
+  `locked_until` datetime DEFAULT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Table structure for table `oidc_states`
+--
+
+CREATE TABLE `oidc_states` (
+  `state` char(30) NOT NULL,
+  `verifier` char(64) NOT NULL,
+  `expiry` datetime NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- --------------------------------------------------------
+
 --
 -- Table structure for table `organisations`
 --
@@ -290,6 +388,37 @@ CREATE TABLE `organisation_affiliations` (
 
 -- --------------------------------------------------------
 
+--
+-- Table structure for table `password_resets`
+--
+
+CREATE TABLE `password_resets` (
+  `token_hash` binary(32) NOT NULL,
+  `user_id` mediumint(9) NOT NULL,
+  `created` datetime NOT NULL,
+  `expiry` datetime NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- --------------------------------------------------------
+
+--
+-- Table structure for table `sessions`
+--
+
+CREATE TABLE `sessions` (
+  `token` char(30) NOT NULL,
+  `user_id` mediumint(9) DEFAULT NULL,
+  `event_id` int(11) DEFAULT NULL,
+  `rights` text DEFAULT NULL,
+  `club_rights` text DEFAULT NULL,
+  `second_factor` tinyint(1) NOT NULL DEFAULT 1,
+  `client` varchar(45) DEFAULT NULL,
+  `created` datetime NOT NULL,
+  `expiry` datetime NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- --------------------------------------------------------
+
 --
 -- Table structure for table `skills`
 --
@@ -313,24 +442,8 @@ CREATE TABLE `teams` (
   `team_key` varchar(10) NOT NULL,
   `name` varchar(30) NOT NULL,
   `description` varchar(100) NOT NULL,
-  `right_club_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_club_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_competence_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_competence_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_course_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_course_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_event_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_event_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_inventory_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_inventory_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_location_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_location_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_organisation_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_organisation_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_team_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_team_read` tinyint(1) NOT NULL DEFAULT 0,
-  `right_user_write` tinyint(1) NOT NULL DEFAULT 0,
-  `right_user_read` tinyint(1) NOT NULL DEFAULT 0
+  `mfa_required` tinyint(1) NOT NULL DEFAULT 0,
+  `club_id` tinyint(4) DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 -- --------------------------------------------------------
@@ -346,6 +459,17 @@ CREATE TABLE `team_members` (
 
 -- --------------------------------------------------------
 
+--
+-- Table structure for table `team_permissions`
+--
+
+CREATE TABLE `team_permissions` (
+  `team_id` mediumint(9) NOT NULL,
+  `permission` varchar(60) NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- --------------------------------------------------------
+
 --
 -- Table structure for table `terms`
 --
@@ -368,6 +492,7 @@ CREATE TABLE `users` (
   `user_id` mediumint(9) NOT NULL,
   `user_key` char(20) NOT NULL,
   `enabled` tinyint(1) NOT NULL DEFAULT 0,
+  `superuser` tinyint(1) NOT NULL DEFAULT 0,
   `credential` mediumint(9) DEFAULT NULL,
   `active` tinyint(1) NOT NULL DEFAULT 1,
   `firstname` varchar(20) NOT NULL,
@@ -386,7 +511,11 @@ CREATE TABLE `users` (
   `bank_account` mediumint(9) DEFAULT NULL,
   `license_main` mediumint(9) DEFAULT NULL,
   `license_extra` mediumint(9) DEFAULT NULL,
-  `note` text DEFAULT NULL
+  `note` text DEFAULT NULL,
+  `anonymised` datetime DEFAULT NULL,
+  `feed_token_hash` binary(32) DEFAULT NULL,
+  `oidc_subject` varchar(255) DEFAULT NULL,
+  `last_login` datetime DEFAULT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 -- --------------------------------------------------------
@@ -415,6 +544,10 @@ CREATE TABLE `user_credentials` (
   `salt` binary(16) NOT NULL,
   `pepper` binary(16) NOT NULL,
   `sp_hash` binary(32) NOT NULL,
+  `algorithm` enum('SHA256','ARGON2ID') NOT NULL DEFAULT 'ARGON2ID',
+  `totp_secret` binary(20) DEFAULT NULL,
+  `totp_enabled` tinyint(1) NOT NULL DEFAULT 0,
+  `totp_step` bigint(20) DEFAULT NULL,
   `since` datetime NOT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
 
@@ -435,6 +568,17 @@ CREATE TABLE `user_possessions` (
 
 -- --------------------------------------------------------
 
+--
+-- Table structure for table `user_recovery_codes`
+--
+
+CREATE TABLE `user_recovery_codes` (
+  `credential_id` mediumint(9) NOT NULL,
+  `code_hash` binary(32) NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+-- --------------------------------------------------------
+
 --
 -- Table structure for table `_info`
 --
@@ -447,6 +591,24 @@ CREATE TABLE `_info` (
 -- Indexes for dumped tables
 --
 
+--
+-- Indexes for table `api_tokens`
+--
+ALTER TABLE `api_tokens`
+  ADD PRIMARY KEY (`token_id`),
+  ADD UNIQUE KEY `token_hash` (`token_hash`),
+  ADD KEY `REF_user` (`user_id`);
+
+--
+-- Indexes for table `audit_log`
+--
+ALTER TABLE `audit_log`
+  ADD PRIMARY KEY (`audit_id`),
+  ADD KEY `REF_user` (`user_id`),
+  ADD KEY `REF_event` (`event_id`),
+  ADD KEY `entity` (`entity`,`entity_id`),
+  ADD KEY `time` (`time`);
+
 --
 -- Indexes for table `bank_accounts`
 --
@@ -512,7 +674,8 @@ ALTER TABLE `events`
   ADD PRIMARY KEY (`event_id`),
   ADD UNIQUE KEY `KEY` (`event_key`),
   ADD KEY `REF_course` (`course_id`),
-  ADD KEY `REF_location` (`location_id`);
+  ADD KEY `REF_location` (`location_id`),
+  ADD KEY `REF_series` (`series_id`);
 
 --
 -- Indexes for table `event_attendance_filters`
@@ -549,6 +712,20 @@ ALTER TABLE `event_owners`
   ADD PRIMARY KEY (`event_id`,`user_id`),
   ADD KEY `REF_user` (`user_id`);
 
+--
+-- Indexes for table `event_series`
+--
+ALTER TABLE `event_series`
+  ADD PRIMARY KEY (`series_id`),
+  ADD KEY `REF_course` (`course_id`),
+  ADD KEY `REF_location` (`location_id`);
+
+--
+-- Indexes for table `event_series_exceptions`
+--
+ALTER TABLE `event_series_exceptions`
+  ADD PRIMARY KEY (`series_id`,`date`);
+
 --
 -- Indexes for table `items`
 --
@@ -575,6 +752,18 @@ ALTER TABLE `locations`
   ADD PRIMARY KEY (`location_id`),
   ADD UNIQUE KEY `KEY` (`location_key`);
 
+--
+-- Indexes for table `login_failures`
+--
+ALTER TABLE `login_failures`
+  ADD PRIMARY KEY (`category`,`subject`);
+
+--
+-- Indexes for table `oidc_states`
+--
+ALTER TABLE `oidc_states`
+  ADD PRIMARY KEY (`state`);
+
 --
 -- Indexes for table `organisations`
 --
@@ -588,6 +777,21 @@ ALTER TABLE `organisation_affiliations`
   ADD KEY `organisation_members_ibfk_1` (`organisation_id`),
   ADD KEY `organisation_members_ibfk_2` (`user_id`);
 
+--
+-- Indexes for table `password_resets`
+--
+ALTER TABLE `password_resets`
+  ADD PRIMARY KEY (`token_hash`),
+  ADD KEY `REF_user` (`user_id`);
+
+--
+-- Indexes for table `sessions`
+--
+ALTER TABLE `sessions`
+  ADD PRIMARY KEY (`token`),
+  ADD KEY `REF_user` (`user_id`),
+  ADD KEY `REF_event` (`event_id`);
+
 --
 -- Indexes for table `skills`
 --
@@ -600,7 +804,8 @@ ALTER TABLE `skills`
 --
 ALTER TABLE `teams`
   ADD PRIMARY KEY (`team_id`),
-  ADD UNIQUE KEY `KEY` (`team_key`);
+  ADD UNIQUE KEY `KEY` (`team_key`),
+  ADD KEY `REF_club` (`club_id`);
 
 --
 -- Indexes for table `team_members`
@@ -609,6 +814,12 @@ ALTER TABLE `team_members`
   ADD PRIMARY KEY (`user_id`,`team_id`),
   ADD KEY `REF_team` (`team_id`);
 
+--
+-- Indexes for table `team_permissions`
+--
+ALTER TABLE `team_permissions`
+  ADD PRIMARY KEY (`team_id`,`permission`);
+
 --
 -- Indexes for table `terms`
 --
@@ -623,6 +834,8 @@ ALTER TABLE `terms`
 ALTER TABLE `users`
   ADD PRIMARY KEY (`user_id`),
   ADD UNIQUE KEY `KEY` (`user_key`),
+  ADD UNIQUE KEY `feed_token_hash` (`feed_token_hash`),
+  ADD UNIQUE KEY `oidc_subject` (`oidc_subject`),
   ADD KEY `users_ibfk_1` (`license_main`),
   ADD KEY `users_ibfk_2` (`license_extra`),
   ADD KEY `users_ibfk_3` (`bank_account`),
@@ -652,10 +865,28 @@ ALTER TABLE `user_possessions`
   ADD KEY `REF_item` (`item_id`),
   ADD KEY `REF_stock` (`stock_id`);
 
+--
+-- Indexes for table `user_recovery_codes`
+--
+ALTER TABLE `user_recovery_codes`
+  ADD PRIMARY KEY (`credential_id`,`code_hash`);
+
 --
 -- AUTO_INCREMENT for dumped tables
 --
 
+--
+-- AUTO_INCREMENT for table `api_tokens`
+--
+ALTER TABLE `api_tokens`
+  MODIFY `token_id` mediumint(9) NOT NULL AUTO_INCREMENT;
+
+--
+-- AUTO_INCREMENT for table `audit_log`
+--
+ALTER TABLE `audit_log`
+  MODIFY `audit_id` bigint(20) NOT NULL AUTO_INCREMENT;
+
 --
 -- AUTO_INCREMENT for table `bank_accounts`
 --
@@ -692,6 +923,12 @@ ALTER TABLE `course_requirements`
 ALTER TABLE `events`
   MODIFY `event_id` int(11) NOT NULL AUTO_INCREMENT;
 
+--
+-- AUTO_INCREMENT for table `event_series`
+--
+ALTER TABLE `event_series`
+  MODIFY `series_id` mediumint(9) NOT NULL AUTO_INCREMENT;
+
 --
 -- AUTO_INCREMENT for table `items`
 --
@@ -768,6 +1005,12 @@ ALTER TABLE `user_possessions`
 -- Constraints for dumped tables
 --
 
+--
+-- Constraints for table `api_tokens`
+--
+ALTER TABLE `api_tokens`
+  ADD CONSTRAINT `api_tokens_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
 --
 -- Constraints for table `club_stocks`
 --
@@ -814,7 +1057,8 @@ ALTER TABLE `course_requirements`
 --
 ALTER TABLE `events`
   ADD CONSTRAINT `events_ibfk_1` FOREIGN KEY (`course_id`) REFERENCES `courses` (`course_id`) ON UPDATE CASCADE,
-  ADD CONSTRAINT `events_ibfk_2` FOREIGN KEY (`location_id`) REFERENCES `locations` (`location_id`) ON UPDATE CASCADE;
+  ADD CONSTRAINT `events_ibfk_2` FOREIGN KEY (`location_id`) REFERENCES `locations` (`location_id`) ON UPDATE CASCADE,
+  ADD CONSTRAINT `events_ibfk_3` FOREIGN KEY (`series_id`) REFERENCES `event_series` (`series_id`) ON DELETE SET NULL ON UPDATE CASCADE;
 
 --
 -- Constraints for table `event_attendance_filters`
@@ -851,6 +1095,19 @@ ALTER TABLE `event_owners`
   ADD CONSTRAINT `event_owners_ibfk_1` FOREIGN KEY (`event_id`) REFERENCES `events` (`event_id`) ON DELETE CASCADE ON UPDATE CASCADE,
   ADD CONSTRAINT `event_owners_ibfk_2` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON DELETE CASCADE ON UPDATE CASCADE;
 
+--
+-- Constraints for table `event_series`
+--
+ALTER TABLE `event_series`
+  ADD CONSTRAINT `event_series_ibfk_1` FOREIGN KEY (`course_id`) REFERENCES `courses` (`course_id`) ON UPDATE CASCADE,
+  ADD CONSTRAINT `event_series_ibfk_2` FOREIGN KEY (`location_id`) REFERENCES `locations` (`location_id`) ON UPDATE CASCADE;
+
+--
+-- Constraints for table `event_series_exceptions`
+--
+ALTER TABLE `event_series_exceptions`
+  ADD CONSTRAINT `event_series_exceptions_ibfk_1` FOREIGN KEY (`series_id`) REFERENCES `event_series` (`series_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
 --
 -- Constraints for table `items`
 --
@@ -864,6 +1121,25 @@ ALTER TABLE `organisation_affiliations`
   ADD CONSTRAINT `organisation_affiliations_ibfk_1` FOREIGN KEY (`organisation_id`) REFERENCES `organisations` (`organisation_id`) ON UPDATE CASCADE,
   ADD CONSTRAINT `organisation_affiliations_ibfk_2` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON UPDATE CASCADE;
 
+--
+-- Constraints for table `password_resets`
+--
+ALTER TABLE `password_resets`
+  ADD CONSTRAINT `password_resets_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+--
+-- Constraints for table `sessions`
+--
+ALTER TABLE `sessions`
+  ADD CONSTRAINT `sessions_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  ADD CONSTRAINT `sessions_ibfk_2` FOREIGN KEY (`event_id`) REFERENCES `events` (`event_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
+--
+-- Constraints for table `teams`
+--
+ALTER TABLE `teams`
+  ADD CONSTRAINT `teams_ibfk_1` FOREIGN KEY (`club_id`) REFERENCES `clubs` (`club_id`) ON UPDATE CASCADE;
+
 --
 -- Constraints for table `team_members`
 --
@@ -871,6 +1147,12 @@ ALTER TABLE `team_members`
   ADD CONSTRAINT `team_members_ibfk_2` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON UPDATE CASCADE,
   ADD CONSTRAINT `team_members_ibfk_3` FOREIGN KEY (`team_id`) REFERENCES `teams` (`team_id`) ON UPDATE CASCADE;
 
+--
+-- Constraints for table `team_permissions`
+--
+ALTER TABLE `team_permissions`
+  ADD CONSTRAINT `team_permissions_ibfk_1` FOREIGN KEY (`team_id`) REFERENCES `teams` (`team_id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
 --
 -- Constraints for table `terms`
 --
@@ -902,6 +1184,12 @@ ALTER TABLE `user_possessions`
   ADD CONSTRAINT `user_possessions_ibfk_1` FOREIGN KEY (`user_id`) REFERENCES `users` (`user_id`) ON UPDATE CASCADE,
   ADD CONSTRAINT `user_possessions_ibfk_2` FOREIGN KEY (`item_id`) REFERENCES `items` (`item_id`) ON UPDATE CASCADE,
   ADD CONSTRAINT `user_possessions_ibfk_3` FOREIGN KEY (`stock_id`) REFERENCES `club_stocks` (`stock_id`) ON UPDATE CASCADE;
+
+--
+-- Constraints for table `user_recovery_codes`
+--
+ALTER TABLE `user_recovery_codes`
+  ADD CONSTRAINT `user_recovery_codes_ibfk_1` FOREIGN KEY (`credential_id`) REFERENCES `user_credentials` (`credential_id`) ON DELETE CASCADE ON UPDATE CASCADE;
 COMMIT;
 
 /*!40101 SET CHARACTER_SET_CLIENT=@OLD_CHARACTER_SET_CLIENT */;
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::Right;
+use crate::error::ErrorKind;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct ApiToken {
+    pub id: u32,
+    pub name: String,
+    pub right: Right,
+    pub created: chrono::NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<chrono::NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<chrono::NaiveDateTime>,
+}
+
+/// Credentials, second factors, tokens and data exports stay out of reach of API tokens, whatever their scope
+pub fn api_token_deny(api_token: Option<u32>) -> Result<(), ErrorKind> {
+    match api_token {
+        None => Ok(()),
+        Some(_) => Err(ErrorKind::ApiTokenDenied),
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct AuditEntry {
+    pub id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<u64>,
+    pub route: String,
+    pub entity: String,
+    pub entity_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<Value>,
+    pub time: chrono::NaiveDateTime,
+}
+
+/// Reduce two snapshots of an entity to the fields that differ
+///
+/// Objects are compared field by field, anything else is kept whole.
+/// Missing snapshots stand for creation and deletion respectively.
+pub fn audit_diff(before: Option<Value>, after: Option<Value>) -> Option<Value> {
+    match (before, after) {
+        (None, None) => None,
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut diff_before = Map::new();
+            let mut diff_after = Map::new();
+
+            for (key, value) in &before {
+                if after.get(key) != Some(value) {
+                    diff_before.insert(key.clone(), value.clone());
+                    diff_after.insert(key.clone(), after.get(key).cloned().unwrap_or(Value::Null));
+                }
+            }
+
+            for (key, value) in &after {
+                if !before.contains_key(key) {
+                    diff_before.insert(key.clone(), Value::Null);
+                    diff_after.insert(key.clone(), value.clone());
+                }
+            }
+
+            Some(serde_json::json!({ "before": diff_before, "after": diff_after }))
+        }
+        (before, after) => Some(serde_json::json!({
+            "before": before.unwrap_or(Value::Null),
+            "after": after.unwrap_or(Value::Null),
+        })),
+    }
+}
+
+/// Replace the values of the given fields in a diff by a marker, so only the fact of their change is kept
+pub fn audit_mask(diff: Option<Value>, fields: &[&str]) -> Option<Value> {
+    let mut diff = diff?;
+
+    for side in ["before", "after"] {
+        if let Some(Value::Object(snapshot)) = diff.get_mut(side) {
+            for (key, value) in snapshot.iter_mut() {
+                if fields.contains(&key.as_str()) && !value.is_null() {
+                    *value = Value::String("<redacted>".to_string());
+                }
+            }
+        }
+    }
+
+    Some(diff)
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Eq, PartialEq, Serialize, Deserialize, Clone)]
+#[derive(Eq, PartialEq, Serialize, Deserialize, Clone)]
 pub struct Credential {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<u32>,
@@ -12,4 +12,24 @@ pub struct Credential {
     pub salt: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub since: Option<chrono::NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// One-time token which was handed out by mail, e.g. for a password reset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Debug output never contains the password or second factor, so credentials can be logged safely
+impl std::fmt::Debug for Credential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credential")
+            .field("id", &self.id)
+            .field("login", &self.login)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("salt", &self.salt)
+            .field("since", &self.since)
+            .field("code", &self.code.as_ref().map(|_| "<redacted>"))
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
 }
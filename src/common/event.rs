@@ -1,4 +1,5 @@
-use crate::common::Location;
+use crate::common::{Acceptance, Location, Occurrence, Permission, Right};
+use crate::error::ErrorKind;
 use serde::{Deserialize, Serialize};
 
 /*
@@ -18,6 +19,9 @@ pub struct Event {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub location: Option<Location>,
+    /// Number of sub-areas of the location the event books, `None` being the whole location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub areas: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -30,6 +34,8 @@ pub struct Event {
     pub scrutable: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub course_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<u32>,
 }
 
 impl Event {
@@ -49,15 +55,23 @@ impl Event {
             begin,
             end,
             location,
+            areas: None,
             note: None,
             occurrence: None,
             acceptance: None,
             public: None,
             scrutable: None,
             course_id: None,
+            series_id: None,
         }
     }
 
+    /// Only accepted events which take place claim their location
+    pub fn claims_location(&self) -> bool {
+        self.acceptance.as_deref() == Some(Acceptance::Accepted.as_str())
+            && self.occurrence.as_deref() == Some(Occurrence::Occurring.as_str())
+    }
+
     pub fn sql_map() -> impl Fn(
         (
             u64,
@@ -83,8 +97,19 @@ impl Event {
                     key: location_key.unwrap(),
                     name: location_name.unwrap(),
                     description: location_description.unwrap(),
+                    capacity: None,
+                    areas: None,
                 }),
             )
         }
     }
 }
+
+/// Booking a location despite conflicts is reserved to those who may accept events
+pub fn event_force(right: &Right, force: bool) -> Result<bool, ErrorKind> {
+    if force && !right.has(Permission::EventAccept) {
+        return Err(ErrorKind::RightEventMissing);
+    }
+
+    Ok(force)
+}
@@ -0,0 +1,116 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Location, SeriesState};
+
+/*
+ * STRUCTS
+ */
+
+/// Weekly recurrence of a course class, which is materialised into one event per occurrence
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct EventSeries {
+    pub id: u32,
+    #[serde(default)]
+    pub course_id: u32,
+    pub title: String,
+    pub location: Location,
+    /// Number of sub-areas of the location each occurrence books, `None` being the whole location
+    #[serde(default)]
+    pub areas: Option<u8>,
+    pub time: NaiveTime,
+    /// Length of each occurrence in minutes
+    pub duration: u16,
+    pub weekdays: Vec<Weekday>,
+    /// Number of weeks between two active weeks, 1 being every week
+    pub interval: u8,
+    pub begin: NaiveDate,
+    pub until: NaiveDate,
+    /// Dates which are skipped although the rule matches them
+    #[serde(default)]
+    pub exceptions: Vec<NaiveDate>,
+}
+
+/// Event of a series as stored, with the date it takes place on
+#[derive(Debug, PartialEq, Clone)]
+pub struct SeriesOccurrence {
+    pub event_id: u64,
+    pub date: NaiveDate,
+    pub state: SeriesState,
+}
+
+/// Changes which bring the stored occurrences of a series in line with its recurrence
+#[derive(Debug, PartialEq, Default)]
+pub struct OccurrencePlan {
+    /// Occurrences which take over the title, time and location of the series
+    pub edit: Vec<u64>,
+    /// Removed occurrences whose date is part of the recurrence again, which are edited as well
+    pub restore: Vec<u64>,
+    pub remove: Vec<u64>,
+    pub create: Vec<NaiveDate>,
+}
+
+impl EventSeries {
+    /// Dates of all occurrences from `begin` to `until`, both inclusive
+    pub fn occurrence_dates(&self) -> Vec<NaiveDate> {
+        let first_week = self.begin.week(Weekday::Mon).first_day();
+        let interval = self.interval.max(1) as i64;
+
+        self.begin
+            .iter_days()
+            .take_while(|date| *date <= self.until)
+            .filter(|date| (date.week(Weekday::Mon).first_day() - first_week).num_weeks() % interval == 0)
+            .filter(|date| self.weekdays.contains(&date.weekday()))
+            .filter(|date| !self.exceptions.contains(date))
+            .collect()
+    }
+
+    /// Begin and end of the occurrence on a given date
+    pub fn occurrence_window(&self, date: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+        let begin = date.and_time(self.time);
+        (begin, begin + chrono::Duration::minutes(self.duration as i64))
+    }
+
+    /// Compare the stored occurrences from a given date onwards with the recurrence.
+    /// Detached occurrences are left alone, but still occupy their date.
+    pub fn occurrence_plan(&self, from: NaiveDate, occurrences: &[SeriesOccurrence]) -> OccurrencePlan {
+        let dates: Vec<NaiveDate> = self
+            .occurrence_dates()
+            .into_iter()
+            .filter(|date| *date >= from)
+            .collect();
+        let mut plan = OccurrencePlan::default();
+
+        for occurrence in occurrences.iter().filter(|occurrence| occurrence.date >= from) {
+            match (occurrence.state, dates.contains(&occurrence.date)) {
+                (SeriesState::Detached, _) => (),
+                (SeriesState::Attached, true) => plan.edit.push(occurrence.event_id),
+                (SeriesState::Removed, true) => {
+                    plan.restore.push(occurrence.event_id);
+                    plan.edit.push(occurrence.event_id);
+                }
+                (SeriesState::Attached, false) => plan.remove.push(occurrence.event_id),
+                (SeriesState::Removed, false) => (),
+            }
+        }
+
+        plan.create = dates
+            .into_iter()
+            .filter(|date| !occurrences.iter().any(|occurrence| occurrence.date == *date))
+            .collect();
+        plan
+    }
+
+    pub fn weekday_mask(&self) -> u8 {
+        self.weekdays
+            .iter()
+            .fold(0, |mask, weekday| mask | 1 << weekday.num_days_from_monday())
+    }
+
+    pub fn weekdays_from_mask(mask: u8) -> Vec<Weekday> {
+        (0..7)
+            .filter(|day| mask & 1 << day != 0)
+            .map(|day| Weekday::try_from(day).unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,64 @@
+use serde::Serialize;
+
+use crate::common::{
+    Affiliation, ApiToken, AuditEntry, Competence, LoginFailure, Possession, Session, Team, Term, User,
+};
+
+/// Everything stored about a single user
+#[derive(Debug, Serialize)]
+pub struct UserDataExport {
+    pub exported: chrono::NaiveDateTime,
+    pub user: User,
+    pub credential: CredentialMetadata,
+    pub teams: Vec<Team>,
+    pub terms: Vec<Term>,
+    pub affiliations: Vec<Affiliation>,
+    pub competences: Vec<Competence>,
+    pub possessions: Vec<Possession>,
+    pub relations: Vec<UserRelation>,
+    pub sessions: Vec<Session>,
+    pub api_tokens: Vec<ApiToken>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login_failure: Option<LoginFailure>,
+    pub audit: Vec<AuditEntry>,
+}
+
+/// What is stored about the logins of a user, without any of the secrets
+#[derive(Debug, Default, Serialize)]
+pub struct CredentialMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_since: Option<chrono::NaiveDateTime>,
+    pub totp_enabled: bool,
+    pub recovery_codes: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oidc_subject: Option<String>,
+    pub calendar_feed: bool,
+}
+
+/// Link of a user to an event or a course, such as a registration or a bookmark
+#[derive(Debug, Serialize)]
+pub struct UserRelation {
+    pub relation: String,
+    pub entity: String,
+    pub entity_id: u64,
+    pub key: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// Audit entries for the export of a user.
+/// Changes of the user to other users are listed without their snapshots, as those belong to the others.
+pub fn export_audit(entries: Vec<AuditEntry>, user_id: u64) -> Vec<AuditEntry> {
+    entries
+        .into_iter()
+        .map(|mut entry| {
+            if entry.entity.starts_with("user") && entry.entity_id != user_id {
+                entry.diff = None;
+            }
+            entry
+        })
+        .collect()
+}
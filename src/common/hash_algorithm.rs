@@ -0,0 +1,38 @@
+#[derive(Debug, PartialEq, Clone)]
+pub enum HashAlgorithm {
+    Sha256,
+    Argon2id,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HashAlgorithm::Sha256 => "SHA256",
+            HashAlgorithm::Argon2id => "ARGON2ID",
+        }
+    }
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for HashAlgorithm {
+    type Err = crate::error::ErrorKind;
+
+    fn from_str<'r>(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SHA256" => Ok(HashAlgorithm::Sha256),
+            "ARGON2ID" => Ok(HashAlgorithm::Argon2id),
+            _ => Err(crate::error::ErrorKind::Parsing),
+        }
+    }
+}
+
+impl core::convert::From<HashAlgorithm> for mysql_common::Value {
+    fn from(a: HashAlgorithm) -> Self {
+        mysql_common::Value::Bytes(a.to_string().into_bytes())
+    }
+}
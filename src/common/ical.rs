@@ -0,0 +1,327 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::common::{Event, Occurrence};
+
+/*
+ * iCalendar (RFC 5545) rendering
+ */
+
+/// Render events as a read-only iCalendar feed, voided events are left out
+pub fn ical_feed(name: &str, events: &[Event], stamp: chrono::NaiveDateTime) -> String {
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//cptserver//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", ical_escape(name)),
+    ];
+
+    for event in events {
+        let status = match event.occurrence.as_deref() {
+            Some(occurrence) if occurrence == Occurrence::Voided.as_str() => continue,
+            Some(occurrence) if occurrence == Occurrence::Canceled.as_str() => "CANCELLED",
+            _ => "CONFIRMED",
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:event-{}@cptserver", event.id));
+        lines.push(format!("DTSTAMP:{}", ical_datetime(&stamp)));
+        lines.push(format!("DTSTART:{}", ical_datetime(&event.begin)));
+        lines.push(format!("DTEND:{}", ical_datetime(&event.end)));
+        lines.push(format!("SUMMARY:{}", ical_escape(&event.title)));
+        if let Some(location) = &event.location {
+            lines.push(format!("LOCATION:{}", ical_escape(&location.name)));
+        }
+        lines.push(format!("STATUS:{}", status));
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| ical_fold(line)).collect()
+}
+
+/// Event times are stored in UTC
+pub fn ical_datetime(datetime: &chrono::NaiveDateTime) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub fn ical_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Terminate a content line and fold it into chunks of at most 75 octets
+fn ical_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+/*
+ * iCalendar parsing
+ */
+
+/// VEVENT of an uploaded calendar, properties which could not be read are `None`
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct IcalEvent {
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub location: Option<String>,
+    pub begin: Option<chrono::NaiveDateTime>,
+    pub end: Option<chrono::NaiveDateTime>,
+    pub recurring: bool,
+    /// Time zone of the begin or end which could not be resolved into a fixed offset, leaving those `None`
+    pub zone: Option<String>,
+}
+
+/// Outcome of importing a single VEVENT
+#[derive(Debug, Serialize)]
+pub struct EventImport {
+    pub uid: Option<String>,
+    pub title: Option<String>,
+    pub begin: Option<chrono::NaiveDateTime>,
+    pub end: Option<chrono::NaiveDateTime>,
+    pub location_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Read all VEVENTs of a calendar.
+/// Times in UTC are taken as they are, floating local times are shifted by `utc_offset` into UTC.
+/// Zoned local times are only resolved for UTC and for zones of the calendar with a single offset,
+/// as following daylight saving time would require a time zone database.
+pub fn ical_parse(text: &str, utc_offset: chrono::Duration) -> Vec<IcalEvent> {
+    let lines = ical_unfold(text);
+    let zones = ical_parse_zones(&lines);
+
+    let mut events = Vec::new();
+    let mut current: Option<IcalEvent> = None;
+    let mut duration: Option<chrono::Duration> = None;
+
+    for line in lines {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut params = head.split(';');
+        let name = params.next().unwrap_or_default().to_ascii_uppercase();
+        let params: Vec<&str> = params.collect();
+        let date_only = params.iter().any(|param| param.eq_ignore_ascii_case("VALUE=DATE"));
+        let zone = params
+            .iter()
+            .find_map(|param| {
+                param
+                    .split_once('=')
+                    .filter(|(key, _)| key.eq_ignore_ascii_case("TZID"))
+            })
+            .map(|(_, tzid)| tzid.trim_matches('"'));
+
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => {
+                current = Some(IcalEvent::default());
+                duration = None;
+            }
+            ("END", "VEVENT") => {
+                if let Some(mut event) = current.take() {
+                    if event.end.is_none() && event.zone.is_none() {
+                        event.end = event.begin.map(|begin| begin + duration.unwrap_or_default());
+                    }
+                    events.push(event);
+                }
+            }
+            _ => (),
+        }
+
+        let Some(event) = current.as_mut() else {
+            continue;
+        };
+
+        match name.as_str() {
+            "UID" => event.uid = Some(ical_unescape(value)),
+            "SUMMARY" => event.summary = Some(ical_unescape(value)),
+            "LOCATION" => event.location = Some(ical_unescape(value)),
+            "DTSTART" => {
+                event.begin = ical_parse_zoned(event, value, zone, &zones, utc_offset);
+                // All day events last until the next day unless stated otherwise
+                if date_only && duration.is_none() {
+                    duration = Some(chrono::Duration::days(1));
+                }
+            }
+            "DTEND" => event.end = ical_parse_zoned(event, value, zone, &zones, utc_offset),
+            "DURATION" => duration = ical_parse_duration(value),
+            "RRULE" | "RDATE" => event.recurring = true,
+            _ => (),
+        }
+    }
+
+    events
+}
+
+/// Offsets of the VTIMEZONEs of a calendar, `None` for zones which change their offset
+fn ical_parse_zones(lines: &[String]) -> HashMap<String, Option<chrono::Duration>> {
+    let mut zones = HashMap::new();
+    let mut current: Option<(String, Vec<Option<chrono::Duration>>)> = None;
+
+    for line in lines {
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = head.split(';').next().unwrap_or_default().to_ascii_uppercase();
+
+        match (name.as_str(), value, current.as_mut()) {
+            ("BEGIN", "VTIMEZONE", _) => current = Some((String::new(), Vec::new())),
+            ("END", "VTIMEZONE", _) => {
+                if let Some((tzid, offsets)) = current.take() {
+                    let offset = match offsets.split_first() {
+                        Some((first, rest)) if rest.iter().all(|offset| offset == first) => *first,
+                        _ => None,
+                    };
+                    zones.insert(tzid, offset);
+                }
+            }
+            ("TZID", _, Some((tzid, _))) => *tzid = value.to_string(),
+            ("TZOFFSETTO", _, Some((_, offsets))) => offsets.push(ical_parse_offset(value)),
+            _ => (),
+        }
+    }
+
+    zones
+}
+
+/// Offsets like `+0100` or `-053000`
+fn ical_parse_offset(value: &str) -> Option<chrono::Duration> {
+    let (sign, digits) = match value.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+
+    if !(digits.len() == 4 || digits.len() == 6) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i64 = digits[0..2].parse().ok()?;
+    let minutes: i64 = digits[2..4].parse().ok()?;
+    let seconds: i64 = digits.get(4..6).unwrap_or("0").parse().ok()?;
+    Some(chrono::Duration::seconds(
+        sign * (hours * 3600 + minutes * 60 + seconds),
+    ))
+}
+
+/// Resolve a date or time with an optional TZID, unresolvable zones are noted on the event
+fn ical_parse_zoned(
+    event: &mut IcalEvent,
+    value: &str,
+    zone: Option<&str>,
+    zones: &HashMap<String, Option<chrono::Duration>>,
+    utc_offset: chrono::Duration,
+) -> Option<chrono::NaiveDateTime> {
+    let Some(tzid) = zone else {
+        return ical_parse_datetime(value, utc_offset);
+    };
+
+    if ["UTC", "GMT", "Etc/UTC", "Etc/GMT"].contains(&tzid) {
+        return ical_parse_datetime(value, chrono::Duration::zero());
+    }
+
+    match zones.get(tzid) {
+        Some(Some(offset)) => ical_parse_datetime(value, *offset),
+        _ => {
+            event.zone = Some(tzid.to_string());
+            None
+        }
+    }
+}
+
+/// Join content lines which were folded by a leading space or tab
+fn ical_unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+fn ical_unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => (),
+        }
+    }
+
+    unescaped
+}
+
+fn ical_parse_datetime(value: &str, utc_offset: chrono::Duration) -> Option<chrono::NaiveDateTime> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        return chrono::NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok();
+    }
+
+    let local = chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(value, "%Y%m%d").map(|date| date.and_time(chrono::NaiveTime::MIN))
+        })
+        .ok()?;
+    Some(local - utc_offset)
+}
+
+/// Durations like `PT1H30M` or `P1D`, negative durations are rejected
+fn ical_parse_duration(value: &str) -> Option<chrono::Duration> {
+    let value = value.strip_prefix('+').unwrap_or(value).strip_prefix('P')?;
+    let mut duration = chrono::Duration::zero();
+    let mut number = String::new();
+    let mut time = false;
+
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => time = true,
+            _ => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                duration += match (c, time) {
+                    ('W', false) => chrono::Duration::weeks(amount),
+                    ('D', false) => chrono::Duration::days(amount),
+                    ('H', true) => chrono::Duration::hours(amount),
+                    ('M', true) => chrono::Duration::minutes(amount),
+                    ('S', true) => chrono::Duration::seconds(amount),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(duration)
+}
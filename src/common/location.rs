@@ -1,9 +1,84 @@
 use serde::{Deserialize, Serialize};
 
+use crate::common::Event;
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Location {
     pub id: u32,
     pub key: String,
     pub name: String,
     pub description: String,
+    /// Number of people the location holds, `None` being unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u16>,
+    /// Number of sub-areas the location can be divided into
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub areas: Option<u8>,
+}
+
+impl Location {
+    /// Number of people an event booking some of the sub-areas may hold, `None` being unlimited
+    pub fn event_capacity(&self, areas: Option<u8>) -> Option<u32> {
+        let capacity = self.capacity? as u32;
+        let total = self.areas.unwrap_or(1).max(1) as u32;
+
+        match areas {
+            None => Some(capacity),
+            Some(areas) => Some(capacity * (areas as u32).clamp(1, total) / total),
+        }
+    }
+}
+
+/// Highest number of sub-areas which are booked at the same time during a time window
+pub fn location_area_peak(
+    begin: chrono::NaiveDateTime,
+    end: chrono::NaiveDateTime,
+    bookings: &[(chrono::NaiveDateTime, chrono::NaiveDateTime, u8)],
+) -> u32 {
+    // The usage only rises when a booking begins, so the peak is found at one of these instants
+    let instants = std::iter::once(begin).chain(
+        bookings
+            .iter()
+            .map(|(booking_begin, _, _)| *booking_begin)
+            .filter(|instant| begin < *instant && *instant < end),
+    );
+
+    instants
+        .map(|instant| {
+            bookings
+                .iter()
+                .filter(|(booking_begin, booking_end, _)| *booking_begin <= instant && instant < *booking_end)
+                .map(|(_, _, areas)| *areas as u32)
+                .sum()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Whether enough sub-areas of the location remain for an event during its time window.
+/// Only other events which claim the same location count, each with its share of the `total` sub-areas.
+pub fn location_free(event: &Event, others: &[Event], total: u8) -> bool {
+    let claims: Vec<&Event> = others.iter().filter(|other| other.claims_location()).collect();
+    location_share_free(event, &claims, total)
+}
+
+/// Whether enough sub-areas of the location remain for an event next to all other events at the same location,
+/// regardless of their acceptance, like those of a calendar which are imported together
+pub fn location_share_free(event: &Event, others: &[&Event], total: u8) -> bool {
+    let location = match &event.location {
+        None => return true,
+        Some(location) => location.id,
+    };
+    let total = total.max(1);
+
+    let bookings: Vec<(chrono::NaiveDateTime, chrono::NaiveDateTime, u8)> = others
+        .iter()
+        .filter(|other| other.location.as_ref().map(|location| location.id) == Some(location))
+        .filter(|other| other.begin < event.end && event.begin < other.end)
+        .map(|other| (other.begin, other.end, other.areas.unwrap_or(total).clamp(1, total)))
+        .collect();
+
+    let peak = location_area_peak(event.begin, event.end, &bookings);
+    let share = event.areas.unwrap_or(total).clamp(1, total) as u32;
+    peak + share <= total as u32
 }
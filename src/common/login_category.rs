@@ -0,0 +1,70 @@
+use rocket::form::error::{ErrorKind, Errors};
+use rocket::form::{self, DataField, FromFormField, ValueField};
+use serde::{Deserialize, Serialize};
+
+pub use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LoginCategory {
+    User,
+    Event,
+    Ip,
+    /// Password reset requests for a user key
+    #[serde(rename = "RESET_USER")]
+    ResetUser,
+    /// Password reset requests from an address
+    #[serde(rename = "RESET_IP")]
+    ResetIp,
+}
+
+impl LoginCategory {
+    pub fn as_str(&self) -> &str {
+        match self {
+            LoginCategory::User => "USER",
+            LoginCategory::Event => "EVENT",
+            LoginCategory::Ip => "IP",
+            LoginCategory::ResetUser => "RESET_USER",
+            LoginCategory::ResetIp => "RESET_IP",
+        }
+    }
+}
+
+impl std::fmt::Display for LoginCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for LoginCategory {
+    type Err = crate::error::ErrorKind;
+
+    fn from_str<'r>(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "USER" => Ok(LoginCategory::User),
+            "EVENT" => Ok(LoginCategory::Event),
+            "IP" => Ok(LoginCategory::Ip),
+            "RESET_USER" => Ok(LoginCategory::ResetUser),
+            "RESET_IP" => Ok(LoginCategory::ResetIp),
+            _ => Err(crate::error::ErrorKind::Parsing),
+        }
+    }
+}
+
+impl core::convert::From<LoginCategory> for mysql_common::Value {
+    fn from(c: LoginCategory) -> Self {
+        mysql_common::Value::Bytes(c.to_string().into_bytes())
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromFormField<'r> for LoginCategory {
+    fn from_value(field: ValueField<'r>) -> form::Result<'r, Self> {
+        LoginCategory::from_str(field.value).map_err(|_| Errors::from(ErrorKind::Missing))
+    }
+
+    async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
+        let web_string: String = crate::common::parse_field(field).await?;
+        LoginCategory::from_str(&web_string).map_err(|_| Errors::from(ErrorKind::Missing))
+    }
+}
@@ -0,0 +1,12 @@
+use crate::common::LoginCategory;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct LoginFailure {
+    pub category: LoginCategory,
+    pub subject: String,
+    pub attempts: u16,
+    pub last_attempt: chrono::NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked_until: Option<chrono::NaiveDateTime>,
+}
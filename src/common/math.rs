@@ -1,6 +1,7 @@
 use rand::Rng;
 use sha2::{Digest, Sha256};
 
+use crate::common::HashAlgorithm;
 use crate::error::ErrorKind;
 
 pub fn random_bytes(size: usize) -> Vec<u8> {
@@ -44,12 +45,35 @@ pub fn hash_sha256(meal: &[u8], pepper: &[u8]) -> Vec<u8> {
     let spiced_meal: Vec<u8> = meal.iter().cloned().chain(pepper.iter().cloned()).collect();
     let digested_meal = Sha256::digest(&spiced_meal);
 
-    // println!("spiced meal: {:?}", spiced_meal);
-    // println!("digested meal: {:?}", digested_meal);
-
     digested_meal.to_vec()
 }
 
+pub fn hash_argon2id(meal: &[u8], pepper: &[u8]) -> Result<Vec<u8>, ErrorKind> {
+    let mut digested_meal = vec![0u8; 32];
+
+    argon2::Argon2::default()
+        .hash_password_into(meal, pepper, &mut digested_meal)
+        .map_err(|_| ErrorKind::HashError)?;
+
+    Ok(digested_meal)
+}
+
+pub fn hash_password(meal: &[u8], pepper: &[u8], algorithm: &HashAlgorithm) -> Result<Vec<u8>, ErrorKind> {
+    match algorithm {
+        HashAlgorithm::Sha256 => Ok(hash_sha256(meal, pepper)),
+        HashAlgorithm::Argon2id => hash_argon2id(meal, pepper),
+    }
+}
+
+/// Compare two digests without leaking the position of the first mismatch through timing
+pub fn digest_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+
+    left.iter().zip(right.iter()).fold(0u8, |acc, (l, r)| acc | (l ^ r)) == 0
+}
+
 pub fn hash128_string(meal: &str) -> Vec<u8> {
     let digested_meal = Sha256::digest(meal.as_bytes());
 
@@ -4,6 +4,8 @@ use rocket::form::{self, DataField, FromFormField, ValueField};
 // Common module
 mod acceptance;
 mod affiliation;
+mod api_token;
+mod audit;
 mod bank_account;
 mod clock;
 mod club;
@@ -11,21 +13,37 @@ mod confirmation;
 mod course;
 mod credential;
 mod event;
+mod event_series;
+mod export;
 pub mod fs;
 mod gender;
+mod hash_algorithm;
+mod ical;
 mod item;
 mod license;
 mod location;
+mod login_category;
+mod login_failure;
 mod math;
 mod occurrence;
+mod oidc;
 mod organisation;
+mod permission;
+mod redact;
+mod retention;
+mod series_scope;
+mod series_state;
+mod session;
 mod skill;
 mod team;
+mod totp;
 mod user;
 
 // Re-export
 pub use acceptance::*;
 pub use affiliation::*;
+pub use api_token::*;
+pub use audit::*;
 pub use bank_account::*;
 pub use clock::*;
 pub use club::*;
@@ -33,16 +51,30 @@ pub use confirmation::*;
 pub use course::*;
 pub use credential::*;
 pub use event::*;
+pub use event_series::*;
+pub use export::*;
 #[allow(unused_imports)]
 pub use gender::*;
+pub use hash_algorithm::*;
+pub use ical::*;
 pub use item::*;
 pub use license::*;
 pub use location::*;
+pub use login_category::*;
+pub use login_failure::*;
 pub use math::*;
 pub use occurrence::*;
+pub use oidc::*;
 pub use organisation::*;
+pub use permission::*;
+pub use redact::*;
+pub use retention::*;
+pub use series_scope::*;
+pub use series_state::*;
+pub use session::*;
 pub use skill::*;
 pub use team::*;
+pub use totp::*;
 pub use user::*;
 
 pub async fn parse_field<'r>(field: DataField<'r, '_>) -> form::Result<'r, String> {
@@ -0,0 +1,122 @@
+use serde::Deserialize;
+
+use crate::error::ErrorKind;
+
+/// Endpoints and client registration of an OpenID Connect identity provider
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcUserInfo {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct OidcDiscovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct OidcTokenResponse {
+    access_token: String,
+}
+
+impl OidcProvider {
+    /// Look up the endpoints from the discovery document of the issuer
+    pub fn discover(issuer: &str, client_id: &str, client_secret: &str, redirect_uri: &str) -> Result<Self, ErrorKind> {
+        let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+        let discovery: OidcDiscovery = ureq::get(&url)
+            .call()
+            .map_err(|_| ErrorKind::OidcFailed)?
+            .into_json()
+            .map_err(|_| ErrorKind::OidcFailed)?;
+
+        Ok(OidcProvider {
+            authorization_endpoint: discovery.authorization_endpoint,
+            token_endpoint: discovery.token_endpoint,
+            userinfo_endpoint: discovery.userinfo_endpoint,
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+        })
+    }
+
+    /// URL the user agent has to visit to authenticate at the provider
+    pub fn authorization_url(&self, state: &str, verifier: &str) -> Result<String, ErrorKind> {
+        let mut url = url::Url::parse(&self.authorization_endpoint).map_err(|_| ErrorKind::OidcFailed)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("scope", "openid email")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .append_pair("state", state)
+            .append_pair("code_challenge", &pkce_challenge(verifier))
+            .append_pair("code_challenge_method", "S256");
+        Ok(url.to_string())
+    }
+
+    /// Redeem an authorization code and fetch the claims of the authenticated user
+    pub fn user_info(&self, code: &str, verifier: &str) -> Result<OidcUserInfo, ErrorKind> {
+        let token: OidcTokenResponse = ureq::post(&self.token_endpoint)
+            .send_form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.redirect_uri),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("code_verifier", verifier),
+            ])
+            .map_err(|_| ErrorKind::OidcFailed)?
+            .into_json()
+            .map_err(|_| ErrorKind::OidcFailed)?;
+
+        let info: OidcUserInfo = ureq::get(&self.userinfo_endpoint)
+            .set("Authorization", &format!("Bearer {}", token.access_token))
+            .call()
+            .map_err(|_| ErrorKind::OidcFailed)?
+            .into_json()
+            .map_err(|_| ErrorKind::OidcFailed)?;
+
+        Ok(info)
+    }
+}
+
+/// PKCE challenge of a code verifier with the S256 method (RFC 7636)
+pub fn pkce_challenge(verifier: &str) -> String {
+    base64url_encode(&crate::common::hash_sha256(verifier.as_bytes(), &[]))
+}
+
+/// Unpadded RFC 4648 base64 with the URL safe alphabet
+fn base64url_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits: u32 = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 6 {
+            bits -= 6;
+            encoded.push(ALPHABET[((buffer >> bits) & 0x3f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        encoded.push(ALPHABET[((buffer << (6 - bits)) & 0x3f) as usize] as char);
+    }
+
+    encoded
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+/// Named permission which teams grant to their members
+///
+/// Permissions ending in `.read` only expose data, all others modify it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Permission {
+    ClubRead,
+    ClubWrite,
+    CompetenceRead,
+    CompetenceWrite,
+    CourseRead,
+    CourseWrite,
+    EventRead,
+    EventWrite,
+    /// Accept or reject events submitted by their owners
+    EventAccept,
+    InventoryRead,
+    InventoryWrite,
+    /// Loan, return, hand out and restock items of a stock
+    InventoryLoan,
+    LocationRead,
+    LocationWrite,
+    OrganisationRead,
+    OrganisationWrite,
+    TeamRead,
+    TeamWrite,
+    UserRead,
+    UserWrite,
+    /// See the address, email and phone of users
+    UserContactRead,
+    /// See the birth date, birth location and nationality of users
+    UserIdentityRead,
+    /// See the height and weight of users
+    UserPhysicalRead,
+    /// See the bank accounts of users
+    UserBankAccountRead,
+    /// Edit the bank accounts of users
+    UserBankAccountWrite,
+    /// See the licenses of users
+    UserLicenseRead,
+    /// Edit the licenses of users
+    UserLicenseWrite,
+    /// See the internal notes about users
+    UserNoteRead,
+}
+
+impl Permission {
+    /// Registry of all permissions
+    pub const ALL: &'static [Permission] = &[
+        Permission::ClubRead,
+        Permission::ClubWrite,
+        Permission::CompetenceRead,
+        Permission::CompetenceWrite,
+        Permission::CourseRead,
+        Permission::CourseWrite,
+        Permission::EventRead,
+        Permission::EventWrite,
+        Permission::EventAccept,
+        Permission::InventoryRead,
+        Permission::InventoryWrite,
+        Permission::InventoryLoan,
+        Permission::LocationRead,
+        Permission::LocationWrite,
+        Permission::OrganisationRead,
+        Permission::OrganisationWrite,
+        Permission::TeamRead,
+        Permission::TeamWrite,
+        Permission::UserRead,
+        Permission::UserWrite,
+        Permission::UserContactRead,
+        Permission::UserIdentityRead,
+        Permission::UserPhysicalRead,
+        Permission::UserBankAccountRead,
+        Permission::UserBankAccountWrite,
+        Permission::UserLicenseRead,
+        Permission::UserLicenseWrite,
+        Permission::UserNoteRead,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Permission::ClubRead => "club.read",
+            Permission::ClubWrite => "club.write",
+            Permission::CompetenceRead => "competence.read",
+            Permission::CompetenceWrite => "competence.write",
+            Permission::CourseRead => "course.read",
+            Permission::CourseWrite => "course.write",
+            Permission::EventRead => "event.read",
+            Permission::EventWrite => "event.write",
+            Permission::EventAccept => "event.accept",
+            Permission::InventoryRead => "inventory.read",
+            Permission::InventoryWrite => "inventory.write",
+            Permission::InventoryLoan => "inventory.loan",
+            Permission::LocationRead => "location.read",
+            Permission::LocationWrite => "location.write",
+            Permission::OrganisationRead => "organisation.read",
+            Permission::OrganisationWrite => "organisation.write",
+            Permission::TeamRead => "team.read",
+            Permission::TeamWrite => "team.write",
+            Permission::UserRead => "user.read",
+            Permission::UserWrite => "user.write",
+            Permission::UserContactRead => "user.contact.read",
+            Permission::UserIdentityRead => "user.identity.read",
+            Permission::UserPhysicalRead => "user.physical.read",
+            Permission::UserBankAccountRead => "user.bank_account.read",
+            Permission::UserBankAccountWrite => "user.bank_account.write",
+            Permission::UserLicenseRead => "user.license.read",
+            Permission::UserLicenseWrite => "user.license.write",
+            Permission::UserNoteRead => "user.note.read",
+        }
+    }
+
+    /// Whether the permission only exposes data
+    pub fn is_read(&self) -> bool {
+        self.as_str().ends_with(".read")
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Permission {
+    type Err = crate::error::ErrorKind;
+
+    fn from_str<'r>(s: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .iter()
+            .find(|permission| permission.as_str() == s)
+            .copied()
+            .ok_or(crate::error::ErrorKind::Parsing)
+    }
+}
+
+impl TryFrom<String> for Permission {
+    type Error = crate::error::ErrorKind;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Permission> for String {
+    fn from(p: Permission) -> Self {
+        p.as_str().to_string()
+    }
+}
+
+impl core::convert::From<Permission> for mysql_common::Value {
+    fn from(p: Permission) -> Self {
+        mysql_common::Value::Bytes(p.to_string().into_bytes())
+    }
+}
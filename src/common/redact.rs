@@ -0,0 +1,26 @@
+/// Replace the values of query parameters in URIs, as they can carry tokens and codes.
+/// Calendar feeds for instance are authenticated by a token in the URI, since calendar clients cannot send headers.
+pub fn redact_query(msg: &str) -> String {
+    let mut redacted = String::with_capacity(msg.len());
+    let mut rest = msg;
+
+    while let Some(index) = rest.find('?') {
+        redacted.push_str(&rest[..=index]);
+        rest = &rest[index + 1..];
+
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let query: Vec<String> = rest[..end]
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) => format!("{}=***", key),
+                None => pair.to_string(),
+            })
+            .collect();
+
+        redacted.push_str(&query.join("&"));
+        rest = &rest[end..];
+    }
+
+    redacted.push_str(rest);
+    redacted
+}
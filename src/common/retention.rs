@@ -0,0 +1,28 @@
+use serde::Serialize;
+
+use crate::common::User;
+
+/// Data which is due for deletion according to the retention policies
+#[derive(Debug, Serialize, Default)]
+pub struct RetentionReport {
+    pub users: Vec<User>,
+    pub events: Vec<RetentionEvent>,
+    pub audit_entries: u64,
+}
+
+/// Event whose attendance is due for deletion
+#[derive(Debug, Serialize)]
+pub struct RetentionEvent {
+    pub id: u64,
+    pub key: String,
+    pub title: String,
+    pub end: chrono::NaiveDateTime,
+    pub presences: u64,
+    pub registrations: u64,
+}
+
+/// Same time of the year a number of calendar years ago, leap days fall back to the 28th of February
+pub fn retention_cutoff_years(now: chrono::NaiveDateTime, years: u16) -> chrono::NaiveDateTime {
+    now.checked_sub_months(chrono::Months::new(years as u32 * 12))
+        .unwrap_or(chrono::NaiveDateTime::MIN)
+}
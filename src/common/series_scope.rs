@@ -0,0 +1,53 @@
+use rocket::form::error::{ErrorKind, Errors};
+use rocket::form::{self, DataField, FromFormField, ValueField};
+
+pub use std::str::FromStr;
+
+/// Which occurrences of an event series are affected by an edit
+#[derive(Debug, PartialEq, Clone)]
+pub enum SeriesScope {
+    This,
+    Following,
+    All,
+}
+
+impl SeriesScope {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SeriesScope::This => "THIS",
+            SeriesScope::Following => "FOLLOWING",
+            SeriesScope::All => "ALL",
+        }
+    }
+}
+
+impl std::fmt::Display for SeriesScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for SeriesScope {
+    type Err = crate::error::ErrorKind;
+
+    fn from_str<'r>(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "THIS" => Ok(SeriesScope::This),
+            "FOLLOWING" => Ok(SeriesScope::Following),
+            "ALL" => Ok(SeriesScope::All),
+            _ => Err(crate::error::ErrorKind::Parsing),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromFormField<'r> for SeriesScope {
+    fn from_value(field: ValueField<'r>) -> form::Result<'r, Self> {
+        SeriesScope::from_str(field.value).map_err(|_| Errors::from(ErrorKind::Missing))
+    }
+
+    async fn from_data(field: DataField<'r, '_>) -> form::Result<'r, Self> {
+        let web_string: String = crate::common::parse_field(field).await?;
+        SeriesScope::from_str(&web_string).map_err(|_| Errors::from(ErrorKind::Missing))
+    }
+}
@@ -0,0 +1,45 @@
+/// How an event relates to the recurrence of its series
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SeriesState {
+    /// Follows every edit of the series
+    Attached,
+    /// Edited on its own and kept as is by edits of the series
+    Detached,
+    /// No longer part of the recurrence, but canceled instead of deleted due to its attendance
+    Removed,
+}
+
+impl SeriesState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SeriesState::Attached => "ATTACHED",
+            SeriesState::Detached => "DETACHED",
+            SeriesState::Removed => "REMOVED",
+        }
+    }
+}
+
+impl std::fmt::Display for SeriesState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for SeriesState {
+    type Err = crate::error::ErrorKind;
+
+    fn from_str<'r>(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ATTACHED" => Ok(SeriesState::Attached),
+            "DETACHED" => Ok(SeriesState::Detached),
+            "REMOVED" => Ok(SeriesState::Removed),
+            _ => Err(crate::error::ErrorKind::Parsing),
+        }
+    }
+}
+
+impl core::convert::From<SeriesState> for mysql_common::Value {
+    fn from(v: SeriesState) -> Self {
+        mysql_common::Value::Bytes(v.to_string().into_bytes())
+    }
+}
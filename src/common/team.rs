@@ -1,32 +1,98 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use serde::{Deserialize, Serialize};
 
+use crate::common::Permission;
+use crate::error::ErrorKind;
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Team {
     pub id: u32,
     pub key: String,
     pub name: String,
     pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfa_required: Option<bool>,
+    /// Club to which the rights of the team are confined, all clubs if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub club_id: Option<u32>,
     pub right: Option<Right>,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+/// Set of permissions granted by teams
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Default)]
+#[serde(transparent)]
 pub struct Right {
-    pub right_club_write: bool,
-    pub right_club_read: bool,
-    pub right_competence_write: bool,
-    pub right_competence_read: bool,
-    pub right_course_write: bool,
-    pub right_course_read: bool,
-    pub right_event_write: bool,
-    pub right_event_read: bool,
-    pub right_inventory_write: bool,
-    pub right_inventory_read: bool,
-    pub right_location_write: bool,
-    pub right_location_read: bool,
-    pub right_organisation_write: bool,
-    pub right_organisation_read: bool,
-    pub right_team_write: bool,
-    pub right_team_read: bool,
-    pub right_user_write: bool,
-    pub right_user_read: bool,
+    pub permissions: BTreeSet<Permission>,
+}
+
+/// Rights granted by teams confined to a club, keyed by the club
+pub type ClubRights = BTreeMap<u32, Right>;
+
+impl Right {
+    /// All rights granted
+    pub fn full() -> Right {
+        Right {
+            permissions: Permission::ALL.iter().copied().collect(),
+        }
+    }
+
+    /// Rights of a superuser, whose write permissions require the second factor like teams requiring it
+    pub fn superuser(second_factor: bool) -> Right {
+        let full = Right::full();
+        match second_factor {
+            true => full,
+            false => Right {
+                permissions: full.permissions.into_iter().filter(Permission::is_read).collect(),
+            },
+        }
+    }
+
+    /// Whether the permission is granted
+    pub fn has(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    /// Rights granted by both
+    pub fn intersect(&self, other: &Right) -> Right {
+        Right {
+            permissions: self.permissions.intersection(&other.permissions).copied().collect(),
+        }
+    }
+
+    /// Rights granted by either
+    pub fn union(&self, other: &Right) -> Right {
+        Right {
+            permissions: self.permissions.union(&other.permissions).copied().collect(),
+        }
+    }
+
+    /// Whether all rights of the other are granted as well
+    pub fn contains(&self, other: &Right) -> bool {
+        self.permissions.is_superset(&other.permissions)
+    }
+
+    /// Read a stored set, permissions which are no longer registered are ignored
+    pub fn from_stored(json: &str) -> Result<Right, ErrorKind> {
+        let permissions: Vec<String> = serde_json::from_str(json).map_err(|_| ErrorKind::Parsing)?;
+        Ok(Right::from_names(&permissions))
+    }
+
+    fn from_names(permissions: &[String]) -> Right {
+        Right {
+            permissions: permissions
+                .iter()
+                .filter_map(|permission| permission.parse().ok())
+                .collect(),
+        }
+    }
+}
+
+/// Read stored club rights, permissions which are no longer registered are ignored
+pub fn club_rights_from_stored(json: &str) -> Result<ClubRights, ErrorKind> {
+    let club_rights: BTreeMap<u32, Vec<String>> = serde_json::from_str(json).map_err(|_| ErrorKind::Parsing)?;
+    Ok(club_rights
+        .into_iter()
+        .map(|(club_id, permissions)| (club_id, Right::from_names(&permissions)))
+        .collect())
 }
@@ -1,8 +1,36 @@
-use crate::common::{BankAccount, Credential, License};
+use crate::common::{BankAccount, Credential, License, Permission, Right};
 use crate::error::ErrorKind;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+/// Permissions to see all personal data of users
+pub const USER_SENSITIVE_PERMISSIONS: &[Permission] = &[
+    Permission::UserContactRead,
+    Permission::UserIdentityRead,
+    Permission::UserPhysicalRead,
+    Permission::UserBankAccountRead,
+    Permission::UserLicenseRead,
+    Permission::UserNoteRead,
+];
+
+/// Personal fields whose values never enter the audit log
+pub const USER_AUDIT_MASKED: &[&str] = &[
+    "credential",
+    "address",
+    "email",
+    "phone",
+    "birth_date",
+    "birth_location",
+    "nationality",
+    "gender",
+    "height",
+    "weight",
+    "bank_account",
+    "license_main",
+    "license_extra",
+    "note",
+];
+
 /*
  * STRUCTS
  */
@@ -86,6 +114,55 @@ impl User {
         }
     }
 
+    /// Remove the personal data which the rights do not grant access to
+    pub fn redact(&mut self, right: &Right) {
+        if !right.has(Permission::UserContactRead) {
+            self.address = None;
+            self.email = None;
+            self.phone = None;
+        }
+        if !right.has(Permission::UserIdentityRead) {
+            self.birth_date = None;
+            self.birth_location = None;
+            self.nationality = None;
+        }
+        if !right.has(Permission::UserPhysicalRead) {
+            self.height = None;
+            self.weight = None;
+        }
+        if !right.has(Permission::UserBankAccountRead) {
+            self.bank_account = None;
+        }
+        if !right.has(Permission::UserLicenseRead) {
+            self.license_main = None;
+            self.license_extra = None;
+        }
+        if !right.has(Permission::UserNoteRead) {
+            self.note = None;
+        }
+    }
+
+    /// Keep the stored personal data which the rights do not grant access to, as it was never shown
+    pub fn retain_hidden(&mut self, stored: &User, right: &Right) {
+        if !right.has(Permission::UserContactRead) {
+            self.address = stored.address.clone();
+            self.email = stored.email.clone();
+            self.phone = stored.phone.clone();
+        }
+        if !right.has(Permission::UserIdentityRead) {
+            self.birth_date = stored.birth_date;
+            self.birth_location = stored.birth_location.clone();
+            self.nationality = stored.nationality.clone();
+        }
+        if !right.has(Permission::UserPhysicalRead) {
+            self.height = stored.height;
+            self.weight = stored.weight;
+        }
+        if !right.has(Permission::UserNoteRead) {
+            self.note = stored.note.clone();
+        }
+    }
+
     pub fn from_row(row: &mut mysql::Row) -> Option<User> {
         row.take::<Option<u64>, &str>("user_id").flatten().map(|user_id| User {
             id: user_id,
@@ -118,6 +195,19 @@ impl User {
  * METHODS
  */
 
+/// Random user key which replaces the key and name of an anonymised user
+pub fn user_pseudonym() -> String {
+    format!("anon{}", crate::common::random_string(12).to_lowercase())
+}
+
+/// Superusers may only be edited by superusers, lest an admin takes over their account
+pub fn user_superuser_deny(actor_superuser: bool, target_superuser: bool) -> Result<(), ErrorKind> {
+    match (actor_superuser, target_superuser) {
+        (false, true) => Err(ErrorKind::RightSuperuserMissing),
+        _ => Ok(()),
+    }
+}
+
 pub fn check_user_key(key: &Option<String>) -> Result<String, ErrorKind> {
     let text = match key {
         None => return Err(ErrorKind::UserKeyMissing),
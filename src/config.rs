@@ -20,8 +20,26 @@ pub struct ServerConfig {
     pub db_user: String,
     pub db_password: String,
 
-    pub cpt_admin: Option<String>,
+    pub cpt_log_level: Option<String>,
     pub cpt_session_duration_hours: u32,
+    pub cpt_session_expiry_sliding: bool,
+    pub cpt_session_lifetime_max_hours: u32,
+    pub cpt_login_attempts_max: u16,
+    pub cpt_login_lockout_base_seconds: u32,
+    pub cpt_login_lockout_max_minutes: u32,
+    pub cpt_password_reset_minutes: u16,
+    pub cpt_oidc_issuer: Option<String>,
+    pub cpt_oidc_client_id: String,
+    pub cpt_oidc_client_secret: String,
+    pub cpt_oidc_redirect_uri: String,
+    pub cpt_oidc_claim: String,
+    pub cpt_mail_transport: String,
+    pub cpt_mail_sender: String,
+    pub cpt_mail_smtp_server: String,
+    pub cpt_mail_smtp_port: u16,
+    pub cpt_mail_smtp_user: Option<String>,
+    pub cpt_mail_smtp_password: Option<String>,
+    pub cpt_mail_file_path: String,
     pub cpt_event_acceptance_auto: bool,
     pub cpt_event_search_date_min_year: u16,
     pub cpt_event_search_date_max_year: u16,
@@ -31,6 +49,12 @@ pub struct ServerConfig {
     pub cpt_event_occurrence_duration_max_days: u16,
     pub cpt_event_occurrence_snap_minutes: u16,
     pub cpt_event_login_buffer_hours: u16,
+    pub cpt_event_series_occurrence_max: u16,
+    pub cpt_event_calendar_history_days: u16,
+    pub cpt_retention_interval_hours: u32,
+    pub cpt_retention_user_inactive_days: Option<u32>,
+    pub cpt_retention_attendance_years: Option<u16>,
+    pub cpt_retention_audit_days: Option<u32>,
 }
 
 impl Default for ServerConfig {
@@ -46,8 +70,26 @@ impl Default for ServerConfig {
             db_user: "cptdb-user".into(),
             db_password: "cptdb-password".into(),
 
-            cpt_admin: None,
+            cpt_log_level: None,
             cpt_session_duration_hours: 3,
+            cpt_session_expiry_sliding: false,
+            cpt_session_lifetime_max_hours: 24,
+            cpt_login_attempts_max: 5,
+            cpt_login_lockout_base_seconds: 30,
+            cpt_login_lockout_max_minutes: 60,
+            cpt_password_reset_minutes: 30,
+            cpt_oidc_issuer: None,
+            cpt_oidc_client_id: "".into(),
+            cpt_oidc_client_secret: "".into(),
+            cpt_oidc_redirect_uri: "".into(),
+            cpt_oidc_claim: "sub".into(),
+            cpt_mail_transport: "OFF".into(),
+            cpt_mail_sender: "cpt@localhost".into(),
+            cpt_mail_smtp_server: "localhost".into(),
+            cpt_mail_smtp_port: 587,
+            cpt_mail_smtp_user: None,
+            cpt_mail_smtp_password: None,
+            cpt_mail_file_path: "mail.log".into(),
             cpt_event_acceptance_auto: true,
             cpt_event_search_date_min_year: 1000,
             cpt_event_search_date_max_year: 3000,
@@ -57,19 +99,19 @@ impl Default for ServerConfig {
             cpt_event_occurrence_duration_max_days: 14,
             cpt_event_occurrence_snap_minutes: 15,
             cpt_event_login_buffer_hours: 24,
+            cpt_event_series_occurrence_max: 200,
+            cpt_event_calendar_history_days: 90,
+            cpt_retention_interval_hours: 24,
+            cpt_retention_user_inactive_days: None,
+            cpt_retention_attendance_years: None,
+            cpt_retention_audit_days: None,
         }
     }
 }
 
 pub fn readConfig() {
     let path = crate::common::fs::local_path("cptserver.toml");
-    let mut server_conf: ServerConfig = confy::load_path(path).unwrap();
-
-    if let Some(ref admin) = server_conf.cpt_admin {
-        if crate::common::validate_user_key(admin).is_err() {
-            server_conf.cpt_admin = None;
-        }
-    }
+    let server_conf: ServerConfig = confy::load_path(path).unwrap();
 
     println!("Rocket settings");
     println!("    => address: {:?}", server_conf.rocket_address);
@@ -83,11 +125,32 @@ pub fn readConfig() {
     println!("    => user: {:?}", server_conf.db_user);
 
     println!("Server settings");
-    println!("    => admin: {:?}", server_conf.cpt_admin);
+    println!("    => log_level: {:?}", server_conf.cpt_log_level);
     println!(
         "    => session_duration_hour: {:?}",
         server_conf.cpt_session_duration_hours
     );
+    println!(
+        "    => session_expiry_sliding: {:?}",
+        server_conf.cpt_session_expiry_sliding
+    );
+    println!(
+        "    => session_lifetime_max_hours: {:?}",
+        server_conf.cpt_session_lifetime_max_hours
+    );
+    println!("    => login_attempts_max: {:?}", server_conf.cpt_login_attempts_max);
+    println!(
+        "    => login_lockout_base_seconds: {:?}",
+        server_conf.cpt_login_lockout_base_seconds
+    );
+    println!(
+        "    => login_lockout_max_minutes: {:?}",
+        server_conf.cpt_login_lockout_max_minutes
+    );
+    println!(
+        "    => password_reset_minutes: {:?}",
+        server_conf.cpt_password_reset_minutes
+    );
     println!(
         "    => event_acceptance_auto: {:?}",
         server_conf.cpt_event_acceptance_auto
@@ -124,6 +187,40 @@ pub fn readConfig() {
         "    => event_login_buffer_hours: {:?}",
         server_conf.cpt_event_login_buffer_hours
     );
+    println!(
+        "    => event_series_occurrence_max: {:?}",
+        server_conf.cpt_event_series_occurrence_max
+    );
+    println!(
+        "    => event_calendar_history_days: {:?}",
+        server_conf.cpt_event_calendar_history_days
+    );
+
+    println!("Mail settings");
+    println!("    => transport: {:?}", server_conf.cpt_mail_transport);
+    println!("    => sender: {:?}", server_conf.cpt_mail_sender);
+    println!("    => smtp_server: {:?}", server_conf.cpt_mail_smtp_server);
+    println!("    => smtp_port: {:?}", server_conf.cpt_mail_smtp_port);
+    println!("    => smtp_user: {:?}", server_conf.cpt_mail_smtp_user);
+    println!("    => file_path: {:?}", server_conf.cpt_mail_file_path);
+
+    println!("OpenID Connect settings");
+    println!("    => issuer: {:?}", server_conf.cpt_oidc_issuer);
+    println!("    => client_id: {:?}", server_conf.cpt_oidc_client_id);
+    println!("    => redirect_uri: {:?}", server_conf.cpt_oidc_redirect_uri);
+    println!("    => claim: {:?}", server_conf.cpt_oidc_claim);
+
+    println!("Retention settings");
+    println!("    => interval_hours: {:?}", server_conf.cpt_retention_interval_hours);
+    println!(
+        "    => user_inactive_days: {:?}",
+        server_conf.cpt_retention_user_inactive_days
+    );
+    println!(
+        "    => attendance_years: {:?}",
+        server_conf.cpt_retention_attendance_years
+    );
+    println!("    => audit_days: {:?}", server_conf.cpt_retention_audit_days);
 
     let _ = CONFIG.set(server_conf);
 }
@@ -156,14 +253,100 @@ pub fn ROCKET_CONFIG() -> rocket::config::Config {
     }
 }
 
-pub fn ADMIN_USER() -> Option<&'static String> {
-    CONFIG.get().unwrap().cpt_admin.as_ref()
+pub fn ROCKET_LOG_LEVEL() -> log::LevelFilter {
+    let level: rocket::config::LogLevel = CONFIG.get().unwrap().rocket_log_level.parse().unwrap();
+    level.into()
+}
+
+pub fn LOG_LEVEL() -> log::LevelFilter {
+    match &CONFIG.get().unwrap().cpt_log_level {
+        None => ROCKET_LOG_LEVEL(),
+        Some(level) => match level.parse::<rocket::config::LogLevel>() {
+            Ok(level) => level.into(),
+            Err(_) => {
+                println!("Invalid log_level {:?}, falling back to the Rocket log level", level);
+                ROCKET_LOG_LEVEL()
+            }
+        },
+    }
 }
 
 pub fn SESSION_DURATION() -> chrono::Duration {
     chrono::Duration::hours(CONFIG.get().unwrap().cpt_session_duration_hours as i64)
 }
 
+pub fn SESSION_EXPIRY_SLIDING() -> bool {
+    CONFIG.get().unwrap().cpt_session_expiry_sliding
+}
+
+pub fn SESSION_LIFETIME_MAX() -> chrono::Duration {
+    chrono::Duration::hours(CONFIG.get().unwrap().cpt_session_lifetime_max_hours as i64)
+}
+
+pub fn LOGIN_ATTEMPTS_MAX() -> u16 {
+    CONFIG.get().unwrap().cpt_login_attempts_max
+}
+
+pub fn LOGIN_LOCKOUT_BASE() -> chrono::Duration {
+    chrono::Duration::seconds(CONFIG.get().unwrap().cpt_login_lockout_base_seconds as i64)
+}
+
+pub fn LOGIN_LOCKOUT_MAX() -> chrono::Duration {
+    chrono::Duration::minutes(CONFIG.get().unwrap().cpt_login_lockout_max_minutes as i64)
+}
+
+pub fn PASSWORD_RESET_DURATION() -> chrono::Duration {
+    chrono::Duration::minutes(CONFIG.get().unwrap().cpt_password_reset_minutes as i64)
+}
+
+pub fn OIDC_ISSUER() -> Option<&'static String> {
+    CONFIG.get().unwrap().cpt_oidc_issuer.as_ref()
+}
+
+pub fn OIDC_CLIENT_ID() -> &'static str {
+    &CONFIG.get().unwrap().cpt_oidc_client_id
+}
+
+pub fn OIDC_CLIENT_SECRET() -> &'static str {
+    &CONFIG.get().unwrap().cpt_oidc_client_secret
+}
+
+pub fn OIDC_REDIRECT_URI() -> &'static str {
+    &CONFIG.get().unwrap().cpt_oidc_redirect_uri
+}
+
+pub fn OIDC_CLAIM() -> &'static str {
+    &CONFIG.get().unwrap().cpt_oidc_claim
+}
+
+pub fn MAIL_TRANSPORT() -> &'static str {
+    &CONFIG.get().unwrap().cpt_mail_transport
+}
+
+pub fn MAIL_SENDER() -> &'static str {
+    &CONFIG.get().unwrap().cpt_mail_sender
+}
+
+pub fn MAIL_SMTP_SERVER() -> &'static str {
+    &CONFIG.get().unwrap().cpt_mail_smtp_server
+}
+
+pub fn MAIL_SMTP_PORT() -> u16 {
+    CONFIG.get().unwrap().cpt_mail_smtp_port
+}
+
+pub fn MAIL_SMTP_CREDENTIALS() -> Option<(String, String)> {
+    let config = CONFIG.get().unwrap();
+    match (&config.cpt_mail_smtp_user, &config.cpt_mail_smtp_password) {
+        (Some(user), Some(password)) => Some((user.clone(), password.clone())),
+        _ => None,
+    }
+}
+
+pub fn MAIL_FILE_PATH() -> std::path::PathBuf {
+    crate::common::fs::local_path(&CONFIG.get().unwrap().cpt_mail_file_path)
+}
+
 pub fn EVENT_ACCEPTENCE_AUTO() -> bool {
     CONFIG.get().unwrap().cpt_event_acceptance_auto
 }
@@ -203,3 +386,43 @@ pub fn EVENT_OCCURRENCE_SNAP() -> chrono::Duration {
 pub fn EVENT_LOGIN_BUFFER() -> chrono::Duration {
     chrono::Duration::hours(CONFIG.get().unwrap().cpt_event_login_buffer_hours as i64)
 }
+
+pub fn EVENT_SERIES_OCCURRENCE_MAX() -> usize {
+    CONFIG.get().unwrap().cpt_event_series_occurrence_max as usize
+}
+
+/// Calendar uploads for the event import exceed the default limit of 8 KiB for strings
+pub fn EVENT_IMPORT_LIMIT() -> rocket::data::ByteUnit {
+    rocket::data::ByteUnit::Mebibyte(1)
+}
+
+pub fn EVENT_CALENDAR_HISTORY() -> chrono::Duration {
+    chrono::Duration::days(CONFIG.get().unwrap().cpt_event_calendar_history_days as i64)
+}
+
+pub fn RETENTION_INTERVAL() -> std::time::Duration {
+    std::time::Duration::from_secs(CONFIG.get().unwrap().cpt_retention_interval_hours.max(1) as u64 * 3600)
+}
+
+/// Inactive users without logins, terms, attendance or edits within this period are anonymised
+pub fn RETENTION_USER_INACTIVE() -> Option<chrono::Duration> {
+    CONFIG
+        .get()
+        .unwrap()
+        .cpt_retention_user_inactive_days
+        .map(|days| chrono::Duration::days(days as i64))
+}
+
+/// Attendance of events which ended before this period is deleted
+pub fn RETENTION_ATTENDANCE_YEARS() -> Option<u16> {
+    CONFIG.get().unwrap().cpt_retention_attendance_years
+}
+
+/// Audit log entries older than this period are deleted
+pub fn RETENTION_AUDIT() -> Option<chrono::Duration> {
+    CONFIG
+        .get()
+        .unwrap()
+        .cpt_retention_audit_days
+        .map(|days| chrono::Duration::days(days as i64))
+}
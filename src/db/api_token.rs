@@ -0,0 +1,122 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::common::{ApiToken, Right, User};
+use crate::error::ErrorKind;
+
+pub fn api_token_list(conn: &mut PooledConn, user_id: u64) -> Result<Vec<ApiToken>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT token_id, name, rights, created, expiry, last_used
+        FROM api_tokens
+        WHERE user_id = :user_id
+        ORDER BY created;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    let rows: Vec<mysql::Row> = conn.exec(&stmt, &params)?;
+
+    let mut tokens: Vec<ApiToken> = Vec::new();
+
+    for mut row in rows {
+        let rights: String = row.take("rights").unwrap();
+        let token = ApiToken {
+            id: row.take("token_id").unwrap(),
+            name: row.take("name").unwrap(),
+            right: Right::from_stored(&rights)?,
+            created: row.take("created").unwrap(),
+            expiry: row.take("expiry").unwrap(),
+            last_used: row.take("last_used").unwrap(),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Owner, scope and token of an API token of an enabled user
+pub fn api_token_info(conn: &mut PooledConn, token_hash: &[u8]) -> Result<Option<(User, ApiToken)>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT u.user_id, u.user_key, u.firstname, u.lastname, u.nickname,
+            t.token_id, t.name, t.rights, t.created, t.expiry, t.last_used
+        FROM api_tokens t
+        JOIN users u ON u.user_id = t.user_id
+        WHERE t.token_hash = :token_hash AND u.enabled = TRUE;",
+    )?;
+    let params = params! {
+        "token_hash" => token_hash,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Ok(None),
+        Some(row) => row,
+    };
+
+    let user = User::from_info(
+        row.take("user_id").unwrap(),
+        row.take("user_key").unwrap(),
+        row.take("firstname").unwrap(),
+        row.take("lastname").unwrap(),
+        row.take("nickname").unwrap(),
+    );
+
+    let rights: String = row.take("rights").unwrap();
+    let token = ApiToken {
+        id: row.take("token_id").unwrap(),
+        name: row.take("name").unwrap(),
+        right: Right::from_stored(&rights)?,
+        created: row.take("created").unwrap(),
+        expiry: row.take("expiry").unwrap(),
+        last_used: row.take("last_used").unwrap(),
+    };
+
+    Ok(Some((user, token)))
+}
+
+pub fn api_token_create(
+    conn: &mut PooledConn,
+    user_id: u64,
+    name: &str,
+    token_hash: &[u8],
+    right: &Right,
+    expiry: Option<chrono::NaiveDateTime>,
+) -> Result<u32, ErrorKind> {
+    let rights = serde_json::to_string(right).map_err(|_| ErrorKind::Parsing)?;
+
+    let stmt = conn.prep(
+        "INSERT INTO api_tokens (user_id, name, token_hash, rights, created, expiry)
+        VALUES (:user_id, :name, :token_hash, :rights, UTC_TIMESTAMP(), :expiry);",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "name" => name,
+        "token_hash" => token_hash,
+        "rights" => &rights,
+        "expiry" => &expiry,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(conn.last_insert_id() as u32)
+}
+
+pub fn api_token_used(conn: &mut PooledConn, token_id: u32) -> Result<(), ErrorKind> {
+    let stmt = conn.prep("UPDATE api_tokens SET last_used = UTC_TIMESTAMP() WHERE token_id = :token_id;")?;
+    let params = params! {
+        "token_id" => token_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn api_token_delete(conn: &mut PooledConn, user_id: u64, token_id: u32) -> Result<(), ErrorKind> {
+    let stmt = conn.prep("DELETE FROM api_tokens WHERE token_id = :token_id AND user_id = :user_id;")?;
+    let params = params! {
+        "token_id" => token_id,
+        "user_id" => user_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
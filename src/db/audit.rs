@@ -0,0 +1,101 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::common::AuditEntry;
+use crate::error::ErrorKind;
+
+pub fn audit_list(
+    conn: &mut PooledConn,
+    user_id: Option<u64>,
+    event_id: Option<u64>,
+    entity: Option<String>,
+    entity_id: Option<u64>,
+    begin: Option<chrono::NaiveDateTime>,
+    end: Option<chrono::NaiveDateTime>,
+) -> Result<Vec<AuditEntry>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT audit_id, user_id, event_id, route, entity, entity_id, diff, time
+        FROM audit_log
+        WHERE (:user_id IS NULL OR user_id = :user_id)
+        AND (:event_id IS NULL OR event_id = :event_id)
+        AND (:entity IS NULL OR entity = :entity)
+        AND (:entity_id IS NULL OR entity_id = :entity_id)
+        AND (:begin IS NULL OR time >= :begin)
+        AND (:end IS NULL OR time <= :end)
+        ORDER BY time DESC, audit_id DESC;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "event_id" => event_id,
+        "entity" => &entity,
+        "entity_id" => entity_id,
+        "begin" => begin,
+        "end" => end,
+    };
+
+    let rows: Vec<mysql::Row> = conn.exec(&stmt, &params)?;
+    rows.into_iter().map(audit_row_map).collect()
+}
+
+/// Entries about the user or any of its credentials and links, as well as the ones made by the user
+pub fn audit_user_list(conn: &mut PooledConn, user_id: u64) -> Result<Vec<AuditEntry>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT audit_id, user_id, event_id, route, entity, entity_id, diff, time
+        FROM audit_log
+        WHERE user_id = :user_id
+        OR (entity LIKE 'user%' AND entity_id = :user_id)
+        ORDER BY time DESC, audit_id DESC;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    let rows: Vec<mysql::Row> = conn.exec(&stmt, &params)?;
+    rows.into_iter().map(audit_row_map).collect()
+}
+
+fn audit_row_map(mut row: mysql::Row) -> Result<AuditEntry, ErrorKind> {
+    let diff: Option<String> = row.take("diff").unwrap();
+    let entry = AuditEntry {
+        id: row.take("audit_id").unwrap(),
+        user_id: row.take("user_id").unwrap(),
+        event_id: row.take("event_id").unwrap(),
+        route: row.take("route").unwrap(),
+        entity: row.take("entity").unwrap(),
+        entity_id: row.take("entity_id").unwrap(),
+        diff: match diff {
+            None => None,
+            Some(diff) => Some(serde_json::from_str(&diff).map_err(|_| ErrorKind::Parsing)?),
+        },
+        time: row.take("time").unwrap(),
+    };
+    Ok(entry)
+}
+
+pub fn audit_create(
+    conn: &mut PooledConn,
+    user_id: Option<u64>,
+    event_id: Option<u64>,
+    route: &str,
+    entity: &str,
+    entity_id: u64,
+    diff: Option<&serde_json::Value>,
+) -> Result<u64, ErrorKind> {
+    let diff = diff.map(|diff| diff.to_string());
+
+    let stmt = conn.prep(
+        "INSERT INTO audit_log (user_id, event_id, route, entity, entity_id, diff, time)
+        VALUES (:user_id, :event_id, :route, :entity, :entity_id, :diff, UTC_TIMESTAMP());",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "event_id" => event_id,
+        "route" => route,
+        "entity" => entity,
+        "entity_id" => entity_id,
+        "diff" => &diff,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(conn.last_insert_id())
+}
@@ -22,6 +22,8 @@ pub fn sieve_list(conn: &mut PooledConn, course_id: u32, role: String) -> Result
                 key: team_key,
                 name,
                 description,
+                mfa_required: None,
+                club_id: None,
                 right: None,
             },
             access,
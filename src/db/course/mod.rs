@@ -41,6 +41,32 @@ pub fn course_list(
     Ok(courses)
 }
 
+pub fn course_info(conn: &mut PooledConn, course_id: u32) -> Result<Course, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT course_id, course_key, title, active, public
+        FROM courses
+        WHERE course_id = :course_id;",
+    )?;
+    let params = params! {
+        "course_id" => course_id,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Err(ErrorKind::CourseMissing),
+        Some(row) => row,
+    };
+
+    let course = Course {
+        id: row.take("course_id").unwrap(),
+        key: row.take("course_key").unwrap(),
+        title: row.take("title").unwrap(),
+        active: row.take("active").unwrap(),
+        public: row.take("public").unwrap(),
+    };
+
+    Ok(course)
+}
+
 pub fn course_available(conn: &mut PooledConn, user_id: u64) -> Result<Vec<Course>, ErrorKind> {
     let stmt = conn.prep(
         "SELECT DISTINCT c.course_id, c.course_key, c.title, c.active, c.public
@@ -198,6 +224,22 @@ pub fn course_requirement_add(
     Ok(())
 }
 
+pub fn course_requirement_course(conn: &mut PooledConn, requirement_id: u64) -> Result<u32, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT course_id
+        FROM course_requirements
+        WHERE requirement_id = :requirement_id;",
+    )?;
+    let params = params! {
+        "requirement_id" => &requirement_id,
+    };
+
+    match conn.exec_first::<u32, _, _>(&stmt, &params)? {
+        None => Err(ErrorKind::Missing),
+        Some(course_id) => Ok(course_id),
+    }
+}
+
 pub fn course_requirement_remove(conn: &mut PooledConn, requirement_id: u64) -> Result<(), ErrorKind> {
     let stmt = conn.prep(
         "DELETE r FROM course_requirements r
@@ -79,6 +79,27 @@ pub fn event_attendance_registration_edit(
     Ok(())
 }
 
+/// Number of users with a positive registration for any role, apart from a given user
+pub fn event_attendance_registration_count(
+    conn: &mut PooledConn,
+    event_id: u64,
+    user_id: u64,
+) -> Result<u32, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT COUNT(DISTINCT r.user_id)
+        FROM event_attendance_registrations r
+        WHERE r.event_id = :event_id AND r.user_id <> :user_id AND r.status = 'POSITIVE';",
+    )?;
+
+    let params = params! {
+        "event_id" => &event_id,
+        "user_id" => &user_id,
+    };
+
+    let count = conn.exec_first::<u32, _, _>(&stmt, &params)?;
+    count.ok_or(ErrorKind::DatabaseError)
+}
+
 pub fn event_attendance_registration_remove(
     conn: &mut PooledConn,
     event_id: u64,
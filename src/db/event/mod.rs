@@ -1,12 +1,13 @@
 use mysql::prelude::Queryable;
 use mysql::{params, PooledConn};
 
-use crate::common::{Acceptance, Affiliation, Course, Event, Location, Occurrence, User};
+use crate::common::{Acceptance, Affiliation, Course, Event, HashAlgorithm, Location, Occurrence, User};
 use crate::error::ErrorKind;
 
 pub mod attendance;
 pub mod moderator;
 pub mod owner;
+pub mod series;
 
 /*
  * METHODS
@@ -16,7 +17,8 @@ pub fn event_info(conn: &mut PooledConn, event_id: u64) -> Result<Event, ErrorKi
     let stmt = conn.prep(
         "SELECT event_id, event_key, e.title,
             l.location_id, l.location_key, l.name AS location_name, l.description AS location_description,
-            e.begin, e.end, e.occurrence, e.acceptance, e.public, e.scrutable, e.note, e.course_id
+            l.capacity AS location_capacity, l.areas AS location_areas,
+            e.areas, e.begin, e.end, e.occurrence, e.acceptance, e.public, e.scrutable, e.note, e.course_id, e.series_id
         FROM events e
         JOIN locations l ON l.location_id = e.location_id
         WHERE event_id = :event_id",
@@ -39,13 +41,17 @@ pub fn event_info(conn: &mut PooledConn, event_id: u64) -> Result<Event, ErrorKi
             key: row.take("location_key").unwrap(),
             name: row.take("location_name").unwrap(),
             description: row.take("location_description").unwrap(),
+            capacity: row.take("location_capacity").unwrap(),
+            areas: row.take("location_areas").unwrap(),
         }),
+        areas: row.take("areas").unwrap(),
         occurrence: row.take("occurrence").unwrap(),
         acceptance: row.take("acceptance").unwrap(),
         public: row.take("public").unwrap(),
         scrutable: row.take("scrutable").unwrap(),
         note: row.take("note").unwrap(),
         course_id: row.take("course_id").unwrap(),
+        series_id: row.take("series_id").unwrap(),
     };
 
     Ok(event)
@@ -61,11 +67,12 @@ pub fn event_list(
     course_true: Option<bool>,
     course_id: Option<u32>,
     owner_id: Option<u64>,
+    attendee_id: Option<u64>,
 ) -> Result<Vec<Event>, ErrorKind> {
     let stmt = conn.prep(
         "SELECT e.event_id, e.event_key, e.title,
             l.location_id, l.location_key, l.name AS location_name, l.description AS location_description,
-            e.begin, e.end, e.occurrence, e.acceptance, e.public, e.scrutable, e.note
+            e.areas, e.begin, e.end, e.occurrence, e.acceptance, e.public, e.scrutable, e.note, e.course_id, e.series_id
         FROM events e
         JOIN locations l ON l.location_id = e.location_id
         LEFT JOIN event_owners o ON e.event_id = o.event_id
@@ -76,6 +83,10 @@ pub fn event_list(
         AND (:acceptance IS NULL OR :acceptance = e.acceptance)
         AND (:course_true IS NULL OR (:course_true = TRUE AND :course_id = e.course_id) OR (:course_true = FALSE AND e.course_id IS NULL))
         AND (:owner_id IS NULL OR :owner_id = o.user_id)
+        AND (:attendee_id IS NULL
+            OR EXISTS (SELECT 1 FROM event_bookmarks b WHERE b.event_id = e.event_id AND b.user_id = :attendee_id)
+            OR EXISTS (SELECT 1 FROM event_attendance_registrations r
+                WHERE r.event_id = e.event_id AND r.user_id = :attendee_id AND r.status = 'POSITIVE'))
         GROUP BY e.event_id;")?;
 
     let params = params! {
@@ -87,6 +98,7 @@ pub fn event_list(
         "course_true" => &course_true,
         "course_id" => &course_id,
         "owner_id" => &owner_id,
+        "attendee_id" => &attendee_id,
     };
 
     let rows: Vec<mysql::Row> = conn.exec(&stmt, &params)?;
@@ -105,13 +117,17 @@ pub fn event_list(
                 key: row.take("location_key").unwrap(),
                 name: row.take("location_name").unwrap(),
                 description: row.take("location_description").unwrap(),
+                capacity: None,
+                areas: None,
             }),
+            areas: row.take("areas").unwrap(),
             occurrence: row.take("occurrence").unwrap(),
             acceptance: row.take("acceptance").unwrap(),
             public: row.take("public").unwrap(),
             scrutable: row.take("scrutable").unwrap(),
             note: row.take("note").unwrap(),
-            course_id: None,
+            course_id: row.take("course_id").unwrap(),
+            series_id: row.take("series_id").unwrap(),
         };
         events.push(item);
     }
@@ -130,17 +146,27 @@ pub fn event_create(
     }
 
     let stmt = conn.prep(
-        "INSERT INTO events (event_key, pwd, title, begin, end, location_id, occurrence, acceptance, public, scrutable, note, course_id)
-        SELECT :event_key, :pwd, :title, :begin, :end, :location_id, :occurrence, :acceptance, :public, :scrutable, :note, :course_id",
+        "INSERT INTO events (event_key, pwd_pepper, pwd_hash, title, begin, end, location_id, areas, occurrence, acceptance, public, scrutable, note, course_id)
+        SELECT :event_key, :pwd_pepper, :pwd_hash, :title, :begin, :end, :location_id, :areas, :occurrence, :acceptance, :public, :scrutable, :note, :course_id",
+    )?;
+
+    // The initial password is never revealed, owners have to rotate it to learn one
+    let pepper: Vec<u8> = crate::common::random_bytes(16);
+    let pwd_hash: Vec<u8> = crate::common::hash_password(
+        crate::common::random_string(12).as_bytes(),
+        &pepper,
+        &HashAlgorithm::Argon2id,
     )?;
 
     let params = params! {
         "event_key" => &event.key,
-        "pwd" => crate::common::random_string(8),
+        "pwd_pepper" => &pepper,
+        "pwd_hash" => &pwd_hash,
         "title" => &event.title,
         "begin" => &event.begin,
         "end" => &event.end,
         "location_id" => &event.location.as_ref().map(|location| location.id),
+        "areas" => &event.areas,
         "occurrence" => &event.occurrence,
         "acceptance" => &acceptance,
         "public" => event.public,
@@ -167,6 +193,7 @@ pub fn event_edit(conn: &mut PooledConn, event_id: u64, event: &Event) -> Result
             begin = :begin,
             end = :end,
             location_id = :location_id,
+            areas = :areas,
             occurrence = :occurrence,
             public = :public,
             scrutable = :scrutable,
@@ -181,6 +208,7 @@ pub fn event_edit(conn: &mut PooledConn, event_id: u64, event: &Event) -> Result
         "begin" => &event.begin,
         "end" => &event.end,
         "location_id" => &event.location.as_ref().map(|location| location.id),
+        "areas" => &event.areas,
         "occurrence" => &event.occurrence,
         "public" => &event.public,
         "scrutable" => &event.scrutable,
@@ -206,15 +234,21 @@ pub fn event_acceptance_edit(conn: &mut PooledConn, event_id: u64, acceptance: &
     Ok(())
 }
 
-pub fn event_password_edit(conn: &mut PooledConn, event_id: u64, password: String) -> Result<(), ErrorKind> {
+pub fn event_password_edit(conn: &mut PooledConn, event_id: u64, password: &str) -> Result<(), ErrorKind> {
+    let pepper: Vec<u8> = crate::common::random_bytes(16);
+    let algorithm = HashAlgorithm::Argon2id;
+    let pwd_hash: Vec<u8> = crate::common::hash_password(password.as_bytes(), &pepper, &algorithm)?;
+
     let stmt = conn.prep(
-        "UPDATE events SET pwd = :pwd
+        "UPDATE events SET pwd_pepper = :pwd_pepper, pwd_hash = :pwd_hash, pwd_algorithm = :pwd_algorithm
         WHERE event_id = :event_id",
     )?;
 
     let params = params! {
         "event_id" => &event_id,
-        "pwd" => &password,
+        "pwd_pepper" => &pepper,
+        "pwd_hash" => &pwd_hash,
+        "pwd_algorithm" => &algorithm,
     };
 
     conn.exec_drop(&stmt, &params)?;
@@ -252,26 +286,90 @@ pub fn event_delete(conn: &mut PooledConn, event_id: u64) -> Result<(), ErrorKin
     Ok(())
 }
 
+/// Whether enough sub-areas of the location are left over during the time window of an event
 pub fn event_free_true(conn: &mut PooledConn, event: &Event) -> Result<bool, ErrorKind> {
+    let location = match &event.location {
+        None => return Ok(true),
+        Some(location) => crate::db::location::location_info(conn, location.id)?,
+    };
+
+    let others = event_location_overlap_list(conn, event, &location)?;
+    Ok(crate::common::location_free(
+        event,
+        &others,
+        location.areas.unwrap_or(1),
+    ))
+}
+
+/// Accepted events which occupy the location of an event during its time window, in case too few sub-areas are left
+pub fn event_conflict_list(conn: &mut PooledConn, event: &Event) -> Result<Vec<Event>, ErrorKind> {
+    let location = match &event.location {
+        None => return Ok(Vec::new()),
+        Some(location) => crate::db::location::location_info(conn, location.id)?,
+    };
+
+    let others = event_location_overlap_list(conn, event, &location)?;
+    if crate::common::location_free(event, &others, location.areas.unwrap_or(1)) {
+        return Ok(Vec::new());
+    }
+
+    Ok(others.into_iter().filter(|other| other.claims_location()).collect())
+}
+
+/// Other events at the location of an event during its time window, regardless of their acceptance
+fn event_location_overlap_list(
+    conn: &mut PooledConn,
+    event: &Event,
+    location: &Location,
+) -> Result<Vec<Event>, ErrorKind> {
     let stmt = conn.prep(
-        "SELECT COUNT(1)
+        "SELECT event_id, event_key, title, begin, end, areas, occurrence, acceptance
         FROM events
         WHERE NOT (end <= :begin OR begin >= :end)
         AND location_id = :location_id
-        AND occurrence = 'OCCURRING'
-        AND acceptance = 'ACCEPTED'",
+        AND event_id <> :event_id
+        ORDER BY begin",
     )?;
     let params = params! {
+        "event_id" => &event.id,
         "begin" => &event.begin,
         "end" => &event.end,
-        "location_id" => &event.location.as_ref().map(|location| location.id),
+        "location_id" => &location.id,
     };
 
-    let count = conn.exec_first::<u64, _, _>(&stmt, &params)?;
-    match count {
-        None => Err(ErrorKind::DatabaseError),
-        Some(count) => Ok(count == 0),
+    let rows: Vec<mysql::Row> = conn.exec(&stmt, &params)?;
+    let mut others = Vec::new();
+
+    for mut row in rows {
+        let mut other = Event::from_info(
+            row.take("event_id").unwrap(),
+            row.take("event_key").unwrap(),
+            row.take("title").unwrap(),
+            row.take("begin").unwrap(),
+            row.take("end").unwrap(),
+            Some(location.clone()),
+        );
+        other.areas = row.take("areas").unwrap();
+        other.occurrence = row.take("occurrence").unwrap();
+        other.acceptance = row.take("acceptance").unwrap();
+        others.push(other);
     }
+
+    Ok(others)
+}
+
+/* EVENT LOCK */
+
+/// Hold the event until the end of the transaction, so that checks of its attendance stay valid until the write
+pub fn event_lock(conn: &mut PooledConn, event_id: u64) -> Result<(), ErrorKind> {
+    let stmt = conn.prep("SELECT event_id FROM events WHERE event_id = :event_id FOR UPDATE")?;
+    let params = params! {
+        "event_id" => &event_id,
+    };
+
+    let event_id: Option<u64> = conn.exec_first(&stmt, &params)?;
+    event_id.ok_or(ErrorKind::EventMissing)?;
+    Ok(())
 }
 
 /* COURSE RELATED */
@@ -0,0 +1,305 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::common::{EventSeries, FromStr, Location, SeriesOccurrence, SeriesState};
+use crate::error::ErrorKind;
+
+pub fn event_series_list(conn: &mut PooledConn, course_id: u32) -> Result<Vec<EventSeries>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT s.series_id
+        FROM event_series s
+        WHERE s.course_id = :course_id
+        ORDER BY s.begin",
+    )?;
+    let params = params! {
+        "course_id" => course_id,
+    };
+
+    let series_ids: Vec<u32> = conn.exec(&stmt, &params)?;
+    let mut series = Vec::new();
+
+    for series_id in series_ids {
+        series.push(event_series_info(conn, series_id)?);
+    }
+
+    Ok(series)
+}
+
+pub fn event_series_info(conn: &mut PooledConn, series_id: u32) -> Result<EventSeries, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT s.series_id, s.course_id, s.title,
+            l.location_id, l.location_key, l.name AS location_name, l.description AS location_description,
+            s.areas, s.time, s.duration, s.weekdays, s.week_interval, s.begin, s.until
+        FROM event_series s
+        JOIN locations l ON l.location_id = s.location_id
+        WHERE s.series_id = :series_id",
+    )?;
+    let params = params! {
+        "series_id" => series_id,
+    };
+
+    let mut row: mysql::Row = conn.exec_first(&stmt, &params)?.ok_or(ErrorKind::EventSeriesMissing)?;
+
+    let stmt_exceptions = conn.prep(
+        "SELECT date
+        FROM event_series_exceptions
+        WHERE series_id = :series_id
+        ORDER BY date",
+    )?;
+    let exceptions: Vec<chrono::NaiveDate> = conn.exec(&stmt_exceptions, &params)?;
+
+    let series = EventSeries {
+        id: row.take("series_id").unwrap(),
+        course_id: row.take("course_id").unwrap(),
+        title: row.take("title").unwrap(),
+        location: Location {
+            id: row.take("location_id").unwrap(),
+            key: row.take("location_key").unwrap(),
+            name: row.take("location_name").unwrap(),
+            description: row.take("location_description").unwrap(),
+            capacity: None,
+            areas: None,
+        },
+        areas: row.take("areas").unwrap(),
+        time: row.take("time").unwrap(),
+        duration: row.take("duration").unwrap(),
+        weekdays: EventSeries::weekdays_from_mask(row.take("weekdays").unwrap()),
+        interval: row.take("week_interval").unwrap(),
+        begin: row.take("begin").unwrap(),
+        until: row.take("until").unwrap(),
+        exceptions,
+    };
+
+    Ok(series)
+}
+
+pub fn event_series_create(conn: &mut PooledConn, course_id: u32, series: &EventSeries) -> Result<u32, ErrorKind> {
+    let stmt = conn.prep(
+        "INSERT INTO event_series (course_id, title, location_id, areas, time, duration, weekdays, week_interval, begin, until)
+        VALUES (:course_id, :title, :location_id, :areas, :time, :duration, :weekdays, :week_interval, :begin, :until)",
+    )?;
+
+    let params = params! {
+        "course_id" => &course_id,
+        "title" => &series.title,
+        "location_id" => &series.location.id,
+        "areas" => &series.areas,
+        "time" => &series.time,
+        "duration" => &series.duration,
+        "weekdays" => &series.weekday_mask(),
+        "week_interval" => &series.interval,
+        "begin" => &series.begin,
+        "until" => &series.until,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    let series_id = conn.last_insert_id() as u32;
+
+    event_series_exception_edit(conn, series_id, &series.exceptions)?;
+    Ok(series_id)
+}
+
+pub fn event_series_edit(conn: &mut PooledConn, series_id: u32, series: &EventSeries) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE event_series
+        SET
+            title = :title,
+            location_id = :location_id,
+            areas = :areas,
+            time = :time,
+            duration = :duration,
+            weekdays = :weekdays,
+            week_interval = :week_interval,
+            begin = :begin,
+            until = :until
+        WHERE series_id = :series_id",
+    )?;
+
+    let params = params! {
+        "series_id" => &series_id,
+        "title" => &series.title,
+        "location_id" => &series.location.id,
+        "areas" => &series.areas,
+        "time" => &series.time,
+        "duration" => &series.duration,
+        "weekdays" => &series.weekday_mask(),
+        "week_interval" => &series.interval,
+        "begin" => &series.begin,
+        "until" => &series.until,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    event_series_exception_edit(conn, series_id, &series.exceptions)?;
+    Ok(())
+}
+
+fn event_series_exception_edit(
+    conn: &mut PooledConn,
+    series_id: u32,
+    exceptions: &[chrono::NaiveDate],
+) -> Result<(), ErrorKind> {
+    let stmt_delete = conn.prep("DELETE FROM event_series_exceptions WHERE series_id = :series_id")?;
+    conn.exec_drop(&stmt_delete, params! {"series_id" => &series_id})?;
+
+    let stmt_insert = conn.prep(
+        "INSERT IGNORE INTO event_series_exceptions (series_id, date)
+        VALUES (:series_id, :date)",
+    )?;
+    let params = exceptions.iter().map(|date| {
+        params! {
+            "series_id" => &series_id,
+            "date" => date,
+        }
+    });
+
+    conn.exec_batch(&stmt_insert, params)?;
+    Ok(())
+}
+
+pub fn event_series_delete(conn: &mut PooledConn, series_id: u32) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "DELETE FROM event_series
+        WHERE series_id = :series_id",
+    )?;
+
+    let params = params! {
+        "series_id" => &series_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/* OCCURRENCES */
+
+/// Events of the series from a given date onwards, with the date they take place on
+pub fn event_series_occurrence_list(
+    conn: &mut PooledConn,
+    series_id: u32,
+    from: chrono::NaiveDate,
+) -> Result<Vec<SeriesOccurrence>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT event_id, DATE(begin) AS date, series_state
+        FROM events
+        WHERE series_id = :series_id AND DATE(begin) >= :from
+        ORDER BY begin",
+    )?;
+
+    let params = params! {
+        "series_id" => &series_id,
+        "from" => &from,
+    };
+
+    let rows: Vec<mysql::Row> = conn.exec(&stmt, &params)?;
+    let mut occurrences = Vec::new();
+
+    for mut row in rows {
+        let state: String = row.take("series_state").unwrap();
+        occurrences.push(SeriesOccurrence {
+            event_id: row.take("event_id").unwrap(),
+            date: row.take("date").unwrap(),
+            state: SeriesState::from_str(&state)?,
+        });
+    }
+
+    Ok(occurrences)
+}
+
+pub fn event_series_occurrence_add(conn: &mut PooledConn, series_id: u32, event_id: u64) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE events
+        SET series_id = :series_id, series_state = 'ATTACHED'
+        WHERE event_id = :event_id",
+    )?;
+
+    let params = params! {
+        "series_id" => &series_id,
+        "event_id" => &event_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/// Hand the events from a given date onwards over to another series
+pub fn event_series_occurrence_move(
+    conn: &mut PooledConn,
+    series_id: u32,
+    target_id: u32,
+    from: chrono::NaiveDate,
+) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE events
+        SET series_id = :target_id
+        WHERE series_id = :series_id AND DATE(begin) >= :from",
+    )?;
+
+    let params = params! {
+        "series_id" => &series_id,
+        "target_id" => &target_id,
+        "from" => &from,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/// Delete an occurrence, unless it already has attendance and is therefore only canceled
+pub fn event_series_occurrence_remove(conn: &mut PooledConn, event_id: u64) -> Result<(), ErrorKind> {
+    let stmt_cancel = conn.prep(
+        "UPDATE events e
+        SET e.occurrence = 'CANCELED', e.series_state = 'REMOVED'
+        WHERE e.event_id = :event_id
+        AND (EXISTS (SELECT 1 FROM event_attendance_registrations r WHERE r.event_id = e.event_id)
+            OR EXISTS (SELECT 1 FROM event_attendance_presences p WHERE p.event_id = e.event_id)
+            OR EXISTS (SELECT 1 FROM event_attendance_filters f WHERE f.event_id = e.event_id))",
+    )?;
+    let stmt_delete = conn.prep(
+        "DELETE e
+        FROM events e
+        WHERE e.event_id = :event_id
+        AND NOT EXISTS (SELECT 1 FROM event_attendance_registrations r WHERE r.event_id = e.event_id)
+        AND NOT EXISTS (SELECT 1 FROM event_attendance_presences p WHERE p.event_id = e.event_id)
+        AND NOT EXISTS (SELECT 1 FROM event_attendance_filters f WHERE f.event_id = e.event_id)",
+    )?;
+
+    let params = params! {
+        "event_id" => &event_id,
+    };
+
+    conn.exec_drop(&stmt_cancel, &params)?;
+    conn.exec_drop(&stmt_delete, &params)?;
+    Ok(())
+}
+
+/// Keep an occurrence which was edited on its own out of further edits of the series
+pub fn event_series_occurrence_detach(conn: &mut PooledConn, event_id: u64) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE events
+        SET series_state = 'DETACHED'
+        WHERE event_id = :event_id",
+    )?;
+
+    let params = params! {
+        "event_id" => &event_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/// Take a removed occurrence back into the series, once its date is part of the recurrence again
+pub fn event_series_occurrence_restore(conn: &mut PooledConn, event_id: u64) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE events
+        SET occurrence = 'OCCURRING', series_state = 'ATTACHED'
+        WHERE event_id = :event_id AND series_state = 'REMOVED'",
+    )?;
+
+    let params = params! {
+        "event_id" => &event_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
@@ -21,6 +21,30 @@ pub fn itemcat_list(conn: &mut PooledConn) -> Result<Vec<ItemCategory>, ErrorKin
     Ok(itemcats)
 }
 
+pub fn itemcat_info(conn: &mut PooledConn, category_id: u64) -> Result<ItemCategory, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT ic.category_id, ic.name
+        FROM item_categories ic
+        WHERE ic.category_id = :category_id;",
+    )?;
+
+    let params = params! {
+        "category_id" => &category_id,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Err(ErrorKind::Missing),
+        Some(row) => row,
+    };
+
+    let itemcat = ItemCategory {
+        id: row.take("category_id").unwrap(),
+        name: row.take("name").unwrap(),
+    };
+
+    Ok(itemcat)
+}
+
 pub fn itemcat_create(conn: &mut PooledConn, category: &ItemCategory) -> Result<u32, ErrorKind> {
     let stmt = conn.prep(
         "INSERT INTO item_categories (name)
@@ -153,7 +153,7 @@ pub fn possession_create(
     acquisition_date: chrono::NaiveDate,
     owned: bool,
     stock_id: Option<u64>,
-) -> Result<(), ErrorKind> {
+) -> Result<u64, ErrorKind> {
     let stmt = conn.prep(
         "INSERT INTO user_possessions (user_id, item_id, acquisition_date, owned, stock_id)
         SELECT :user_id, :item_id, :acquisition_date, :owned, :stock_id;",
@@ -168,7 +168,7 @@ pub fn possession_create(
     };
 
     conn.exec_drop(&stmt, &params)?;
-    Ok(())
+    Ok(conn.last_insert_id())
 }
 
 pub fn possession_edit(
@@ -112,7 +112,7 @@ pub fn stock_create(
     item_id: u64,
     storage: &str,
     owned: u32,
-) -> Result<(), ErrorKind> {
+) -> Result<u64, ErrorKind> {
     let stmt = conn.prep(
         "INSERT INTO club_stocks (club_id, item_id, storage, owned, loaned)
         SELECT :club_id, :item_id, :storage, :owned, :loaned;",
@@ -128,7 +128,7 @@ pub fn stock_create(
 
     conn.exec_drop(&stmt, &params)?;
 
-    Ok(())
+    Ok(conn.last_insert_id())
 }
 
 pub fn stock_edit(
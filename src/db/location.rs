@@ -6,33 +6,65 @@ use crate::error::ErrorKind;
 
 pub fn location_list(conn: &mut PooledConn) -> Result<Vec<Location>, ErrorKind> {
     let stmt = conn.prep(
-        "SELECT location_id, location_key, name, description
+        "SELECT location_id, location_key, name, description, capacity, areas
         FROM locations;",
     )?;
 
     let params = params::Params::Empty;
 
-    let map = |(location_id, location_key, name, description)| Location {
+    let map = |(location_id, location_key, name, description, capacity, areas)| Location {
         id: location_id,
         key: location_key,
         name,
         description,
+        capacity,
+        areas: Some(areas),
     };
 
     let terms = conn.exec_map(&stmt, &params, &map)?;
     Ok(terms)
 }
 
+pub fn location_info(conn: &mut PooledConn, location_id: u32) -> Result<Location, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT location_id, location_key, name, description, capacity, areas
+        FROM locations
+        WHERE location_id = :location_id;",
+    )?;
+
+    let params = params! {
+        "location_id" => location_id,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Err(ErrorKind::Missing),
+        Some(row) => row,
+    };
+
+    let location = Location {
+        id: row.take("location_id").unwrap(),
+        key: row.take("location_key").unwrap(),
+        name: row.take("name").unwrap(),
+        description: row.take("description").unwrap(),
+        capacity: row.take("capacity").unwrap(),
+        areas: row.take("areas").unwrap(),
+    };
+
+    Ok(location)
+}
+
 pub fn location_create(conn: &mut PooledConn, location: &Location) -> Result<u32, ErrorKind> {
     let stmt = conn.prep(
-        "INSERT INTO locations (location_key, name, description)
-        VALUES (:location_key, :name, :description)",
+        "INSERT INTO locations (location_key, name, description, capacity, areas)
+        VALUES (:location_key, :name, :description, :capacity, :areas)",
     )?;
 
     let params = params! {
         "location_key" => &location.key,
         "name" => &location.name,
         "description" => &location.description,
+        "capacity" => &location.capacity,
+        "areas" => &location.areas.unwrap_or(1).max(1),
     };
 
     conn.exec_drop(&stmt, &params)?;
@@ -45,7 +77,9 @@ pub fn location_edit(conn: &mut PooledConn, location_id: u32, location: &Locatio
         "UPDATE locations SET
             location_key = :location_key,
             name = :name,
-            description = :description
+            description = :description,
+            capacity = COALESCE(:capacity, capacity),
+            areas = COALESCE(:areas, areas)
         WHERE location_id = :location_id",
     )?;
 
@@ -54,6 +88,8 @@ pub fn location_edit(conn: &mut PooledConn, location_id: u32, location: &Locatio
         "location_key" => &location.key,
         "name" => &location.name,
         "description" => &location.description,
+        "capacity" => &location.capacity,
+        "areas" => &location.areas.map(|areas| areas.max(1)),
     };
 
     conn.exec_drop(&stmt, &params)?;
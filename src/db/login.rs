@@ -1,12 +1,13 @@
 use mysql::prelude::Queryable;
 use mysql::{params, PooledConn};
 
-use crate::common::{Right, User};
+use crate::common::{ClubRights, FromStr, HashAlgorithm, LoginCategory, LoginFailure, Permission, Right, User};
 use crate::error::ErrorKind;
 
-pub fn user_login(conn: &mut PooledConn, user_key: &str, salted_hash: &Vec<u8>) -> Result<User, ErrorKind> {
+pub fn user_login(conn: &mut PooledConn, user_key: &str, salted_hash: &[u8]) -> Result<User, ErrorKind> {
     let stmt = conn.prep(
-        "SELECT u.user_id, u.user_key, uc.sp_hash, uc.pepper, uc.salt, u.enabled, u.firstname, u.lastname, u.nickname
+        "SELECT u.user_id, u.user_key, uc.credential_id, uc.sp_hash, uc.pepper, uc.algorithm,
+            u.enabled, u.firstname, u.lastname, u.nickname
         FROM users u
         LEFT JOIN user_credentials uc ON uc.credential_id = u.credential
         WHERE u.user_key = :user_key;",
@@ -35,114 +36,124 @@ pub fn user_login(conn: &mut PooledConn, user_key: &str, salted_hash: &Vec<u8>)
     );
 
     // User has no password configured
-    let (pepper, salt): (Vec<u8>, Vec<u8>) = match (row.take("pepper").unwrap(), row.take("salt").unwrap()) {
-        (Some(pepper), Some(salt)) => (pepper, salt),
+    let (credential_id, pepper, algorithm): (u32, Vec<u8>, String) = match (
+        row.take("credential_id").unwrap(),
+        row.take("pepper").unwrap(),
+        row.take("algorithm").unwrap(),
+    ) {
+        (Some(credential_id), Some(pepper), Some(algorithm)) => (credential_id, pepper, algorithm),
         _ => return Err(ErrorKind::UserPasswordMissing),
     };
-    let peppered_hash: Vec<u8> = crate::common::hash_sha256(&salted_hash, &pepper);
-
-    println!(
-        "User {} login attempt with salted hash {} (salt {}) resulting in peppered hash {} (pepper {})",
-        user_key,
-        hex::encode(&salted_hash),
-        hex::encode(&salt),
-        hex::encode(&peppered_hash),
-        hex::encode(&pepper)
-    );
+    let algorithm = HashAlgorithm::from_str(&algorithm)?;
+    let peppered_hash: Vec<u8> = crate::common::hash_password(salted_hash, &pepper, &algorithm)?;
 
     let peppered_hash_db: Vec<u8> = row.take("sp_hash").unwrap();
 
-    if peppered_hash != peppered_hash_db {
+    if !crate::common::digest_eq(&peppered_hash, &peppered_hash_db) {
+        log::info!("User {} login failed", user_key);
         return Err(ErrorKind::UserLoginFail);
     };
 
+    // Transparently upgrade legacy hashes while the salted hash is at hand
+    if algorithm != HashAlgorithm::Argon2id {
+        user_credential_rehash(conn, credential_id, salted_hash)?;
+    }
+
     Ok(user)
 }
 
-pub fn user_right(conn: &mut PooledConn, user_id: u64) -> Result<Right, ErrorKind> {
+pub fn user_credential_rehash(conn: &mut PooledConn, credential_id: u32, salted_hash: &[u8]) -> Result<(), ErrorKind> {
+    let pepper: Vec<u8> = crate::common::random_bytes(16);
+    let algorithm = HashAlgorithm::Argon2id;
+    let peppered_hash: Vec<u8> = crate::common::hash_password(salted_hash, &pepper, &algorithm)?;
+
     let stmt = conn.prep(
-        "SELECT
-            COALESCE(MAX(right_club_write),0) AS right_club_write,
-            COALESCE(MAX(right_club_read),0) AS right_club_read,
-            COALESCE(MAX(right_competence_write),0) AS right_competence_write,
-            COALESCE(MAX(right_competence_read),0) AS right_competence_read,
-            COALESCE(MAX(right_course_write),0) AS right_course_write,
-            COALESCE(MAX(right_course_read),0) AS right_course_read,
-            COALESCE(MAX(right_event_write),0) AS right_event_write,
-            COALESCE(MAX(right_event_read),0) AS right_event_read,
-            COALESCE(MAX(right_inventory_write),0) AS right_inventory_write,
-            COALESCE(MAX(right_inventory_read),0) AS right_inventory_read,
-            COALESCE(MAX(right_location_write),0) AS right_location_write,
-            COALESCE(MAX(right_location_read),0) AS right_location_read,
-            COALESCE(MAX(right_organisation_write),0) AS right_organisation_write,
-            COALESCE(MAX(right_organisation_read),0) AS right_organisation_read,
-            COALESCE(MAX(right_team_write),0) AS right_team_write,
-            COALESCE(MAX(right_team_read),0) AS right_team_read,
-            COALESCE(MAX(right_user_write),0) AS right_user_write,
-            COALESCE(MAX(right_user_read),0) AS right_user_read
-        FROM users u
-        LEFT JOIN team_members ON (u.user_id = team_members.user_id)
-        LEFT JOIN teams ON (team_members.team_id = teams.team_id)
-        WHERE u.user_id = :user_id
-        GROUP BY u.user_id;",
+        "UPDATE user_credentials
+        SET pepper = :pepper, sp_hash = :sp_hash, algorithm = :algorithm
+        WHERE credential_id = :credential_id;",
     )?;
+    let params = params! {
+        "credential_id" => credential_id,
+        "pepper" => &pepper,
+        "sp_hash" => &peppered_hash,
+        "algorithm" => &algorithm,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/// Rights of the user in teams without a club, which hold for all clubs.
+/// Write permissions of teams requiring a second factor are only granted to users with confirmed TOTP,
+/// and only if the session was opened with it, as told by `second_factor`.
+/// Superusers hold all rights regardless of their teams, their write permissions depend on `second_factor` alike.
+pub fn user_right(conn: &mut PooledConn, user_id: u64, second_factor: bool) -> Result<Right, ErrorKind> {
+    let stmt = conn.prep("SELECT superuser FROM users WHERE user_id = :user_id;")?;
     let params = params! {
         "user_id" => user_id,
     };
 
-    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+    match conn.exec_first::<bool, _, _>(&stmt, &params)? {
         None => return Err(ErrorKind::UserMissing),
-        Some(row) => row,
+        Some(true) => return Ok(Right::superuser(second_factor)),
+        Some(false) => (),
     };
 
-    let right = Right {
-        right_club_write: row.take("right_club_write").unwrap(),
-        right_club_read: row.take("right_club_read").unwrap(),
-        right_competence_write: row.take("right_competence_write").unwrap(),
-        right_competence_read: row.take("right_competence_read").unwrap(),
-        right_course_write: row.take("right_course_write").unwrap(),
-        right_course_read: row.take("right_course_read").unwrap(),
-        right_event_write: row.take("right_event_write").unwrap(),
-        right_event_read: row.take("right_event_read").unwrap(),
-        right_inventory_write: row.take("right_inventory_write").unwrap(),
-        right_inventory_read: row.take("right_inventory_read").unwrap(),
-        right_location_write: row.take("right_location_write").unwrap(),
-        right_location_read: row.take("right_location_read").unwrap(),
-        right_organisation_write: row.take("right_organisation_write").unwrap(),
-        right_organisation_read: row.take("right_organisation_read").unwrap(),
-        right_team_write: row.take("right_team_write").unwrap(),
-        right_team_read: row.take("right_team_read").unwrap(),
-        right_user_write: row.take("right_user_write").unwrap(),
-        right_user_read: row.take("right_user_read").unwrap(),
-    };
+    let mut right = Right::default();
+    for (club_id, permission) in user_permission_list(conn, user_id, second_factor)? {
+        if club_id.is_none() {
+            right.permissions.insert(permission);
+        }
+    }
 
     Ok(right)
 }
 
-pub fn event_credential(conn: &mut PooledConn, event_id: u64) -> Result<(String, String), ErrorKind> {
+/// Rights of the user in teams confined to a club, keyed by the club
+pub fn user_club_right(conn: &mut PooledConn, user_id: u64, second_factor: bool) -> Result<ClubRights, ErrorKind> {
+    let mut rights = ClubRights::new();
+    for (club_id, permission) in user_permission_list(conn, user_id, second_factor)? {
+        if let Some(club_id) = club_id {
+            rights.entry(club_id).or_default().permissions.insert(permission);
+        }
+    }
+
+    Ok(rights)
+}
+
+fn user_permission_list(
+    conn: &mut PooledConn,
+    user_id: u64,
+    second_factor: bool,
+) -> Result<Vec<(Option<u32>, Permission)>, ErrorKind> {
     let stmt = conn.prep(
-        "SELECT event_key, pwd
-        FROM events
-        WHERE event_id = :event_id;",
+        "SELECT DISTINCT t.club_id, tp.permission
+        FROM team_members tm
+        JOIN users u ON (u.user_id = tm.user_id)
+        LEFT JOIN user_credentials uc ON (u.credential = uc.credential_id)
+        JOIN teams t ON (tm.team_id = t.team_id)
+        JOIN team_permissions tp ON (t.team_id = tp.team_id)
+        WHERE tm.user_id = :user_id
+        AND (tp.permission LIKE '%.read' OR NOT t.mfa_required OR (uc.totp_enabled AND :second_factor));",
     )?;
     let params = params! {
-        "event_id" => event_id,
-    };
-
-    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
-        None => return Err(ErrorKind::EventMissing),
-        Some(row) => row,
+        "user_id" => user_id,
+        "second_factor" => second_factor,
     };
 
-    let event_key: String = row.take("event_key").unwrap();
-    let event_pwd: String = row.take("pwd").unwrap();
+    let rows = conn.exec::<(Option<u32>, String), _, _>(&stmt, &params)?;
 
-    Ok((event_key, event_pwd))
+    // Permissions which are no longer registered are ignored
+    let permissions = rows
+        .into_iter()
+        .filter_map(|(club_id, permission)| Permission::from_str(&permission).ok().map(|p| (club_id, p)))
+        .collect();
+    Ok(permissions)
 }
 
-pub fn event_login(conn: &mut PooledConn, event_key: &str) -> Result<(u64, String), ErrorKind> {
+pub fn event_login(conn: &mut PooledConn, event_key: &str, event_pwd: &str) -> Result<u64, ErrorKind> {
     let stmt = conn.prep(
-        "SELECT event_id, pwd
+        "SELECT event_id, pwd_pepper, pwd_hash, pwd_algorithm
         FROM events WHERE event_key = :event_key",
     )?;
     let params = params! {
@@ -155,8 +166,22 @@ pub fn event_login(conn: &mut PooledConn, event_key: &str) -> Result<(u64, Strin
     };
 
     let event_id: u64 = row.take("event_id").unwrap();
-    let event_pwd: String = row.take("pwd").unwrap();
-    Ok((event_id, event_pwd))
+    let pepper: Vec<u8> = row.take("pwd_pepper").unwrap();
+    let peppered_hash_db: Vec<u8> = row.take("pwd_hash").unwrap();
+    let algorithm = HashAlgorithm::from_str(&row.take::<String, &str>("pwd_algorithm").unwrap())?;
+    let peppered_hash: Vec<u8> = crate::common::hash_password(event_pwd.as_bytes(), &pepper, &algorithm)?;
+
+    if !crate::common::digest_eq(&peppered_hash, &peppered_hash_db) {
+        log::info!("Event {} login failed", event_key);
+        return Err(ErrorKind::EventLoginFail);
+    };
+
+    // Transparently upgrade legacy hashes while the clear password is at hand
+    if algorithm != HashAlgorithm::Argon2id {
+        crate::db::event::event_password_edit(conn, event_id, event_pwd)?;
+    }
+
+    Ok(event_id)
 }
 
 pub fn course_current_event(
@@ -164,9 +189,9 @@ pub fn course_current_event(
     course_key: &str,
     date_min: &chrono::NaiveDateTime,
     date_max: &chrono::NaiveDateTime,
-) -> Result<(String, String), ErrorKind> {
+) -> Result<u64, ErrorKind> {
     let stmt = conn.prep(
-        "SELECT s.event_key, s.pwd
+        "SELECT s.event_id
         FROM events s
         JOIN courses c ON c.course_id = s.course_id
         WHERE c.course_key = :course_key
@@ -179,14 +204,10 @@ pub fn course_current_event(
         "date_max" => date_max,
     };
 
-    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
-        None => return Err(ErrorKind::EventMissing),
-        Some(row) => row,
-    };
-
-    let event_key: String = row.take("event_key").unwrap();
-    let event_pwd: String = row.take("pwd").unwrap();
-    Ok((event_key, event_pwd))
+    match conn.exec_first::<u64, _, _>(&stmt, &params)? {
+        None => Err(ErrorKind::EventMissing),
+        Some(event_id) => Ok(event_id),
+    }
 }
 
 pub fn location_current_event(
@@ -194,9 +215,9 @@ pub fn location_current_event(
     location_key: &str,
     date_min: &chrono::NaiveDateTime,
     date_max: &chrono::NaiveDateTime,
-) -> Result<(String, String), ErrorKind> {
+) -> Result<u64, ErrorKind> {
     let stmt = conn.prep(
-        "SELECT s.event_key, s.pwd
+        "SELECT s.event_id
         FROM events s
         JOIN locations l ON l.location_id = s.location_id
         WHERE l.location_key = :location_key
@@ -209,11 +230,191 @@ pub fn location_current_event(
         "date_max" => date_max,
     };
 
-    let mut row: mysql::Row = match conn.exec_first(&stmt, &params) {
-        Err(..) | Ok(None) => return Err(ErrorKind::EventMissing),
-        Ok(Some(row)) => row,
+    match conn.exec_first::<u64, _, _>(&stmt, &params) {
+        Err(..) | Ok(None) => Err(ErrorKind::EventMissing),
+        Ok(Some(event_id)) => Ok(event_id),
+    }
+}
+
+/// Enabled user matching the claim of an identity provider, either by subject or by email.
+/// Subjects match the linked subject of a user, or the user key of users without one.
+pub fn oidc_login(conn: &mut PooledConn, subject: Option<&str>, email: Option<&str>) -> Result<User, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT user_id, user_key, enabled, firstname, lastname, nickname
+        FROM users
+        WHERE oidc_subject = :subject
+        OR (oidc_subject IS NULL AND user_key = :subject)
+        OR email = :email;",
+    )?;
+    let params = params! {
+        "subject" => subject,
+        "email" => email,
     };
-    let event_key: String = row.take("event_key").unwrap();
-    let event_pwd: String = row.take("pwd").unwrap();
-    Ok((event_key, event_pwd))
+
+    let mut rows: Vec<mysql::Row> = conn.exec(&stmt, &params)?;
+
+    // Ambiguous emails are not resolved to any user
+    let mut row = match rows.len() {
+        1 => rows.remove(0),
+        _ => return Err(ErrorKind::UserMissing),
+    };
+
+    if !row.take::<bool, &str>("enabled").unwrap() {
+        return Err(ErrorKind::UserDisabled);
+    }
+
+    let user = User::from_info(
+        row.take("user_id").unwrap(),
+        row.take("user_key").unwrap(),
+        row.take("firstname").unwrap(),
+        row.take("lastname").unwrap(),
+        row.take("nickname").unwrap(),
+    );
+
+    Ok(user)
+}
+
+pub fn oidc_state_create(
+    conn: &mut PooledConn,
+    state: &str,
+    verifier: &str,
+    expiry: &chrono::NaiveDateTime,
+) -> Result<(), ErrorKind> {
+    conn.query_drop("DELETE FROM oidc_states WHERE expiry < UTC_TIMESTAMP();")?;
+
+    let stmt = conn.prep("INSERT INTO oidc_states (state, verifier, expiry) VALUES (:state, :verifier, :expiry);")?;
+    let params = params! {
+        "state" => state,
+        "verifier" => verifier,
+        "expiry" => expiry,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/// Consume a state, returns its PKCE verifier if it was issued and did not expire yet
+pub fn oidc_state_take(conn: &mut PooledConn, state: &str) -> Result<Option<String>, ErrorKind> {
+    let stmt_select =
+        conn.prep("SELECT verifier FROM oidc_states WHERE state = :state AND expiry > UTC_TIMESTAMP();")?;
+    let stmt_delete = conn.prep("DELETE FROM oidc_states WHERE state = :state;")?;
+    let params = params! {
+        "state" => state,
+    };
+
+    let verifier = conn.exec_first::<String, _, _>(&stmt_select, &params)?;
+    conn.exec_drop(&stmt_delete, &params)?;
+
+    // A concurrent callback already consumed the state
+    match conn.affected_rows() {
+        0 => Ok(None),
+        _ => Ok(verifier),
+    }
+}
+
+/* LOGIN FAILURES */
+
+pub fn login_failure_list(conn: &mut PooledConn) -> Result<Vec<LoginFailure>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT category, subject, attempts, last_attempt, locked_until
+        FROM login_failures
+        ORDER BY last_attempt DESC;",
+    )?;
+
+    let rows: Vec<mysql::Row> = conn.exec(&stmt, params::Params::Empty)?;
+    rows.into_iter().map(login_failure_row_map).collect()
+}
+
+fn login_failure_row_map(mut row: mysql::Row) -> Result<LoginFailure, ErrorKind> {
+    let category: String = row.take("category").unwrap();
+    Ok(LoginFailure {
+        category: LoginCategory::from_str(&category)?,
+        subject: row.take("subject").unwrap(),
+        attempts: row.take("attempts").unwrap(),
+        last_attempt: row.take("last_attempt").unwrap(),
+        locked_until: row.take("locked_until").unwrap(),
+    })
+}
+
+pub fn login_failure_info(
+    conn: &mut PooledConn,
+    category: &LoginCategory,
+    subject: &str,
+) -> Result<Option<LoginFailure>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT attempts, last_attempt, locked_until
+        FROM login_failures
+        WHERE category = :category AND subject = :subject;",
+    )?;
+    let params = params! {
+        "category" => category,
+        "subject" => subject,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Ok(None),
+        Some(row) => row,
+    };
+
+    Ok(Some(LoginFailure {
+        category: category.clone(),
+        subject: subject.to_string(),
+        attempts: row.take("attempts").unwrap(),
+        last_attempt: row.take("last_attempt").unwrap(),
+        locked_until: row.take("locked_until").unwrap(),
+    }))
+}
+
+pub fn login_failure_add(conn: &mut PooledConn, category: &LoginCategory, subject: &str) -> Result<u16, ErrorKind> {
+    let stmt = conn.prep(
+        "INSERT INTO login_failures (category, subject, attempts, last_attempt)
+        VALUES (:category, :subject, 1, UTC_TIMESTAMP())
+        ON DUPLICATE KEY UPDATE attempts = attempts + 1, last_attempt = UTC_TIMESTAMP();",
+    )?;
+    let params = params! {
+        "category" => category,
+        "subject" => subject,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+
+    match login_failure_info(conn, category, subject)? {
+        None => Err(ErrorKind::DatabaseError),
+        Some(failure) => Ok(failure.attempts),
+    }
+}
+
+pub fn login_failure_lock(
+    conn: &mut PooledConn,
+    category: &LoginCategory,
+    subject: &str,
+    locked_until: &chrono::NaiveDateTime,
+) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE login_failures
+        SET locked_until = :locked_until
+        WHERE category = :category AND subject = :subject;",
+    )?;
+    let params = params! {
+        "category" => category,
+        "subject" => subject,
+        "locked_until" => locked_until,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn login_failure_delete(conn: &mut PooledConn, category: &LoginCategory, subject: &str) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "DELETE FROM login_failures
+        WHERE category = :category AND subject = :subject;",
+    )?;
+    let params = params! {
+        "category" => category,
+        "subject" => subject,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
 }
@@ -1,3 +1,5 @@
+pub mod api_token;
+pub mod audit;
 pub mod club;
 pub mod competence;
 pub mod course;
@@ -6,6 +8,8 @@ pub mod inventory;
 pub mod location;
 pub mod login;
 pub mod organisation;
+pub mod retention;
+pub mod session;
 pub mod skill;
 pub mod team;
 pub mod user;
@@ -14,7 +18,27 @@ use crate::error::ErrorKind;
 use mysql::prelude::Queryable;
 use mysql::PooledConn;
 
-static SCHEME_VERSION: u8 = 3;
+static SCHEME_VERSION: u8 = 4;
+
+/// Run several database functions within one transaction, which is rolled back on any error.
+/// Within an open transaction, the functions join it instead, so that transactions can be nested.
+pub fn transaction<T>(
+    conn: &mut PooledConn,
+    f: impl FnOnce(&mut PooledConn) -> Result<T, ErrorKind>,
+) -> Result<T, ErrorKind> {
+    // Transactions are opened by disabling autocommit, which unlike START TRANSACTION does not commit an open one
+    if conn.query_first::<bool, _>("SELECT @@autocommit;")? == Some(false) {
+        return f(conn);
+    }
+
+    conn.query_drop("SET autocommit = 0;")?;
+    let result = match f(conn) {
+        Ok(value) => conn.query_drop("COMMIT;").map(|_| value).map_err(ErrorKind::from),
+        Err(e) => conn.query_drop("ROLLBACK;").map_err(ErrorKind::from).and(Err(e)),
+    };
+    conn.query_drop("SET autocommit = 1;")?;
+    result
+}
 
 pub fn get_version(conn: &mut PooledConn) -> Result<u8, ErrorKind> {
     let query_version = "SELECT version FROM _info;";
@@ -39,7 +63,7 @@ pub fn migrate_scheme(conn: &mut PooledConn, db_name: &str) -> Result<(), ErrorK
     if is_empty {
         let partial_path = format!("sql/schema_{}.sql", latest_version);
         let local_path = crate::common::fs::local_path(&partial_path);
-        println!("DB: Fresh setup to version {}", latest_version);
+        log::info!("DB: Fresh setup to version {}", latest_version);
 
         // Apply the schema
         let query_schema = std::fs::read_to_string(local_path).map_err(|_| ErrorKind::Default)?;
@@ -65,7 +89,7 @@ pub fn migrate_scheme(conn: &mut PooledConn, db_name: &str) -> Result<(), ErrorK
         // Case 3: Schema info exists or was set in case 2
         let mut current_version: u8 = get_version(conn)?;
 
-        println!("DB: Migration from version {} to {}", current_version, latest_version);
+        log::info!("DB: Migration from version {} to {}", current_version, latest_version);
         // Do incremental migrations
         while current_version < latest_version {
             let partial_path = format!("sql/migrate_{}.sql", current_version + 1);
@@ -0,0 +1,113 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::common::{RetentionEvent, User};
+use crate::error::ErrorKind;
+
+/// Inactive users without logins, terms, attendance or edits since the cutoff, who are not yet anonymised
+pub fn retention_user_list(conn: &mut PooledConn, cutoff: chrono::NaiveDateTime) -> Result<Vec<User>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT u.user_id, u.user_key, u.firstname, u.lastname, u.nickname
+        FROM users u
+        LEFT JOIN user_credentials uc ON (uc.credential_id = u.credential)
+        WHERE u.active = FALSE AND u.superuser = FALSE AND u.anonymised IS NULL
+        AND (u.last_login IS NULL OR u.last_login < :cutoff)
+        AND (uc.since IS NULL OR uc.since < :cutoff)
+        AND NOT EXISTS (
+            SELECT 1 FROM sessions s
+            WHERE s.user_id = u.user_id AND s.expiry >= :cutoff)
+        AND NOT EXISTS (
+            SELECT 1 FROM api_tokens k
+            WHERE k.user_id = u.user_id AND k.last_used >= :cutoff)
+        AND NOT EXISTS (
+            SELECT 1 FROM terms t
+            WHERE t.user_id = u.user_id AND (t.term_end IS NULL OR t.term_end >= :cutoff))
+        AND NOT EXISTS (
+            SELECT 1 FROM event_attendance_presences p
+            JOIN events e ON (e.event_id = p.event_id)
+            WHERE p.user_id = u.user_id AND e.end >= :cutoff)
+        AND NOT EXISTS (
+            SELECT 1 FROM event_attendance_registrations r
+            JOIN events e ON (e.event_id = r.event_id)
+            WHERE r.user_id = u.user_id AND e.end >= :cutoff)
+        AND NOT EXISTS (
+            SELECT 1 FROM audit_log a
+            WHERE a.entity = 'user' AND a.entity_id = u.user_id AND a.time >= :cutoff)
+        ORDER BY u.user_id;",
+    )?;
+    let params = params! {
+        "cutoff" => cutoff,
+    };
+
+    let map = |(user_id, user_key, firstname, lastname, nickname)| {
+        User::from_info(user_id, user_key, firstname, lastname, nickname)
+    };
+
+    let users = conn.exec_map(&stmt, &params, &map)?;
+    Ok(users)
+}
+
+/// Events which ended before the cutoff and still have attendance
+pub fn retention_event_list(
+    conn: &mut PooledConn,
+    cutoff: chrono::NaiveDateTime,
+) -> Result<Vec<RetentionEvent>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT e.event_id, e.event_key, e.title, e.end,
+            (SELECT COUNT(*) FROM event_attendance_presences p WHERE p.event_id = e.event_id) AS presences,
+            (SELECT COUNT(*) FROM event_attendance_registrations r WHERE r.event_id = e.event_id) AS registrations
+        FROM events e
+        WHERE e.end < :cutoff
+        HAVING presences > 0 OR registrations > 0
+        ORDER BY e.end;",
+    )?;
+    let params = params! {
+        "cutoff" => cutoff,
+    };
+
+    let map = |(id, key, title, end, presences, registrations)| RetentionEvent {
+        id,
+        key,
+        title,
+        end,
+        presences,
+        registrations,
+    };
+
+    let events = conn.exec_map(&stmt, &params, &map)?;
+    Ok(events)
+}
+
+pub fn retention_attendance_delete(conn: &mut PooledConn, cutoff: chrono::NaiveDateTime) -> Result<(), ErrorKind> {
+    for table in ["event_attendance_presences", "event_attendance_registrations"] {
+        conn.exec_drop(
+            format!(
+                "DELETE a FROM {table} a
+                JOIN events e ON (e.event_id = a.event_id)
+                WHERE e.end < :cutoff;"
+            ),
+            params! { "cutoff" => cutoff },
+        )?;
+    }
+    Ok(())
+}
+
+pub fn retention_audit_count(conn: &mut PooledConn, cutoff: chrono::NaiveDateTime) -> Result<u64, ErrorKind> {
+    let stmt = conn.prep("SELECT COUNT(*) FROM audit_log WHERE time < :cutoff;")?;
+    let params = params! {
+        "cutoff" => cutoff,
+    };
+
+    let count: Option<u64> = conn.exec_first(&stmt, &params)?;
+    Ok(count.unwrap_or(0))
+}
+
+pub fn retention_audit_delete(conn: &mut PooledConn, cutoff: chrono::NaiveDateTime) -> Result<(), ErrorKind> {
+    let stmt = conn.prep("DELETE FROM audit_log WHERE time < :cutoff;")?;
+    let params = params! {
+        "cutoff" => cutoff,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::common::{ClubRights, Right, Session, User};
+use crate::error::ErrorKind;
+
+/*
+ * USER SESSIONS
+ */
+
+pub fn user_session_create(
+    conn: &mut PooledConn,
+    token: &str,
+    user_id: u64,
+    second_factor: bool,
+    right: &Right,
+    club_rights: &ClubRights,
+    expiry: &chrono::NaiveDateTime,
+    client: Option<String>,
+) -> Result<(), ErrorKind> {
+    let rights = serde_json::to_string(right).map_err(|_| ErrorKind::Parsing)?;
+    let club_rights = serde_json::to_string(club_rights).map_err(|_| ErrorKind::Parsing)?;
+
+    let stmt = conn.prep(
+        "INSERT INTO sessions (token, user_id, rights, club_rights, second_factor, client, created, expiry)
+        VALUES (:token, :user_id, :rights, :club_rights, :second_factor, :client, UTC_TIMESTAMP(), :expiry);",
+    )?;
+    let params = params! {
+        "token" => token,
+        "user_id" => user_id,
+        "second_factor" => second_factor,
+        "rights" => &rights,
+        "club_rights" => &club_rights,
+        "client" => &client,
+        "expiry" => expiry,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+
+    let stmt = conn.prep("UPDATE users SET last_login = UTC_TIMESTAMP() WHERE user_id = :user_id;")?;
+    conn.exec_drop(&stmt, params! { "user_id" => user_id })?;
+    Ok(())
+}
+
+pub fn user_session_info(
+    conn: &mut PooledConn,
+    token: &str,
+) -> Result<Option<(User, Right, ClubRights, Session)>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT u.user_id, u.user_key, u.firstname, u.lastname, u.nickname, s.rights, s.club_rights,
+            s.created, s.expiry, s.client
+        FROM sessions s
+        JOIN users u ON u.user_id = s.user_id
+        WHERE s.token = :token;",
+    )?;
+    let params = params! {
+        "token" => token,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Ok(None),
+        Some(row) => row,
+    };
+
+    let user = User::from_info(
+        row.take("user_id").unwrap(),
+        row.take("user_key").unwrap(),
+        row.take("firstname").unwrap(),
+        row.take("lastname").unwrap(),
+        row.take("nickname").unwrap(),
+    );
+
+    let rights: String = row.take("rights").unwrap();
+    let right = Right::from_stored(&rights)?;
+    let club_rights: ClubRights = match row.take::<Option<String>, &str>("club_rights").unwrap() {
+        None => ClubRights::new(),
+        Some(club_rights) => crate::common::club_rights_from_stored(&club_rights)?,
+    };
+    let session = Session {
+        created: row.take("created").unwrap(),
+        expiry: row.take("expiry").unwrap(),
+        client: row.take("client").unwrap(),
+    };
+
+    Ok(Some((user, right, club_rights, session)))
+}
+
+pub fn user_session_list(conn: &mut PooledConn, user_id: u64) -> Result<Vec<Session>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT created, expiry, client
+        FROM sessions
+        WHERE user_id = :user_id AND expiry > UTC_TIMESTAMP()
+        ORDER BY created;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    let map = |(created, expiry, client)| Session {
+        created,
+        expiry,
+        client,
+    };
+
+    let sessions = conn.exec_map(&stmt, &params, &map)?;
+    Ok(sessions)
+}
+
+/// Replace the rights of the sessions of a user which were opened with or without a second factor
+pub fn user_session_right_edit(
+    conn: &mut PooledConn,
+    user_id: u64,
+    second_factor: bool,
+    right: &Right,
+    club_rights: &ClubRights,
+) -> Result<(), ErrorKind> {
+    let rights = serde_json::to_string(right).map_err(|_| ErrorKind::Parsing)?;
+    let club_rights = serde_json::to_string(club_rights).map_err(|_| ErrorKind::Parsing)?;
+
+    let stmt = conn.prep(
+        "UPDATE sessions
+        SET rights = :rights, club_rights = :club_rights
+        WHERE user_id = :user_id AND second_factor = :second_factor;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "second_factor" => second_factor,
+        "rights" => &rights,
+        "club_rights" => &club_rights,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn user_session_delete(conn: &mut PooledConn, user_id: u64) -> Result<(), ErrorKind> {
+    let stmt = conn.prep("DELETE FROM sessions WHERE user_id = :user_id;")?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/*
+ * EVENT SESSIONS
+ */
+
+pub fn event_session_create(
+    conn: &mut PooledConn,
+    token: &str,
+    event_id: u64,
+    expiry: &chrono::NaiveDateTime,
+    client: Option<String>,
+) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "INSERT INTO sessions (token, event_id, client, created, expiry)
+        VALUES (:token, :event_id, :client, UTC_TIMESTAMP(), :expiry);",
+    )?;
+    let params = params! {
+        "token" => token,
+        "event_id" => event_id,
+        "client" => &client,
+        "expiry" => expiry,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn event_session_info(
+    conn: &mut PooledConn,
+    token: &str,
+) -> Result<Option<(u64, chrono::NaiveDateTime)>, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT event_id, expiry
+        FROM sessions
+        WHERE token = :token AND event_id IS NOT NULL;",
+    )?;
+    let params = params! {
+        "token" => token,
+    };
+
+    let session = conn.exec_first::<(u64, chrono::NaiveDateTime), _, _>(&stmt, &params)?;
+    Ok(session)
+}
+
+/*
+ * COMMON
+ */
+
+pub fn session_refresh(
+    conn: &mut PooledConn,
+    token: &str,
+    token_new: &str,
+    expiry: &chrono::NaiveDateTime,
+) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE sessions
+        SET token = :token_new, expiry = :expiry
+        WHERE token = :token;",
+    )?;
+    let params = params! {
+        "token" => token,
+        "token_new" => token_new,
+        "expiry" => expiry,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn session_expiry_edit(
+    conn: &mut PooledConn,
+    token: &str,
+    expiry: &chrono::NaiveDateTime,
+) -> Result<(), ErrorKind> {
+    let stmt = conn.prep(
+        "UPDATE sessions
+        SET expiry = :expiry
+        WHERE token = :token;",
+    )?;
+    let params = params! {
+        "token" => token,
+        "expiry" => expiry,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn session_delete(conn: &mut PooledConn, token: &str) -> Result<(), ErrorKind> {
+    let stmt = conn.prep("DELETE FROM sessions WHERE token = :token;")?;
+    let params = params! {
+        "token" => token,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn session_expired_delete(conn: &mut PooledConn) -> Result<(), ErrorKind> {
+    conn.query_drop("DELETE FROM sessions WHERE expiry < UTC_TIMESTAMP();")?;
+    Ok(())
+}
@@ -24,6 +24,33 @@ pub fn skill_list(conn: &mut PooledConn) -> Result<Vec<Skill>, ErrorKind> {
     Ok(terms)
 }
 
+pub fn skill_info(conn: &mut PooledConn, skill_id: u32) -> Result<Skill, ErrorKind> {
+    let stmt = conn.prep(
+        "SELECT skill_id, skill_key, title, min, max
+        FROM skills
+        WHERE skill_id = :skill_id;",
+    )?;
+
+    let params = params! {
+        "skill_id" => skill_id,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Err(ErrorKind::Missing),
+        Some(row) => row,
+    };
+
+    let skill = Skill {
+        id: row.take("skill_id").unwrap(),
+        key: row.take("skill_key").unwrap(),
+        title: row.take("title").unwrap(),
+        min: row.take("min").unwrap(),
+        max: row.take("max").unwrap(),
+    };
+
+    Ok(skill)
+}
+
 pub fn skill_create(conn: &mut PooledConn, skill: &Skill) -> Result<u32, ErrorKind> {
     let stmt = conn.prep(
         "INSERT INTO skills (skill_key, title, min, max)
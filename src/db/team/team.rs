@@ -1,7 +1,7 @@
 use mysql::prelude::Queryable;
 use mysql::{params, PooledConn};
 
-use crate::common::{Right, Team};
+use crate::common::{FromStr, Permission, Right, Team};
 use crate::error::ErrorKind;
 
 /*
@@ -14,7 +14,8 @@ pub fn team_list(conn: &mut PooledConn) -> Result<Vec<Team>, ErrorKind> {
             team_id,
             team_key,
             name,
-            description        
+            description,
+            club_id
         FROM teams;",
     )?;
 
@@ -30,6 +31,8 @@ pub fn team_list(conn: &mut PooledConn) -> Result<Vec<Team>, ErrorKind> {
             key: row.take("team_key").unwrap(),
             name: row.take("name").unwrap(),
             description: row.take("description").unwrap(),
+            mfa_required: None,
+            club_id: row.take("club_id").unwrap(),
             right: None,
         };
         teams.push(team);
@@ -45,24 +48,8 @@ pub fn team_info(conn: &mut PooledConn, team_id: &u32) -> Result<Team, ErrorKind
             team_key,
             name,
             description,
-            right_club_write,
-            right_club_read,
-            right_competence_write,
-            right_competence_read,
-            right_course_write,
-            right_course_read,
-            right_event_write,
-            right_event_read,
-            right_inventory_write,
-            right_inventory_read,
-            right_location_write,
-            right_location_read,
-            right_organisation_write,
-            right_organisation_read,
-            right_team_write,
-            right_team_read,
-            right_user_write,
-            right_user_read            
+            mfa_required,
+            club_id
         FROM teams
         WHERE team_id = :team_id;",
     )?;
@@ -81,26 +68,9 @@ pub fn team_info(conn: &mut PooledConn, team_id: &u32) -> Result<Team, ErrorKind
         key: row.take("team_key").unwrap(),
         name: row.take("name").unwrap(),
         description: row.take("description").unwrap(),
-        right: Some(Right {
-            right_club_write: row.take("right_club_write").unwrap(),
-            right_club_read: row.take("right_club_read").unwrap(),
-            right_competence_write: row.take("right_competence_write").unwrap(),
-            right_competence_read: row.take("right_competence_read").unwrap(),
-            right_course_write: row.take("right_course_write").unwrap(),
-            right_course_read: row.take("right_course_read").unwrap(),
-            right_event_write: row.take("right_event_write").unwrap(),
-            right_event_read: row.take("right_event_read").unwrap(),
-            right_inventory_write: row.take("right_inventory_write").unwrap(),
-            right_inventory_read: row.take("right_inventory_read").unwrap(),
-            right_location_write: row.take("right_location_write").unwrap(),
-            right_location_read: row.take("right_location_read").unwrap(),
-            right_organisation_write: row.take("right_organisation_write").unwrap(),
-            right_organisation_read: row.take("right_organisation_read").unwrap(),
-            right_team_write: row.take("right_team_write").unwrap(),
-            right_team_read: row.take("right_team_read").unwrap(),
-            right_user_write: row.take("right_user_write").unwrap(),
-            right_user_read: row.take("right_user_read").unwrap(),
-        }),
+        mfa_required: row.take("mfa_required").unwrap(),
+        club_id: row.take("club_id").unwrap(),
+        right: Some(team_permission_list(conn, team_id)?),
     };
 
     Ok(team)
@@ -111,17 +81,23 @@ pub fn team_create(conn: &mut PooledConn, team: &Team) -> Result<u32, ErrorKind>
         "INSERT INTO teams (
             team_key,
             name,
-            description)
+            description,
+            mfa_required,
+            club_id)
         VALUES (
             :team_key,
             :name,
-            :description);",
+            :description,
+            :mfa_required,
+            :club_id);",
     )?;
 
     let params = params! {
         "team_key" => &team.key,
         "name" => &team.name,
         "description" => &team.description,
+        "mfa_required" => team.mfa_required.unwrap_or(false),
+        "club_id" => &team.club_id,
     };
 
     conn.exec_drop(&stmt, &params)?;
@@ -134,7 +110,9 @@ pub fn team_edit(conn: &mut PooledConn, team_id: &u32, team: &Team) -> Result<()
         "UPDATE teams SET
             team_key = :team_key,
             name = :name,
-            description = :description
+            description = :description,
+            mfa_required = COALESCE(:mfa_required, mfa_required),
+            club_id = :club_id
         WHERE team_id = :team_id",
     )?;
 
@@ -143,60 +121,56 @@ pub fn team_edit(conn: &mut PooledConn, team_id: &u32, team: &Team) -> Result<()
         "team_key" => &team.key,
         "name" => &team.name,
         "description" => &team.description,
+        "mfa_required" => &team.mfa_required,
+        "club_id" => &team.club_id,
     };
 
     conn.exec_drop(&stmt, &params)?;
     Ok(())
 }
 
-pub fn team_right_edit(conn: &mut PooledConn, team_id: &u32, right: &Right) -> Result<(), ErrorKind> {
-    let stmt = conn.prep(
-        "UPDATE teams SET
-            right_club_write = :right_club_write,
-            right_club_read = :right_club_read,
-            right_competence_write = :right_competence_write,
-            right_competence_read = :right_competence_read,
-            right_course_write = :right_course_write,
-            right_course_read = :right_course_read,
-            right_event_write = :right_event_write,
-            right_event_read = :right_event_read,
-            right_inventory_write = :right_inventory_write,
-            right_inventory_read = :right_inventory_read,
-            right_location_write = :right_location_write,
-            right_location_read = :right_location_read,
-            right_organisation_write = :right_organisation_write,
-            right_organisation_read = :right_organisation_read,
-            right_team_write = :right_team_write,
-            right_team_read = :right_team_read,
-            right_user_write = :right_user_write,
-            right_user_read = :right_user_read
-        WHERE team_id = :team_id",
-    )?;
-
+fn team_permission_list(conn: &mut PooledConn, team_id: &u32) -> Result<Right, ErrorKind> {
+    let stmt = conn.prep("SELECT permission FROM team_permissions WHERE team_id = :team_id;")?;
     let params = params! {
         "team_id" => team_id,
-        "right_club_write" => &right.right_club_write,
-        "right_club_read" => &right.right_club_read,
-        "right_competence_write" => &right.right_competence_write,
-        "right_competence_read" => &right.right_competence_read,
-        "right_course_write" => &right.right_course_write,
-        "right_course_read" => &right.right_course_read,
-        "right_event_write" => &right.right_event_write,
-        "right_event_read" => &right.right_event_read,
-        "right_inventory_write" => &right.right_inventory_write,
-        "right_inventory_read" => &right.right_inventory_read,
-        "right_location_write" => &right.right_location_write,
-        "right_location_read" => &right.right_location_read,
-        "right_organisation_write" => &right.right_organisation_write,
-        "right_organisation_read" => &right.right_organisation_read,
-        "right_team_write" => &right.right_team_write,
-        "right_team_read" => &right.right_team_read,
-        "right_user_write" => &right.right_user_write,
-        "right_user_read" => &right.right_user_read,
     };
 
-    conn.exec_drop(&stmt, &params)?;
-    Ok(())
+    let permissions = conn.exec::<String, _, _>(&stmt, &params)?;
+
+    // Permissions which are no longer registered are ignored
+    let right = Right {
+        permissions: permissions
+            .iter()
+            .filter_map(|p| Permission::from_str(p).ok())
+            .collect(),
+    };
+    Ok(right)
+}
+
+/// Replace all permissions of the team
+pub fn team_right_edit(conn: &mut PooledConn, team_id: &u32, right: &Right) -> Result<(), ErrorKind> {
+    // The team must not be left without permissions if the insert fails
+    crate::db::transaction(conn, |conn| {
+        let stmt = conn.prep("DELETE FROM team_permissions WHERE team_id = :team_id;")?;
+        let params = params! {
+            "team_id" => team_id,
+        };
+        conn.exec_drop(&stmt, &params)?;
+
+        let stmt = conn.prep(
+            "INSERT INTO team_permissions (team_id, permission)
+            VALUES (:team_id, :permission);",
+        )?;
+        let params = right.permissions.iter().map(|permission| {
+            params! {
+                "team_id" => team_id,
+                "permission" => *permission,
+            }
+        });
+
+        conn.exec_batch(&stmt, params)?;
+        Ok(())
+    })
 }
 
 pub fn team_delete(conn: &mut PooledConn, team_id: &u32) -> Result<(), ErrorKind> {
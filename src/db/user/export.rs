@@ -0,0 +1,143 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::common::{CredentialMetadata, LoginCategory, Team, UserDataExport, UserRelation};
+use crate::error::Result;
+
+/// Collect everything stored about the user, except for secrets such as password hashes
+pub fn user_data_export(conn: &mut PooledConn, user_id: u64) -> Result<UserDataExport> {
+    let user = crate::db::user::user_detailed(conn, user_id)?;
+    let login_failure = match &user.key {
+        None => None,
+        Some(user_key) => crate::db::login::login_failure_info(conn, &LoginCategory::User, user_key)?,
+    };
+    let audit = crate::db::audit::audit_user_list(conn, user_id)?;
+
+    let export = UserDataExport {
+        exported: chrono::Utc::now().naive_utc(),
+        user,
+        credential: user_credential_metadata(conn, user_id)?,
+        teams: user_team_list(conn, user_id)?,
+        terms: crate::db::club::term_list(conn, None, Some(user_id as u32), None)?,
+        affiliations: crate::db::organisation::affiliation_list(conn, Some(user_id), None)?,
+        competences: crate::db::competence::competence_list(conn, Some(user_id), None, i16::MIN, i16::MAX)?,
+        possessions: crate::db::inventory::possession_list(conn, Some(user_id), None, None, None)?,
+        relations: user_relation_list(conn, user_id)?,
+        sessions: crate::db::session::user_session_list(conn, user_id)?,
+        api_tokens: crate::db::api_token::api_token_list(conn, user_id)?,
+        login_failure,
+        audit: crate::common::export_audit(audit, user_id),
+    };
+
+    Ok(export)
+}
+
+fn user_credential_metadata(conn: &mut PooledConn, user_id: u64) -> Result<CredentialMetadata> {
+    let stmt = conn.prep(
+        "SELECT uc.since, uc.totp_enabled,
+            (SELECT COUNT(1) FROM user_recovery_codes rc WHERE rc.credential_id = uc.credential_id) AS recovery_codes,
+            u.oidc_subject, u.feed_token_hash IS NOT NULL AS calendar_feed
+        FROM users u
+        LEFT JOIN user_credentials uc ON (uc.credential_id = u.credential)
+        WHERE u.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Ok(CredentialMetadata::default()),
+        Some(row) => row,
+    };
+
+    let metadata = CredentialMetadata {
+        password_since: row.take("since").unwrap(),
+        totp_enabled: row.take::<Option<bool>, &str>("totp_enabled").unwrap().unwrap_or(false),
+        recovery_codes: row.take("recovery_codes").unwrap(),
+        oidc_subject: row.take("oidc_subject").unwrap(),
+        calendar_feed: row.take("calendar_feed").unwrap(),
+    };
+    Ok(metadata)
+}
+
+fn user_team_list(conn: &mut PooledConn, user_id: u64) -> Result<Vec<Team>> {
+    let stmt = conn.prep(
+        "SELECT t.team_id, t.team_key, t.name, t.description, t.club_id
+        FROM team_members tm
+        JOIN teams t ON (t.team_id = tm.team_id)
+        WHERE tm.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    let map = |(team_id, team_key, name, description, club_id)| Team {
+        id: team_id,
+        key: team_key,
+        name,
+        description,
+        mfa_required: None,
+        club_id,
+        right: None,
+    };
+
+    let teams = conn.exec_map(&stmt, &params, &map)?;
+    Ok(teams)
+}
+
+/// Registrations, presences, filters, bookmarks, ownerships and moderations of the user
+fn user_relation_list(conn: &mut PooledConn, user_id: u64) -> Result<Vec<UserRelation>> {
+    let stmt = conn.prep(
+        "SELECT 'REGISTRATION' AS relation, 'event' AS entity, e.event_id AS entity_id, e.event_key AS entity_key, e.title,
+            r.role, r.status
+        FROM event_attendance_registrations r
+        JOIN events e ON (e.event_id = r.event_id)
+        WHERE r.user_id = :user_id
+        UNION ALL
+        SELECT 'PRESENCE', 'event', e.event_id, e.event_key, e.title, p.role, NULL
+        FROM event_attendance_presences p
+        JOIN events e ON (e.event_id = p.event_id)
+        WHERE p.user_id = :user_id
+        UNION ALL
+        SELECT 'FILTER', 'event', e.event_id, e.event_key, e.title, f.role, IF(f.access, 'ALLOWED', 'DENIED')
+        FROM event_attendance_filters f
+        JOIN events e ON (e.event_id = f.event_id)
+        WHERE f.user_id = :user_id
+        UNION ALL
+        SELECT 'BOOKMARK', 'event', e.event_id, e.event_key, e.title, NULL, NULL
+        FROM event_bookmarks b
+        JOIN events e ON (e.event_id = b.event_id)
+        WHERE b.user_id = :user_id
+        UNION ALL
+        SELECT 'OWNER', 'event', e.event_id, e.event_key, e.title, NULL, NULL
+        FROM event_owners o
+        JOIN events e ON (e.event_id = o.event_id)
+        WHERE o.user_id = :user_id
+        UNION ALL
+        SELECT 'BOOKMARK', 'course', c.course_id, c.course_key, c.title, NULL, NULL
+        FROM course_bookmarks b
+        JOIN courses c ON (c.course_id = b.course_id)
+        WHERE b.user_id = :user_id
+        UNION ALL
+        SELECT 'MODERATOR', 'course', c.course_id, c.course_key, c.title, NULL, NULL
+        FROM course_moderators m
+        JOIN courses c ON (c.course_id = m.course_id)
+        WHERE m.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    let map = |(relation, entity, entity_id, key, title, role, status)| UserRelation {
+        relation,
+        entity,
+        entity_id,
+        key,
+        title,
+        role,
+        status,
+    };
+
+    let relations = conn.exec_map(&stmt, &params, &map)?;
+    Ok(relations)
+}
@@ -0,0 +1,31 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::error::Result;
+
+/// Replace the calendar feed token of a user, `None` disables the personal feed
+pub fn feed_token_edit(conn: &mut PooledConn, user_id: u64, token_hash: Option<&[u8]>) -> Result<()> {
+    let stmt = conn.prep("UPDATE users SET feed_token_hash = :token_hash WHERE user_id = :user_id;")?;
+    let params = params! {
+        "user_id" => user_id,
+        "token_hash" => token_hash,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/// Enabled user of a calendar feed token
+pub fn feed_token_user(conn: &mut PooledConn, token_hash: &[u8]) -> Result<Option<u64>> {
+    let stmt = conn.prep(
+        "SELECT user_id
+        FROM users
+        WHERE feed_token_hash = :token_hash AND enabled = TRUE;",
+    )?;
+    let params = params! {
+        "token_hash" => token_hash,
+    };
+
+    let user_id = conn.exec_first::<u64, _, _>(&stmt, &params)?;
+    Ok(user_id)
+}
@@ -1,7 +1,15 @@
 mod bank_account;
+mod export;
+mod feed_token;
 mod license;
+mod password_reset;
+mod totp;
 mod user;
 
 pub use bank_account::*;
+pub use export::*;
+pub use feed_token::*;
 pub use license::*;
+pub use password_reset::*;
+pub use totp::*;
 pub use user::*;
@@ -0,0 +1,54 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::error::Result;
+
+pub fn password_reset_create(
+    conn: &mut PooledConn,
+    user_id: u64,
+    token_hash: &[u8],
+    expiry: &chrono::NaiveDateTime,
+) -> Result<()> {
+    let stmt = conn.prep(
+        "INSERT INTO password_resets (token_hash, user_id, created, expiry)
+        VALUES (:token_hash, :user_id, UTC_TIMESTAMP(), :expiry);",
+    )?;
+    let params = params! {
+        "token_hash" => token_hash,
+        "user_id" => user_id,
+        "expiry" => expiry,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+/// User of a password reset token that did not expire yet
+pub fn password_reset_user(conn: &mut PooledConn, token_hash: &[u8]) -> Result<Option<u64>> {
+    let stmt = conn.prep(
+        "SELECT user_id
+        FROM password_resets
+        WHERE token_hash = :token_hash AND expiry > UTC_TIMESTAMP();",
+    )?;
+    let params = params! {
+        "token_hash" => token_hash,
+    };
+
+    let user_id = conn.exec_first::<u64, _, _>(&stmt, &params)?;
+    Ok(user_id)
+}
+
+pub fn password_reset_delete(conn: &mut PooledConn, user_id: u64) -> Result<()> {
+    let stmt = conn.prep("DELETE FROM password_resets WHERE user_id = :user_id;")?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn password_reset_expired_delete(conn: &mut PooledConn) -> Result<()> {
+    conn.query_drop("DELETE FROM password_resets WHERE expiry < UTC_TIMESTAMP();")?;
+    Ok(())
+}
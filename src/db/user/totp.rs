@@ -0,0 +1,157 @@
+use mysql::prelude::Queryable;
+use mysql::{params, PooledConn};
+
+use crate::common::TotpInfo;
+use crate::error::{ErrorKind, Result};
+
+pub fn user_totp_info(conn: &mut PooledConn, user_id: u64) -> Result<Option<TotpInfo>> {
+    let stmt = conn.prep(
+        "SELECT uc.totp_secret, uc.totp_enabled, uc.totp_step
+        FROM user_credentials uc
+        JOIN users u ON u.credential = uc.credential_id
+        WHERE u.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    let mut row: mysql::Row = match conn.exec_first(&stmt, &params)? {
+        None => return Err(ErrorKind::UserPasswordMissing),
+        Some(row) => row,
+    };
+
+    let secret: Option<Vec<u8>> = row.take("totp_secret").unwrap();
+    Ok(secret.map(|secret| TotpInfo {
+        secret,
+        enabled: row.take("totp_enabled").unwrap(),
+        step: row.take("totp_step").unwrap(),
+    }))
+}
+
+/// Store a new secret that is unconfirmed until the first valid code
+pub fn user_totp_create(conn: &mut PooledConn, user_id: u64, secret: &[u8]) -> Result<()> {
+    let stmt = conn.prep(
+        "UPDATE user_credentials uc
+        JOIN users u ON u.credential = uc.credential_id
+        SET uc.totp_secret = :totp_secret, uc.totp_enabled = FALSE, uc.totp_step = NULL
+        WHERE u.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "totp_secret" => secret,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+
+    if conn.affected_rows() == 0 {
+        return Err(ErrorKind::UserPasswordMissing);
+    }
+
+    Ok(())
+}
+
+pub fn user_totp_enable(conn: &mut PooledConn, user_id: u64, step: u64) -> Result<()> {
+    let stmt = conn.prep(
+        "UPDATE user_credentials uc
+        JOIN users u ON u.credential = uc.credential_id
+        SET uc.totp_enabled = TRUE, uc.totp_step = :totp_step
+        WHERE u.user_id = :user_id AND uc.totp_secret IS NOT NULL;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "totp_step" => step,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn user_totp_step_edit(conn: &mut PooledConn, user_id: u64, step: u64) -> Result<()> {
+    let stmt = conn.prep(
+        "UPDATE user_credentials uc
+        JOIN users u ON u.credential = uc.credential_id
+        SET uc.totp_step = :totp_step
+        WHERE u.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "totp_step" => step,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
+pub fn user_totp_delete(conn: &mut PooledConn, user_id: u64) -> Result<()> {
+    let stmt = conn.prep(
+        "UPDATE user_credentials uc
+        JOIN users u ON u.credential = uc.credential_id
+        SET uc.totp_secret = NULL, uc.totp_enabled = FALSE, uc.totp_step = NULL
+        WHERE u.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    user_recovery_code_delete(conn, user_id)?;
+    Ok(())
+}
+
+/*
+ * RECOVERY CODES
+ */
+
+/// Replace all recovery codes of the user
+pub fn user_recovery_code_create(conn: &mut PooledConn, user_id: u64, code_hashes: &[Vec<u8>]) -> Result<()> {
+    user_recovery_code_delete(conn, user_id)?;
+
+    let stmt = conn.prep(
+        "INSERT INTO user_recovery_codes (credential_id, code_hash)
+        SELECT u.credential, :code_hash
+        FROM users u
+        WHERE u.user_id = :user_id AND u.credential IS NOT NULL;",
+    )?;
+
+    let params = code_hashes.iter().map(|code_hash| {
+        params! {
+            "user_id" => user_id,
+            "code_hash" => code_hash,
+        }
+    });
+
+    conn.exec_batch(&stmt, params)?;
+    Ok(())
+}
+
+/// Consume a recovery code, returns whether it was valid
+pub fn user_recovery_code_use(conn: &mut PooledConn, user_id: u64, code_hash: &[u8]) -> Result<bool> {
+    let stmt = conn.prep(
+        "DELETE rc
+        FROM user_recovery_codes rc
+        JOIN users u ON u.credential = rc.credential_id
+        WHERE u.user_id = :user_id AND rc.code_hash = :code_hash;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "code_hash" => code_hash,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(conn.affected_rows() > 0)
+}
+
+pub fn user_recovery_code_delete(conn: &mut PooledConn, user_id: u64) -> Result<()> {
+    let stmt = conn.prep(
+        "DELETE rc
+        FROM user_recovery_codes rc
+        JOIN users u ON u.credential = rc.credential_id
+        WHERE u.user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use mysql::prelude::Queryable;
 use mysql::{params, PooledConn};
 
-use crate::common::{BankAccount, Credential, License, User};
+use crate::common::{BankAccount, Credential, HashAlgorithm, License, User};
 use crate::error::{ErrorKind, Result};
 
 pub fn user_list(conn: &mut PooledConn, active: Option<bool>) -> Result<Vec<User>> {
@@ -125,6 +125,8 @@ pub fn user_detailed(conn: &mut PooledConn, user_id: u64) -> Result<User> {
                 password: None,
                 salt: row.take("credential_salt").map(|s: Vec<u8>| hex::encode(s)),
                 since: row.take("credential_since").unwrap(),
+                code: None,
+                token: None,
             }),
         active: row.take("active").unwrap(),
         firstname: row.take("firstname").unwrap(),
@@ -265,6 +267,18 @@ pub fn user_edit(conn: &mut PooledConn, user_id: u64, user: &mut User) -> Result
     Ok(())
 }
 
+/// Link the user to the subject of the identity provider, which takes precedence over the user key
+pub fn user_oidc_subject_edit(conn: &mut PooledConn, user_id: u64, subject: Option<&str>) -> Result<()> {
+    let stmt = conn.prep("UPDATE users SET oidc_subject = :subject WHERE user_id = :user_id;")?;
+    let params = params! {
+        "user_id" => user_id,
+        "subject" => subject,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
 pub fn user_delete(conn: &mut PooledConn, user_id: u64) -> Result<()> {
     let stmt = conn.prep("DELETE u FROM users u WHERE u.user_id = :user_id;")?;
     let params = params! {
@@ -275,6 +289,99 @@ pub fn user_delete(conn: &mut PooledConn, user_id: u64) -> Result<()> {
     Ok(())
 }
 
+/// Replace the personal data of the user by a pseudonym, while keeping attendance, terms and competences
+pub fn user_anonymise(conn: &mut PooledConn, user_id: u64, pseudonym: &str) -> Result<()> {
+    crate::db::transaction(conn, |conn| {
+        let stmt = conn.prep("SELECT user_key FROM users WHERE user_id = :user_id FOR UPDATE;")?;
+        let user_key: Option<String> = match conn.exec_first(&stmt, params! { "user_id" => user_id })? {
+            None => return Err(ErrorKind::Missing),
+            Some(user_key) => user_key,
+        };
+
+        // The rows are referenced by the user, thus are deleted through the user, which then is set to NULL
+        for (table, column, id) in [
+            ("bank_accounts", "bank_account", "id"),
+            ("licenses", "license_main", "id"),
+            ("licenses", "license_extra", "id"),
+            ("user_credentials", "credential", "credential_id"),
+        ] {
+            conn.exec_drop(
+                format!("DELETE t FROM {table} t JOIN users u ON u.{column} = t.{id} WHERE u.user_id = :user_id;"),
+                params! { "user_id" => user_id },
+            )?;
+        }
+
+        // Links which only serve the user or reveal more about the person
+        for table in [
+            "sessions",
+            "api_tokens",
+            "password_resets",
+            "team_members",
+            "event_bookmarks",
+            "course_bookmarks",
+            "organisation_affiliations",
+        ] {
+            conn.exec_drop(
+                format!("DELETE FROM {table} WHERE user_id = :user_id;"),
+                params! { "user_id" => user_id },
+            )?;
+        }
+
+        // Lockouts are tracked by the former user key
+        if let Some(user_key) = user_key {
+            let stmt = conn.prep("DELETE FROM login_failures WHERE category = 'USER' AND subject = :user_key;")?;
+            conn.exec_drop(&stmt, params! { "user_key" => user_key })?;
+        }
+
+        let stmt = conn.prep(
+            "UPDATE users SET
+                user_key = :pseudonym,
+                enabled = FALSE,
+                superuser = FALSE,
+                active = FALSE,
+                firstname = 'Anonymous',
+                lastname = :pseudonym,
+                nickname = NULL,
+                address = NULL,
+                email = NULL,
+                phone = NULL,
+                birth_date = NULL,
+                birth_location = NULL,
+                nationality = NULL,
+                gender = NULL,
+                height = NULL,
+                weight = NULL,
+                image_url = NULL,
+                note = NULL,
+                feed_token_hash = NULL,
+                oidc_subject = NULL,
+                last_login = NULL,
+                anonymised = UTC_TIMESTAMP()
+            WHERE user_id = :user_id;",
+        )?;
+        let params = params! {
+            "user_id" => user_id,
+            "pseudonym" => pseudonym,
+        };
+        conn.exec_drop(&stmt, &params)?;
+
+        // Former snapshots of the user would otherwise retain the personal data,
+        // this includes terms, possessions, competences and affiliations, which embed the user as its owner or judge
+        let stmt = conn.prep(
+            "UPDATE audit_log SET diff = NULL
+            WHERE (entity = 'user' AND entity_id = :user_id)
+            OR JSON_CONTAINS(JSON_EXTRACT(diff, '$**.user.id', '$**.judge.id'), :user_json);",
+        )?;
+        let params = params! {
+            "user_id" => user_id,
+            "user_json" => user_id.to_string(),
+        };
+        conn.exec_drop(&stmt, &params)?;
+
+        Ok(())
+    })
+}
+
 pub fn user_created_true(conn: &mut PooledConn, user_key: &str) -> Result<Option<u64>> {
     let stmt = conn.prep("SELECT user_id FROM users WHERE user_key = :user_key;")?;
     let params = params! { "user_key" => user_key };
@@ -283,6 +390,39 @@ pub fn user_created_true(conn: &mut PooledConn, user_key: &str) -> Result<Option
     Ok(user_id)
 }
 
+pub fn user_superuser_count(conn: &mut PooledConn) -> Result<u32> {
+    let count: Option<u32> = conn.query_first("SELECT COUNT(*) FROM users WHERE superuser = TRUE;")?;
+    Ok(count.unwrap_or(0))
+}
+
+pub fn user_superuser_true(conn: &mut PooledConn, user_id: u64) -> Result<bool> {
+    let stmt = conn.prep("SELECT superuser FROM users WHERE user_id = :user_id;")?;
+    let params = params! {
+        "user_id" => user_id,
+    };
+
+    match conn.exec_first::<bool, _, _>(&stmt, &params)? {
+        None => Err(ErrorKind::UserMissing),
+        Some(superuser) => Ok(superuser),
+    }
+}
+
+pub fn user_superuser_edit(conn: &mut PooledConn, user_id: u64, superuser: bool) -> Result<()> {
+    // Superusers are always enabled, otherwise they could not log in
+    let stmt = conn.prep(
+        "UPDATE users
+        SET superuser = :superuser, enabled = enabled OR :superuser
+        WHERE user_id = :user_id;",
+    )?;
+    let params = params! {
+        "user_id" => user_id,
+        "superuser" => superuser,
+    };
+
+    conn.exec_drop(&stmt, &params)?;
+    Ok(())
+}
+
 pub fn user_password_info(conn: &mut PooledConn, user_id: u64) -> Result<Option<Credential>> {
     let stmt = conn.prep(
         "SELECT uc.credential_id, uc.salt, uc.since
@@ -306,6 +446,8 @@ pub fn user_password_info(conn: &mut PooledConn, user_id: u64) -> Result<Option<
         password: None,
         salt: row.take("salt").map(|s: Vec<u8>| hex::encode(s)),
         since: row.take("since").unwrap(),
+        code: None,
+        token: None,
     };
 
     Ok(Some(creditial))
@@ -320,17 +462,19 @@ pub fn user_password_create(conn: &mut PooledConn, user_id: u64, hash_string: &s
     let salted_hash: Vec<u8> = crate::common::decode_hash256(hash_string)?;
 
     let pepper: Vec<u8> = crate::common::random_bytes(16);
-    let peppered_hash: Vec<u8> = crate::common::hash_sha256(&salted_hash, &pepper);
+    let algorithm = HashAlgorithm::Argon2id;
+    let peppered_hash: Vec<u8> = crate::common::hash_password(&salted_hash, &pepper, &algorithm)?;
 
     let stmt_uc = conn.prep(
-        "INSERT INTO user_credentials (salt, pepper, sp_hash, since)
-        VALUES(:salt, :pepper, :sp_hash, UTC_TIMESTAMP());",
+        "INSERT INTO user_credentials (salt, pepper, sp_hash, algorithm, since)
+        VALUES(:salt, :pepper, :sp_hash, :algorithm, UTC_TIMESTAMP());",
     )?;
 
     let params_uc = params! {
         "salt" => &salt,
         "pepper" => &pepper,
         "sp_hash" => &peppered_hash,
+        "algorithm" => &algorithm,
     };
 
     conn.exec_drop(&stmt_uc, &params_uc)?;
@@ -362,11 +506,12 @@ pub fn user_password_edit(conn: &mut PooledConn, user_id: u64, hash_string: &str
     let salted_hash: Vec<u8> = crate::common::decode_hash256(hash_string)?;
 
     let pepper: Vec<u8> = crate::common::random_bytes(16);
-    let peppered_hash: Vec<u8> = crate::common::hash_sha256(&salted_hash, &pepper);
+    let algorithm = HashAlgorithm::Argon2id;
+    let peppered_hash: Vec<u8> = crate::common::hash_password(&salted_hash, &pepper, &algorithm)?;
 
     let stmt = conn.prep(
         "UPDATE user_credentials
-        SET salt = :salt, pepper = :pepper, sp_hash = :sp_hash, since = UTC_TIMESTAMP()
+        SET salt = :salt, pepper = :pepper, sp_hash = :sp_hash, algorithm = :algorithm, since = UTC_TIMESTAMP()
         WHERE credential_id = :credential_id;",
     )?;
     let params = params! {
@@ -374,6 +519,7 @@ pub fn user_password_edit(conn: &mut PooledConn, user_id: u64, hash_string: &str
         "salt" => &salt,
         "pepper" => &pepper,
         "sp_hash" => &peppered_hash,
+        "algorithm" => &algorithm,
     };
 
     conn.exec_drop(&stmt, &params)?;
@@ -27,6 +27,12 @@ pub enum ErrorKind {
     RegexError,
     #[error("Time error")]
     TimeError,
+    #[error("Hashing error")]
+    HashError,
+    #[error("Mail transport is disabled")]
+    MailDisabled,
+    #[error("Mail delivery failed")]
+    MailFailed,
 
     #[error("Session token missing")]
     SessionTokenMissing,
@@ -34,6 +40,29 @@ pub enum ErrorKind {
     SessionTokenInvalid,
     #[error("Session token expired")]
     SessionTokenExpired,
+    #[error("API token name is invalid")]
+    ApiTokenNameInvalid,
+    #[error("API token scope exceeds the own rights")]
+    ApiTokenScopeInvalid,
+    #[error("Action is not available to API tokens")]
+    ApiTokenDenied,
+    #[error("Calendar feed token is invalid")]
+    FeedTokenInvalid,
+
+    #[error("Login is temporarily locked due to failed attempts")]
+    LoginLocked,
+    #[error("OpenID Connect login is disabled")]
+    OidcDisabled,
+    #[error("OpenID Connect provider request failed")]
+    OidcFailed,
+    #[error("OpenID Connect state is invalid or expired")]
+    OidcStateInvalid,
+    #[error("Second factor is required")]
+    TotpRequired,
+    #[error("Second factor is invalid")]
+    TotpInvalid,
+    #[error("Password reset token is invalid or expired")]
+    PasswordResetInvalid,
 
     #[error("User is missing")]
     UserMissing,
@@ -82,10 +111,20 @@ pub enum ErrorKind {
     EventWindowInvalid,
     #[error("Event time window conflicts with others")]
     EventWindowConflict,
+    #[error("Event time zone cannot be resolved")]
+    EventTimezoneInvalid,
+    #[error("Event calendar exceeds the upload limit")]
+    EventCalendarLimit,
     #[error("Event status has an invalid format")]
     EventStatusInvalid,
     #[error("Event status is conflicting")]
     EventStatusConflict,
+    #[error("Event series is missing")]
+    EventSeriesMissing,
+    #[error("Event series has an invalid recurrence")]
+    EventSeriesInvalid,
+    #[error("Event has reached the capacity of its location")]
+    EventCapacityReached,
 
     #[error("Course is missing")]
     CourseMissing,
@@ -143,6 +182,8 @@ pub enum ErrorKind {
     RightTeamMissing,
     #[error("User permissions are missing")]
     RightUserMissing,
+    #[error("Superuser permissions are missing")]
+    RightSuperuserMissing,
 }
 
 impl From<mysql::UrlError> for ErrorKind {
@@ -1,6 +1,5 @@
 #![allow(clippy::too_many_arguments)]
 
-use mysql::PooledConn;
 use rocket_cors::{AllowedHeaders, AllowedOrigins};
 use std::collections::HashSet;
 
@@ -19,51 +18,21 @@ fn index() -> &'static str {
     "Welcome to the CPT server."
 }
 
-fn promote_user_to_admin(conn: &mut PooledConn) -> anyhow::Result<()> {
-    // Check if an admin user is configured
-    let admin_key = match crate::config::ADMIN_USER() {
-        Some(key) => key,
-        None => return Ok(()),
-    };
+/// One-shot command line bootstrap of the first superuser, the password is read from stdin
+fn bootstrap(user_key: &str) -> anyhow::Result<()> {
+    let mut conn = utils::db::get_db_conn()?;
 
-    // If admin user is missing, create him
-    if crate::db::user::user_created_true(conn, admin_key)?.is_none() {
-        let mut user = crate::common::User::from_info(
-            0,
-            admin_key.clone(),
-            "Placeholder".to_string(),
-            "Placeholder".to_string(),
-            None,
-        );
-        crate::db::user::user_create(conn, &mut user)?;
-    }
+    eprint!("Password for {}: ", user_key);
+    let mut password = String::new();
+    std::io::stdin().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
 
-    // Elevate the user to admin
-    *crate::session::ADMINSESSION.lock().unwrap() = Some(admin_key.clone());
+    utils::bootstrap::bootstrap_superuser(&mut conn, user_key, password)?;
+    println!("Superuser {} is ready", user_key);
     Ok(())
 }
 
-#[rocket::launch]
-fn rocket() -> _ {
-    config::readConfig();
-
-    if utils::db::init_db_pool().is_err() {
-        panic!("Database pool initialization failed")
-    };
-
-    let mut conn = match utils::db::get_db_conn() {
-        Ok(conn) => conn,
-        Err(_) => panic!("Database connection failed"),
-    };
-
-    if db::migrate_scheme(&mut conn, &crate::config::DB_NAME()).is_err() {
-        panic!("Database update failed")
-    };
-
-    if promote_user_to_admin(&mut conn).is_err() {
-        panic!("Admin elevation failed")
-    };
-
+fn rocket() -> rocket::Rocket<rocket::Build> {
     let rocket_config = crate::config::ROCKET_CONFIG();
 
     // CORS
@@ -105,20 +74,34 @@ fn rocket() -> _ {
                 route::anon::club_image,
                 route::anon::club_banner,
                 route::anon::course_list,
+                route::anon::course_calendar,
+                route::anon::location_calendar,
+                route::anon::user_calendar,
                 route::anon::user_salt,
+                route::anon::password_reset_request,
+                route::anon::password_reset_confirm,
                 route::login::user_login,
                 route::login::event_login,
+                route::login::oidc_login,
+                route::login::oidc_callback,
                 route::login::course_login,
                 route::login::location_login,
+                route::login::session_refresh,
+                route::login::logout,
+                route::admin::audit::audit_list,
+                route::admin::audit::retention_report,
                 route::admin::user::user_list,
                 route::admin::user::user_detailed,
+                route::admin::user::user_data_export,
                 route::admin::user::user_create,
                 route::admin::user::user_edit,
                 route::admin::user::user_delete,
+                route::admin::user::user_anonymise,
                 route::admin::user::user_password_info,
                 route::admin::user::user_password_create,
                 route::admin::user::user_password_edit,
                 route::admin::user::user_password_delete,
+                route::admin::user::user_oidc_subject_edit,
                 route::admin::user::user_bank_account_create,
                 route::admin::user::user_bank_account_edit,
                 route::admin::user::user_bank_account_delete,
@@ -128,11 +111,27 @@ fn rocket() -> _ {
                 route::admin::user::user_license_extra_edit,
                 route::admin::user::user_license_main_delete,
                 route::admin::user::user_license_extra_delete,
+                route::admin::user::user_session_list,
+                route::admin::user::user_session_revoke,
+                route::admin::user::user_totp_delete,
+                route::admin::user::login_lockout_list,
+                route::admin::user::login_lockout_clear,
                 route::regular::user::user_info,
                 route::regular::user::user_right,
+                route::regular::user::user_data_export,
+                route::regular::user::user_calendar_token_rotate,
+                route::regular::user::user_calendar_token_revoke,
+                route::regular::user::session_list,
                 route::regular::user::user_password_info,
                 route::regular::user::user_password_set,
                 route::regular::user::user_list,
+                route::regular::totp::totp_enrol,
+                route::regular::totp::totp_confirm,
+                route::regular::totp::totp_recovery_create,
+                route::regular::totp::totp_delete,
+                route::regular::api_token::api_token_list,
+                route::regular::api_token::api_token_create,
+                route::regular::api_token::api_token_revoke,
                 route::admin::club::club_list,
                 route::admin::club::club_info,
                 route::admin::club::club_create,
@@ -169,10 +168,12 @@ fn rocket() -> _ {
                 route::moderator::course::course_moderator_remove,
                 route::admin::event::event_list,
                 route::admin::event::event_info,
-                route::admin::event::event_credential,
                 route::admin::event::event_create,
+                route::admin::event::event_import,
+                route::admin::event::event_conflicts,
                 route::admin::event::event_edit,
                 route::admin::event::event_password_edit,
+                route::admin::event::event_password_rotate,
                 route::admin::event::event_course_info,
                 route::admin::event::event_course_edit,
                 route::admin::event::event_delete,
@@ -185,6 +186,11 @@ fn rocket() -> _ {
                 route::admin::event::owner::owner_list,
                 route::admin::event::owner::owner_add,
                 route::admin::event::owner::owner_remove,
+                route::admin::event::series::series_list,
+                route::admin::event::series::series_info,
+                route::admin::event::series::series_create,
+                route::admin::event::series::series_edit,
+                route::admin::event::series::series_delete,
                 route::admin::event::attendance::registration_list,
                 route::admin::event::attendance::filter_list,
                 route::admin::event::attendance::filter_edit,
@@ -198,6 +204,10 @@ fn rocket() -> _ {
                 route::moderator::event::event_edit,
                 route::moderator::event::event_edit_password,
                 route::moderator::event::event_delete,
+                route::moderator::event::series::series_list,
+                route::moderator::event::series::series_create,
+                route::moderator::event::series::series_edit,
+                route::moderator::event::series::series_delete,
                 route::regular::event::event_list,
                 route::regular::event::event_create,
                 route::regular::event::event_owner_true,
@@ -213,6 +223,7 @@ fn rocket() -> _ {
                 route::owner::event::event_info,
                 route::owner::event::event_edit,
                 route::owner::event::event_password_edit,
+                route::owner::event::event_password_rotate,
                 route::owner::event::event_delete,
                 route::owner::event::event_submit,
                 route::owner::event::event_withdraw,
@@ -273,6 +284,7 @@ fn rocket() -> _ {
                 route::admin::team::team_info,
                 route::admin::team::team_create,
                 route::admin::team::team_edit,
+                route::admin::team::permission_list,
                 route::admin::team::team_right_edit,
                 route::admin::team::team_delete,
                 route::admin::team::team_member_list,
@@ -300,4 +312,39 @@ fn rocket() -> _ {
             ],
         )
         .attach(cors)
+        .attach(rocket::fairing::AdHoc::on_liftoff("Retention", |_| {
+            Box::pin(async { utils::retention::retention_task_spawn() })
+        }))
+}
+
+#[rocket::main]
+async fn main() -> anyhow::Result<()> {
+    config::readConfig();
+    utils::logger::init_logger();
+
+    if utils::db::init_db_pool().is_err() {
+        panic!("Database pool initialization failed")
+    };
+
+    let mut conn = match utils::db::get_db_conn() {
+        Ok(conn) => conn,
+        Err(_) => panic!("Database connection failed"),
+    };
+
+    if db::migrate_scheme(&mut conn, &crate::config::DB_NAME()).is_err() {
+        panic!("Database update failed")
+    };
+
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        None => (),
+        Some("bootstrap") => match args.get(2) {
+            Some(user_key) => return bootstrap(user_key),
+            None => anyhow::bail!("Usage: cptserver bootstrap <user_key>"),
+        },
+        Some(command) => anyhow::bail!("Unknown command: {}", command),
+    }
+
+    rocket().launch().await?;
+    Ok(())
 }
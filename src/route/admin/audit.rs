@@ -0,0 +1,53 @@
+use rocket::serde::json::Json;
+
+use crate::common::{AuditEntry, Permission, RetentionReport, WebDateTime, USER_SENSITIVE_PERMISSIONS};
+use crate::error::{ErrorKind, Result};
+use crate::session::UserSession;
+
+/* ROUTES */
+
+#[rocket::get("/admin/audit_list?<user_id>&<event_id>&<entity>&<entity_id>&<begin>&<end>")]
+pub fn audit_list(
+    session: UserSession,
+    user_id: Option<u64>,
+    event_id: Option<u64>,
+    entity: Option<String>,
+    entity_id: Option<u64>,
+    begin: Option<WebDateTime>,
+    end: Option<WebDateTime>,
+) -> Result<Json<Vec<AuditEntry>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session.right.has(Permission::UserRead) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+
+    let mut entries = crate::db::audit::audit_list(
+        conn,
+        user_id,
+        event_id,
+        entity,
+        entity_id,
+        begin.map(|dt| dt.to_naive()),
+        end.map(|dt| dt.to_naive()),
+    )?;
+
+    // Snapshots of users carry their personal data, which is hidden unless all of it may be seen
+    if !USER_SENSITIVE_PERMISSIONS.iter().all(|p| session.right.has(*p)) {
+        for entry in entries.iter_mut().filter(|entry| entry.entity == "user") {
+            entry.diff = None;
+        }
+    }
+
+    Ok(Json(entries))
+}
+
+#[rocket::get("/admin/retention_report")]
+pub fn retention_report(session: UserSession) -> Result<Json<RetentionReport>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session.right.has(Permission::UserRead) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+
+    let report = crate::utils::retention::retention_report(conn)?;
+    Ok(Json(report))
+}
@@ -2,25 +2,24 @@ pub mod term;
 
 use rocket::serde::json::Json;
 
-use crate::common::{Affiliation, Club, Event, Term, User, WebDate, WebDateTime};
+use crate::common::{Affiliation, Club, Event, Permission, Term, User, WebDate, WebDateTime};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/club_list")]
 pub fn club_list(session: UserSession) -> Result<Json<Vec<Club>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_read {
-        return Err(ErrorKind::RightClubMissing);
-    };
-
-    let clubs = crate::db::club::club_list(conn)?;
+    let clubs: Vec<Club> = crate::db::club::club_list(conn)?
+        .into_iter()
+        .filter(|club| session.club_right(club.id as u32).has(Permission::ClubRead))
+        .collect();
     Ok(Json(clubs))
 }
 
 #[rocket::get("/admin/club_info?<club_id>")]
 pub fn club_info(session: UserSession, club_id: u32) -> Result<Json<Club>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_read {
+    if !session.club_right(club_id).has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
@@ -32,34 +31,47 @@ pub fn club_info(session: UserSession, club_id: u32) -> Result<Json<Club>> {
 #[rocket::post("/admin/club_create", format = "application/json", data = "<club>")]
 pub fn club_create(session: UserSession, club: Json<Club>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_write {
+    if !session.right.has(Permission::ClubWrite) {
         return Err(ErrorKind::RightClubMissing);
     };
 
-    let id = crate::db::club::club_create(conn, &club)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::club::club_create(conn, &club)?;
+        let after = crate::db::club::club_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "club", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post("/admin/club_edit?<club_id>", format = "application/json", data = "<club>")]
 pub fn club_edit(session: UserSession, club_id: u32, club: Json<Club>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_write {
+    if !session.club_right(club_id).has(Permission::ClubWrite) {
         return Err(ErrorKind::RightClubMissing);
     };
 
-    crate::db::club::club_edit(conn, club_id, &club)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::club::club_info(conn, club_id)?;
+        crate::db::club::club_edit(conn, club_id, &club)?;
+        let after = crate::db::club::club_info(conn, club_id)?;
+        crate::utils::audit::audit(conn, &session, "club", club_id as u64, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/club_delete?<club_id>")]
 pub fn club_delete(session: UserSession, club_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_write {
+    if !session.right.has(Permission::ClubWrite) {
         return Err(ErrorKind::RightClubMissing);
     };
 
-    crate::db::club::club_delete(conn, club_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::club::club_info(conn, club_id)?;
+        crate::db::club::club_delete(conn, club_id)?;
+        crate::utils::audit::audit(conn, &session, "club", club_id as u64, Some(&before), None)?;
+        Ok(())
+    })
 }
 
 /* STATISTICS */
@@ -67,7 +79,7 @@ pub fn club_delete(session: UserSession, club_id: u32) -> Result<()> {
 #[rocket::get("/admin/club_statistic_terms?<club_id>&<point_in_time>")]
 pub fn statistic_terms(session: UserSession, club_id: u32, point_in_time: WebDate) -> Result<Json<Vec<Term>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_read {
+    if !session.club_right(club_id).has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
@@ -78,7 +90,7 @@ pub fn statistic_terms(session: UserSession, club_id: u32, point_in_time: WebDat
 #[rocket::get("/admin/club_statistic_members?<club_id>&<point_in_time>")]
 pub fn statistic_members(session: UserSession, club_id: u32, point_in_time: WebDate) -> Result<Json<Vec<(User, u32)>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_read {
+    if !session.club_right(club_id).has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
@@ -94,7 +106,7 @@ pub fn statistic_team(
     team_id: u32,
 ) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_read {
+    if !session.club_right(club_id).has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
@@ -110,7 +122,7 @@ pub fn statistic_organisation(
     point_in_time: WebDate,
 ) -> Result<Json<Vec<Affiliation>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_read {
+    if !session.club_right(club_id).has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
@@ -129,7 +141,7 @@ pub fn statistic_attendance(
     time_window_end: WebDateTime,
 ) -> Result<Json<Vec<Event>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_club_read {
+    if !session.club_right(club_id).has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
@@ -1,12 +1,16 @@
 use rocket::serde::json::Json;
 
-use crate::common::Term;
+use crate::common::{Permission, Term};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/term_list?<club_id>&<user_id>")]
 pub fn term_list(session: UserSession, club_id: Option<u32>, user_id: Option<u32>) -> Result<Json<Vec<Term>>> {
-    if !session.right.right_club_read {
+    let right = match club_id {
+        None => session.right.clone(),
+        Some(club_id) => session.club_right(club_id),
+    };
+    if !right.has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
@@ -17,44 +21,64 @@ pub fn term_list(session: UserSession, club_id: Option<u32>, user_id: Option<u32
 
 #[rocket::get("/admin/term_info?<term_id>")]
 pub fn term_info(session: UserSession, term_id: u32) -> Result<Json<Term>> {
-    if !session.right.right_club_read {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let term = crate::db::club::term_info(conn, term_id)?;
+
+    if !session.club_right(term.club.id as u32).has(Permission::ClubRead) {
         return Err(ErrorKind::RightClubMissing);
     };
 
-    let conn = &mut crate::utils::db::get_db_conn()?;
-    let term = crate::db::club::term_info(conn, term_id)?;
     Ok(Json(term))
 }
 
 #[rocket::post("/admin/term_create", format = "application/json", data = "<term>")]
 pub fn term_create(session: UserSession, term: Json<Term>) -> Result<String> {
-    if !session.right.right_club_write {
+    if !session.club_right(term.club.id as u32).has(Permission::ClubWrite) {
         return Err(ErrorKind::RightClubMissing);
     };
 
     let conn = &mut crate::utils::db::get_db_conn()?;
-    let id = crate::db::club::term_create(conn, &term)?;
-    Ok(id.to_string())
+
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::club::term_create(conn, &term)?;
+        let after = crate::db::club::term_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "term", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post("/admin/term_edit?<term_id>", format = "application/json", data = "<term>")]
 pub fn term_edit(session: UserSession, term_id: i64, term: Json<Term>) -> Result<()> {
-    if !session.right.right_club_write {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let before = crate::db::club::term_info(conn, term_id as u32)?;
+
+    // Moving a term requires the rights in both clubs
+    if !session.club_right(before.club.id as u32).has(Permission::ClubWrite)
+        || !session.club_right(term.club.id as u32).has(Permission::ClubWrite)
+    {
         return Err(ErrorKind::RightClubMissing);
     };
 
-    let conn = &mut crate::utils::db::get_db_conn()?;
-    crate::db::club::term_edit(conn, term_id, &term)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::club::term_edit(conn, term_id, &term)?;
+        let after = crate::db::club::term_info(conn, term_id as u32)?;
+        crate::utils::audit::audit(conn, &session, "term", term_id as u64, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/term_delete?<term_id>")]
 pub fn term_delete(session: UserSession, term_id: i64) -> Result<()> {
-    if !session.right.right_club_write {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let before = crate::db::club::term_info(conn, term_id as u32)?;
+
+    if !session.club_right(before.club.id as u32).has(Permission::ClubWrite) {
         return Err(ErrorKind::RightClubMissing);
     };
 
-    let conn = &mut crate::utils::db::get_db_conn()?;
-    crate::db::club::term_delete(conn, term_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::club::term_delete(conn, term_id)?;
+        crate::utils::audit::audit(conn, &session, "term", term_id as u64, Some(&before), None)?;
+        Ok(())
+    })
 }
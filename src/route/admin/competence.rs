@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::Competence;
+use crate::common::{Competence, Permission};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -13,7 +13,7 @@ pub fn competence_list(
     max: Option<i16>,
 ) -> Result<Json<Vec<Competence>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_read {
+    if !session.right.has(Permission::CompetenceRead) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
@@ -25,7 +25,7 @@ pub fn competence_list(
 #[rocket::get("/admin/competence_info?<competence_id>")]
 pub fn competence_info(session: UserSession, competence_id: Option<u64>) -> Result<Json<Competence>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_read {
+    if !session.right.has(Permission::CompetenceRead) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
@@ -40,12 +40,16 @@ pub fn competence_info(session: UserSession, competence_id: Option<u64>) -> Resu
 #[rocket::post("/admin/competence_create", format = "application/json", data = "<competence>")]
 pub fn competence_create(session: UserSession, competence: Json<Competence>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_write {
+    if !session.right.has(Permission::CompetenceWrite) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
-    let id = crate::db::competence::competence_create(conn, &competence)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::competence::competence_create(conn, &competence)?;
+        let after = crate::db::competence::competence_info(conn, Some(id as u64))?;
+        crate::utils::audit::audit(conn, &session, "competence", id as u64, None, after.as_ref())?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post(
@@ -55,21 +59,37 @@ pub fn competence_create(session: UserSession, competence: Json<Competence>) ->
 )]
 pub fn competence_edit(session: UserSession, competence_id: u64, competence: Json<Competence>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_write {
+    if !session.right.has(Permission::CompetenceWrite) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
-    crate::db::competence::competence_edit(conn, competence_id, &competence)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::competence::competence_info(conn, Some(competence_id))?;
+        crate::db::competence::competence_edit(conn, competence_id, &competence)?;
+        let after = crate::db::competence::competence_info(conn, Some(competence_id))?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "competence",
+            competence_id,
+            before.as_ref(),
+            after.as_ref(),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/competence_delete?<competence_id>")]
 pub fn competence_delete(session: UserSession, competence_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_write {
+    if !session.right.has(Permission::CompetenceWrite) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
-    crate::db::competence::competence_delete(conn, competence_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::competence::competence_info(conn, Some(competence_id))?;
+        crate::db::competence::competence_delete(conn, competence_id)?;
+        crate::utils::audit::audit(conn, &session, "competence", competence_id, before.as_ref(), None)?;
+        Ok(())
+    })
 }
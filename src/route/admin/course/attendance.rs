@@ -1,13 +1,16 @@
 use rocket::serde::json::Json;
 
-use crate::common::Team;
+use crate::common::{Permission, Team};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/course_attendance_sieve_list?<course_id>&<role>")]
 pub fn sieve_list(session: UserSession, course_id: u32, role: String) -> Result<Json<Vec<(Team, bool)>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -18,21 +21,47 @@ pub fn sieve_list(session: UserSession, course_id: u32, role: String) -> Result<
 #[rocket::head("/admin/course_attendance_sieve_edit?<course_id>&<team_id>&<role>&<access>")]
 pub fn sieve_edit(session: UserSession, course_id: u32, team_id: u64, role: String, access: bool) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::attendance::sieve_edit(conn, course_id, team_id, role, access)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::attendance::sieve_edit(conn, course_id, team_id, role.clone(), access)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_sieve",
+            course_id as u64,
+            None,
+            Some(&serde_json::json!({ "team_id": team_id, "role": role, "access": access })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/course_attendance_sieve_remove?<course_id>&<team_id>&<role>")]
 pub fn sieve_remove(session: UserSession, course_id: u32, team_id: u64, role: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::attendance::sieve_remove(conn, course_id, team_id, role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::attendance::sieve_remove(conn, course_id, team_id, role.clone())?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_sieve",
+            course_id as u64,
+            Some(&serde_json::json!({ "team_id": team_id, "role": role })),
+            None,
+        )?;
+        Ok(())
+    })
 }
@@ -3,7 +3,7 @@ pub mod moderator;
 
 use rocket::serde::json::Json;
 
-use crate::common::{Acceptance, Course, Event, Requirement, User, WebBool};
+use crate::common::{Acceptance, Course, Event, Permission, Requirement, User, WebBool};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -15,7 +15,7 @@ pub fn course_list(
     public: Option<WebBool>,
 ) -> Result<Json<Vec<Course>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session.right.has(Permission::CourseRead) {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -27,40 +27,62 @@ pub fn course_list(
 #[rocket::post("/admin/course_create", format = "application/json", data = "<course>")]
 pub fn course_create(session: UserSession, course: Json<Course>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session.right.has(Permission::CourseWrite) {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    let id = crate::db::course::course_create(conn, &course)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::course::course_create(conn, &course)?;
+        let after = crate::db::course::course_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "course", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post("/admin/course_edit?<course_id>", format = "application/json", data = "<course>")]
 pub fn course_edit(session: UserSession, course_id: u32, course: Json<Course>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::course_edit(conn, course_id, &course)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::course::course_info(conn, course_id)?;
+        crate::db::course::course_edit(conn, course_id, &course)?;
+        let after = crate::db::course::course_info(conn, course_id)?;
+        crate::utils::audit::audit(conn, &session, "course", course_id as u64, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/course_delete?<course_id>")]
 pub fn course_delete(session: UserSession, course_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::course_delete(conn, course_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::course::course_info(conn, course_id)?;
+        crate::db::course::course_delete(conn, course_id)?;
+        crate::utils::audit::audit(conn, &session, "course", course_id as u64, Some(&before), None)?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/course_event_list?<course_id>")]
 pub fn course_event_list(session: UserSession, course_id: u32) -> Result<Json<Vec<Event>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -74,6 +96,7 @@ pub fn course_event_list(session: UserSession, course_id: u32) -> Result<Json<Ve
         Some(true),
         Some(course_id),
         None,
+        None,
     )?;
     Ok(Json(events))
 }
@@ -81,7 +104,10 @@ pub fn course_event_list(session: UserSession, course_id: u32) -> Result<Json<Ve
 #[rocket::get("/admin/course_requirement_list?<course_id>")]
 pub fn course_requirement_list(session: UserSession, course_id: u32) -> Result<Json<Vec<Requirement>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -92,29 +118,49 @@ pub fn course_requirement_list(session: UserSession, course_id: u32) -> Result<J
 #[rocket::head("/admin/course_requirement_add?<course_id>&<skill_id>&<rank>")]
 pub fn course_requirement_add(session: UserSession, course_id: u32, skill_id: u32, rank: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::course_requirement_add(conn, course_id, skill_id, rank)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::course_requirement_add(conn, course_id, skill_id, rank)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_requirement",
+            course_id as u64,
+            None,
+            Some(&serde_json::json!({ "skill_id": skill_id, "rank": rank })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/course_requirement_remove?<requirement_id>")]
 pub fn course_requirement_remove(session: UserSession, requirement_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    let course_id = crate::db::course::course_requirement_course(conn, requirement_id)?;
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::course_requirement_remove(conn, requirement_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::course_requirement_remove(conn, requirement_id)?;
+        crate::utils::audit::audit_action(conn, &session, "course_requirement", requirement_id)?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/course_club_info?<course_id>")]
 pub fn course_club_info(session: UserSession, course_id: u64) -> Result<Json<Option<u32>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session.course_right(conn, course_id)?.has(Permission::CourseRead) {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -125,21 +171,44 @@ pub fn course_club_info(session: UserSession, course_id: u64) -> Result<Json<Opt
 #[rocket::head("/admin/course_club_edit?<course_id>&<club_id>")]
 pub fn course_club_edit(session: UserSession, course_id: u64, club_id: Option<u32>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session.course_right(conn, course_id)?.has(Permission::CourseWrite) {
+        return Err(ErrorKind::RightCourseMissing);
+    };
+
+    // Moving a course requires the rights in the new club as well
+    let right = match club_id {
+        None => session.right.clone(),
+        Some(club_id) => session.club_right(club_id),
+    };
+    if !right.has(Permission::CourseWrite) {
         return Err(ErrorKind::RightCourseMissing);
     };
-    if !session.right.right_club_write {
+    if !right.has(Permission::ClubWrite) {
         return Err(ErrorKind::RightClubMissing);
     };
 
-    crate::db::course::course_club_edit(conn, course_id, club_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::course::course_club_info(conn, course_id)?;
+        crate::db::course::course_club_edit(conn, course_id, club_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_club",
+            course_id,
+            Some(&serde_json::json!({ "club_id": before })),
+            Some(&serde_json::json!({ "club_id": club_id })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/course_statistic_class?<course_id>")]
 pub fn course_statistic_class(session: UserSession, course_id: u32) -> Result<Json<Vec<(Event, u64, u64, u64, u64)>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -154,7 +223,10 @@ pub fn course_statistic_attendance(
     role: String,
 ) -> Result<Json<Vec<(User, u64)>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -170,7 +242,10 @@ pub fn course_statistic_attendance1(
     role: String,
 ) -> Result<Json<Vec<Event>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
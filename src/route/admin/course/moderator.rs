@@ -1,13 +1,16 @@
 use rocket::serde::json::Json;
 
-use crate::common::User;
+use crate::common::{Permission, User};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/course_moderator_list?<course_id>")]
 pub fn course_moderator_list(session: UserSession, course_id: u32) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_read {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
@@ -18,21 +21,47 @@ pub fn course_moderator_list(session: UserSession, course_id: u32) -> Result<Jso
 #[rocket::head("/admin/course_moderator_add?<course_id>&<user_id>")]
 pub fn course_moderator_add(session: UserSession, course_id: u32, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::moderator::course_moderator_add(conn, course_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::moderator::course_moderator_add(conn, course_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_moderator",
+            course_id as u64,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/course_moderator_remove?<course_id>&<user_id>")]
 pub fn course_moderator_remove(session: UserSession, course_id: u32, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_course_write {
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseWrite)
+    {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::course::moderator::course_moderator_remove(conn, course_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::moderator::course_moderator_remove(conn, course_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_moderator",
+            course_id as u64,
+            Some(&serde_json::json!({ "user_id": user_id })),
+            None,
+        )?;
+        Ok(())
+    })
 }
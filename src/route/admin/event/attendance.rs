@@ -1,13 +1,13 @@
 use rocket::serde::json::Json;
 
-use crate::common::User;
+use crate::common::{Permission, User};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/event_attendance_registration_list?<event_id>&<role>")]
 pub fn registration_list(session: UserSession, event_id: u64, role: String) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -18,7 +18,7 @@ pub fn registration_list(session: UserSession, event_id: u64, role: String) -> R
 #[rocket::get("/admin/event_attendance_filter_list?<event_id>&<role>")]
 pub fn filter_list(session: UserSession, event_id: u64, role: String) -> Result<Json<Vec<(User, bool)>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -29,29 +29,49 @@ pub fn filter_list(session: UserSession, event_id: u64, role: String) -> Result<
 #[rocket::head("/admin/event_attendance_filter_edit?<event_id>&<user_id>&<role>&<access>")]
 pub fn filter_edit(session: UserSession, event_id: u64, user_id: u64, role: String, access: bool) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::attendance::event_attendance_filter_edit(conn, event_id, user_id, role, access)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_filter_edit(conn, event_id, user_id, role.clone(), access)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_filter",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role, "access": access })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/event_attendance_filter_remove?<event_id>&<user_id>&<role>")]
 pub fn filter_remove(session: UserSession, event_id: u64, user_id: u64, role: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::attendance::event_attendance_filter_remove(conn, event_id, user_id, role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_filter_remove(conn, event_id, user_id, role.clone())?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_filter",
+            event_id,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+            None,
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/event_attendance_presence_pool?<event_id>&<role>")]
 pub fn presence_pool(session: UserSession, event_id: u64, role: String) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -62,7 +82,7 @@ pub fn presence_pool(session: UserSession, event_id: u64, role: String) -> Resul
 #[rocket::get("/admin/event_attendance_presence_list?<event_id>&<role>")]
 pub fn presence_list(session: UserSession, event_id: u64, role: String) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -73,27 +93,47 @@ pub fn presence_list(session: UserSession, event_id: u64, role: String) -> Resul
 #[rocket::head("/admin/event_attendance_presence_add?<event_id>&<user_id>&<role>")]
 pub fn presence_add(session: UserSession, event_id: u64, user_id: u64, role: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    let pool = crate::db::event::attendance::event_attendance_presence_pool(conn, event_id, &role, true)?;
-
-    if !pool.iter().any(|user| user.id == user_id) {
-        return Err(ErrorKind::EventPresenceForbidden);
-    }
-
-    crate::db::event::attendance::event_attendance_presence_add(conn, event_id, user_id, &role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let pool = crate::db::event::attendance::event_attendance_presence_pool(conn, event_id, &role, true)?;
+
+        if !pool.iter().any(|user| user.id == user_id) {
+            return Err(ErrorKind::EventPresenceForbidden);
+        }
+
+        crate::db::event::attendance::event_attendance_presence_add(conn, event_id, user_id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/event_attendance_presence_remove?<event_id>&<user_id>&<role>")]
 pub fn presence_remove(session: UserSession, event_id: u64, user_id: u64, role: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::attendance::event_attendance_presence_remove(conn, event_id, user_id, &role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_presence_remove(conn, event_id, user_id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            event_id,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+            None,
+        )?;
+        Ok(())
+    })
 }
@@ -1,9 +1,13 @@
 pub mod attendance;
 pub mod owner;
+pub mod series;
 
+use rocket::data::Data;
 use rocket::serde::json::Json;
 
-use crate::common::{Acceptance, Affiliation, Course, Credential, Event, Occurrence, User, WebBool, WebDateTime};
+use crate::common::{
+    Acceptance, Affiliation, Course, Credential, Event, EventImport, Occurrence, Permission, User, WebBool, WebDateTime,
+};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -22,7 +26,7 @@ pub fn event_list(
     owner_id: Option<u64>,
 ) -> Result<Json<Vec<Event>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -40,6 +44,7 @@ pub fn event_list(
         course_true.map(|b| b.to_bool()),
         course_id,
         owner_id,
+        None,
     )?;
     Ok(Json(events))
 }
@@ -47,77 +52,158 @@ pub fn event_list(
 #[rocket::get("/admin/event_info?<event_id>")]
 pub fn event_info(session: UserSession, event_id: u64) -> Result<Json<Event>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
     Ok(Json(crate::db::event::event_info(conn, event_id)?))
 }
 
-#[rocket::get("/admin/event_credential?<event_id>")]
-pub fn event_credential(session: UserSession, event_id: u64) -> Result<Json<Credential>> {
+#[rocket::post("/admin/event_password_rotate?<event_id>")]
+pub fn event_password_rotate(session: UserSession, event_id: u64) -> Result<Json<Credential>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    let (event_key, event_pwd) = crate::db::login::event_credential(conn, event_id)?;
-
-    Ok(Json(Credential {
-        id: None,
-        login: Some(event_key),
-        password: Some(event_pwd),
-        salt: None,
-        since: None,
-    }))
+    crate::db::transaction(conn, |conn| {
+        let credential = crate::utils::event::rotate_event_password(conn, event_id)?;
+        crate::utils::audit::audit_action(conn, &session, "event_password", event_id)?;
+        Ok(Json(credential))
+    })
 }
 
 #[rocket::post("/admin/event_create?<course_id>", format = "application/json", data = "<event>")]
 pub fn event_create(session: UserSession, course_id: Option<u32>, mut event: Json<Event>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    if course_id.is_some() && !session.right.right_course_write {
+    if course_id.is_some() && !session.right.has(Permission::CourseWrite) {
         return Err(ErrorKind::RightCourseMissing);
     };
 
     crate::utils::event::validate_event_dates(&mut event)?;
 
-    let id = crate::db::event::event_create(conn, &event, &Acceptance::Draft, course_id)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::event::event_create(conn, &event, &Acceptance::Draft, course_id)?;
+        let after = crate::db::event::event_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "event", id, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
-#[rocket::post("/admin/event_edit?<event_id>", format = "application/json", data = "<event>")]
-pub fn event_edit(session: UserSession, event_id: u64, mut event: Json<Event>) -> Result<()> {
+#[rocket::get("/admin/event_conflicts?<event_id>")]
+pub fn event_conflicts(session: UserSession, event_id: u64) -> Result<Json<Vec<Event>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
+    let event = crate::db::event::event_info(conn, event_id)?;
+    let conflicts = crate::db::event::event_conflict_list(conn, &event)?;
+    Ok(Json(conflicts))
+}
+
+#[rocket::post(
+    "/admin/event_import?<dry_run>&<utc_offset>",
+    format = "text/calendar",
+    data = "<calendar>"
+)]
+pub async fn event_import(
+    session: UserSession,
+    dry_run: Option<WebBool>,
+    utc_offset: Option<i16>,
+    calendar: Data<'_>,
+) -> Result<Json<Vec<EventImport>>> {
+    if !session.right.has(Permission::EventWrite) {
+        return Err(ErrorKind::RightEventMissing);
+    };
+
+    let calendar = calendar
+        .open(crate::config::EVENT_IMPORT_LIMIT())
+        .into_string()
+        .await
+        .map_err(|_| ErrorKind::Parsing)?;
+    if !calendar.is_complete() {
+        return Err(ErrorKind::EventCalendarLimit);
+    }
+
+    let utc_offset = chrono::Duration::minutes(utc_offset.unwrap_or(0) as i64);
+    let dry_run = dry_run.map(|b| b.to_bool()).unwrap_or(false);
+
+    // Only reading the upload is async, the database calls block and must not run on the executor
+    let imports = rocket::tokio::task::spawn_blocking(move || -> Result<Vec<EventImport>> {
+        let conn = &mut crate::utils::db::get_db_conn()?;
+
+        crate::db::transaction(conn, |conn| {
+            let imports = crate::utils::event::event_import(conn, &calendar, utc_offset, dry_run)?;
+
+            for event_id in imports.iter().filter_map(|import| import.event_id) {
+                let after = crate::db::event::event_info(conn, event_id)?;
+                crate::utils::audit::audit(conn, &session, "event", event_id, None, Some(&after))?;
+            }
+            Ok(imports)
+        })
+    })
+    .await
+    .map_err(|_| ErrorKind::Default)??;
+
+    Ok(Json(imports))
+}
+
+#[rocket::post(
+    "/admin/event_edit?<event_id>&<force>",
+    format = "application/json",
+    data = "<event>"
+)]
+pub fn event_edit(session: UserSession, event_id: u64, force: Option<WebBool>, mut event: Json<Event>) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session.right.has(Permission::EventWrite) {
+        return Err(ErrorKind::RightEventMissing);
+    };
+
+    let force = crate::common::event_force(&session.right, force.map(|b| b.to_bool()).unwrap_or(false))?;
+
     crate::utils::event::validate_event_dates(&mut event)?;
 
-    crate::db::event::event_edit(conn, event_id, &event)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+
+        // The acceptance is not part of the edit
+        event.id = event_id;
+        event.acceptance = before.acceptance.clone();
+        if !force && event.claims_location() && !crate::db::event::event_free_true(conn, &event)? {
+            return Err(ErrorKind::EventWindowConflict);
+        }
+
+        crate::db::event::event_edit(conn, event_id, &event)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::post("/admin/event_password_edit?<event_id>", format = "text/plain", data = "<password>")]
 pub fn event_password_edit(session: UserSession, event_id: u64, password: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    let password = crate::utils::event::validate_clear_password(password)?;
-    crate::db::event::event_password_edit(conn, event_id, password)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let password = crate::utils::event::validate_clear_password(password)?;
+        crate::db::event::event_password_edit(conn, event_id, &password)?;
+        crate::utils::audit::audit_action(conn, &session, "event_password", event_id)?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/event_course_info?<event_id>")]
 pub fn event_course_info(session: UserSession, event_id: u64) -> Result<Json<Option<Course>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -128,78 +214,118 @@ pub fn event_course_info(session: UserSession, event_id: u64) -> Result<Json<Opt
 #[rocket::head("/admin/event_course_edit?<event_id>&<course_id>")]
 pub fn event_course_edit(session: UserSession, event_id: u64, course_id: Option<u32>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
-    if !session.right.right_course_write {
+    if !session.right.has(Permission::CourseWrite) {
         return Err(ErrorKind::RightCourseMissing);
     };
 
-    crate::db::event::event_course_edit(conn, event_id, course_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_course_info(conn, event_id)?;
+        crate::db::event::event_course_edit(conn, event_id, course_id)?;
+        let after = crate::db::event::event_course_info(conn, event_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_course",
+            event_id,
+            before.as_ref(),
+            after.as_ref(),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/event_delete?<event_id>")]
 pub fn event_delete(session: UserSession, event_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::event_delete(conn, event_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_delete(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), None)?;
+        Ok(())
+    })
 }
 
-#[rocket::head("/admin/event_accept?<event_id>")]
-pub fn event_accept(session: UserSession, event_id: u64) -> Result<()> {
+#[rocket::head("/admin/event_accept?<event_id>&<force>")]
+pub fn event_accept(session: UserSession, event_id: u64, force: Option<WebBool>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventAccept) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    // Perhaps lock the DB during checking and potentially accepting the request
-    let event: Event = crate::db::event::event_info(conn, event_id)?;
-
-    // Check if the event is somewhat reasonable
-    if !crate::utils::event::is_event_valid(&event) {
-        return Err(ErrorKind::EventWindowInvalid);
-    }
-
-    crate::db::event::event_acceptance_edit(conn, event.id, &Acceptance::Accepted)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        // Perhaps lock the DB during checking and potentially accepting the request
+        let event: Event = crate::db::event::event_info(conn, event_id)?;
+
+        // Check if the event is somewhat reasonable
+        if !crate::utils::event::is_event_valid(&event) {
+            return Err(ErrorKind::EventWindowInvalid);
+        }
+
+        // Double bookings are only accepted on purpose
+        if !force.map(|b| b.to_bool()).unwrap_or(false) && !crate::db::event::event_free_true(conn, &event)? {
+            return Err(ErrorKind::EventWindowConflict);
+        }
+
+        crate::db::event::event_acceptance_edit(conn, event.id, &Acceptance::Accepted)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&event), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/event_reject?<event_id>")]
 pub fn event_reject(session: UserSession, event_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventAccept) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Rejected)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Rejected)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/event_suspend?<event_id>")]
 pub fn event_suspend(session: UserSession, event_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Pending)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Pending)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/event_withdraw?<event_id>")]
 pub fn event_withdraw(session: UserSession, event_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Draft)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Draft)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/event_statistic_packlist?<event_id>&<category1>&<category2>&<category3>")]
@@ -211,7 +337,7 @@ pub fn statistic_packlist(
     category3: Option<u32>,
 ) -> Result<Json<Vec<(User, u32, u32, u32)>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -226,7 +352,7 @@ pub fn statistic_organisation(
     organisation_id: u64,
 ) -> Result<Json<Vec<Affiliation>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -1,13 +1,13 @@
 use rocket::serde::json::Json;
 
-use crate::common::User;
+use crate::common::{Permission, User};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/event_owner_list?<event_id>")]
 pub fn owner_list(session: UserSession, event_id: u64) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_read {
+    if !session.right.has(Permission::EventRead) {
         return Err(ErrorKind::RightEventMissing);
     };
 
@@ -18,21 +18,41 @@ pub fn owner_list(session: UserSession, event_id: u64) -> Result<Json<Vec<User>>
 #[rocket::head("/admin/event_owner_add?<event_id>&<user_id>")]
 pub fn owner_add(session: UserSession, event_id: u64, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::owner::event_owner_add(conn, event_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::owner::event_owner_add(conn, event_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_owner",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/event_owner_remove?<event_id>&<user_id>")]
 pub fn owner_remove(session: UserSession, event_id: u64, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_event_write {
+    if !session.right.has(Permission::EventWrite) {
         return Err(ErrorKind::RightEventMissing);
     };
 
-    crate::db::event::owner::event_owner_remove(conn, event_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::owner::event_owner_remove(conn, event_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_owner",
+            event_id,
+            Some(&serde_json::json!({ "user_id": user_id })),
+            None,
+        )?;
+        Ok(())
+    })
 }
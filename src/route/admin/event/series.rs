@@ -0,0 +1,140 @@
+use rocket::serde::json::Json;
+
+use crate::common::{Acceptance, EventSeries, Permission, SeriesScope};
+use crate::error::{ErrorKind, Result};
+use crate::session::UserSession;
+
+#[rocket::get("/admin/event_series_list?<course_id>")]
+pub fn series_list(session: UserSession, course_id: u32) -> Result<Json<Vec<EventSeries>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session
+        .course_right(conn, course_id as u64)?
+        .has(Permission::CourseRead)
+    {
+        return Err(ErrorKind::RightCourseMissing);
+    };
+
+    let series = crate::db::event::series::event_series_list(conn, course_id)?;
+    Ok(Json(series))
+}
+
+#[rocket::get("/admin/event_series_info?<series_id>")]
+pub fn series_info(session: UserSession, series_id: u32) -> Result<Json<EventSeries>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let series = crate::db::event::series::event_series_info(conn, series_id)?;
+
+    if !session
+        .course_right(conn, series.course_id as u64)?
+        .has(Permission::CourseRead)
+    {
+        return Err(ErrorKind::RightCourseMissing);
+    };
+
+    Ok(Json(series))
+}
+
+#[rocket::post(
+    "/admin/event_series_create?<course_id>",
+    format = "application/json",
+    data = "<series>"
+)]
+pub fn series_create(session: UserSession, course_id: u32, mut series: Json<EventSeries>) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let right = session.course_right(conn, course_id as u64)?;
+    if !right.has(Permission::CourseWrite) {
+        return Err(ErrorKind::RightCourseMissing);
+    };
+
+    if !right.has(Permission::EventWrite) {
+        return Err(ErrorKind::RightEventMissing);
+    };
+
+    let acceptance = match right.has(Permission::EventAccept) {
+        true => Acceptance::Accepted,
+        false => Acceptance::Draft,
+    };
+
+    crate::db::transaction(conn, |conn| {
+        let id = crate::utils::event_series::event_series_create(conn, course_id, &mut series, &acceptance)?;
+        let after = crate::db::event::series::event_series_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "event_series", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
+}
+
+#[rocket::post(
+    "/admin/event_series_edit?<series_id>&<event_id>&<scope>",
+    format = "application/json",
+    data = "<series>"
+)]
+pub fn series_edit(
+    session: UserSession,
+    series_id: u32,
+    event_id: Option<u64>,
+    scope: SeriesScope,
+    mut series: Json<EventSeries>,
+) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let before = crate::db::event::series::event_series_info(conn, series_id)?;
+    let right = session.course_right(conn, before.course_id as u64)?;
+    if !right.has(Permission::CourseWrite) {
+        return Err(ErrorKind::RightCourseMissing);
+    };
+
+    if !right.has(Permission::EventWrite) {
+        return Err(ErrorKind::RightEventMissing);
+    };
+
+    let acceptance = match right.has(Permission::EventAccept) {
+        true => Acceptance::Accepted,
+        false => Acceptance::Draft,
+    };
+
+    crate::db::transaction(conn, |conn| {
+        let id =
+            crate::utils::event_series::event_series_edit(conn, series_id, event_id, &scope, &mut series, &acceptance)?;
+        let after = crate::db::event::series::event_series_info(conn, series_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_series",
+            series_id as u64,
+            Some(&before),
+            Some(&after),
+        )?;
+
+        if id != series_id {
+            let split = crate::db::event::series::event_series_info(conn, id)?;
+            crate::utils::audit::audit(conn, &session, "event_series", id as u64, None, Some(&split))?;
+        }
+        Ok(id.to_string())
+    })
+}
+
+#[rocket::head("/admin/event_series_delete?<series_id>&<event_id>&<scope>")]
+pub fn series_delete(session: UserSession, series_id: u32, event_id: Option<u64>, scope: SeriesScope) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let before = crate::db::event::series::event_series_info(conn, series_id)?;
+    let right = session.course_right(conn, before.course_id as u64)?;
+    if !right.has(Permission::CourseWrite) {
+        return Err(ErrorKind::RightCourseMissing);
+    };
+
+    if !right.has(Permission::EventWrite) {
+        return Err(ErrorKind::RightEventMissing);
+    };
+
+    crate::db::transaction(conn, |conn| {
+        crate::utils::event_series::event_series_delete(conn, series_id, event_id, &scope)?;
+        let after = crate::db::event::series::event_series_info(conn, series_id).ok();
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_series",
+            series_id as u64,
+            Some(&before),
+            after.as_ref(),
+        )?;
+        Ok(())
+    })
+}
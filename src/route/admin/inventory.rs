@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::{Item, ItemCategory, Possession, Stock, WebBool};
+use crate::common::{Item, ItemCategory, Permission, Possession, Stock, WebBool};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -9,7 +9,7 @@ use crate::session::UserSession;
 #[rocket::get("/admin/item_list?<category_id>")]
 pub fn item_list(session: UserSession, category_id: Option<u32>) -> Result<Json<Vec<Item>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_read {
+    if !session.right.has(Permission::InventoryRead) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
@@ -20,7 +20,7 @@ pub fn item_list(session: UserSession, category_id: Option<u32>) -> Result<Json<
 #[rocket::get("/admin/item_info?<item_id>")]
 pub fn item_info(session: UserSession, item_id: u32) -> Result<Json<Item>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_read {
+    if !session.right.has(Permission::InventoryRead) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
@@ -31,34 +31,47 @@ pub fn item_info(session: UserSession, item_id: u32) -> Result<Json<Item>> {
 #[rocket::post("/admin/item_create", format = "application/json", data = "<item>")]
 pub fn item_create(session: UserSession, item: Json<Item>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    let id = crate::db::inventory::item_create(conn, &item)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::inventory::item_create(conn, &item)?;
+        let after = crate::db::inventory::item_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "item", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post("/admin/item_edit?<item_id>", format = "application/json", data = "<item>")]
 pub fn item_edit(session: UserSession, item_id: u64, item: Json<Item>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    crate::db::inventory::item_edit(conn, item_id, &item)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::inventory::item_info(conn, item_id as u32)?;
+        crate::db::inventory::item_edit(conn, item_id, &item)?;
+        let after = crate::db::inventory::item_info(conn, item_id as u32)?;
+        crate::utils::audit::audit(conn, &session, "item", item_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/item_delete?<item_id>")]
 pub fn item_delete(session: UserSession, item_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    crate::db::inventory::item_delete(conn, item_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::inventory::item_info(conn, item_id as u32)?;
+        crate::db::inventory::item_delete(conn, item_id)?;
+        crate::utils::audit::audit(conn, &session, "item", item_id, Some(&before), None)?;
+        Ok(())
+    })
 }
 
 /* ITEM CATEGORIES */
@@ -66,7 +79,7 @@ pub fn item_delete(session: UserSession, item_id: u64) -> Result<()> {
 #[rocket::get("/admin/itemcat_list")]
 pub fn itemcat_list(session: UserSession) -> Result<Json<Vec<ItemCategory>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_read {
+    if !session.right.has(Permission::InventoryRead) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
@@ -77,34 +90,47 @@ pub fn itemcat_list(session: UserSession) -> Result<Json<Vec<ItemCategory>>> {
 #[rocket::post("/admin/itemcat_create", format = "application/json", data = "<itemcat>")]
 pub fn itemcat_create(session: UserSession, itemcat: Json<ItemCategory>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    let id = crate::db::inventory::itemcat_create(conn, &itemcat)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::inventory::itemcat_create(conn, &itemcat)?;
+        let after = crate::db::inventory::itemcat_info(conn, id as u64)?;
+        crate::utils::audit::audit(conn, &session, "itemcat", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post("/admin/itemcat_edit?<category_id>", format = "application/json", data = "<itemcat>")]
 pub fn itemcat_edit(session: UserSession, category_id: u64, itemcat: Json<ItemCategory>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    crate::db::inventory::itemcat_edit(conn, category_id, &itemcat)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::inventory::itemcat_info(conn, category_id)?;
+        crate::db::inventory::itemcat_edit(conn, category_id, &itemcat)?;
+        let after = crate::db::inventory::itemcat_info(conn, category_id)?;
+        crate::utils::audit::audit(conn, &session, "itemcat", category_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/itemcat_delete?<category_id>")]
 pub fn itemcat_delete(session: UserSession, category_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    crate::db::inventory::itemcat_delete(conn, category_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::inventory::itemcat_info(conn, category_id)?;
+        crate::db::inventory::itemcat_delete(conn, category_id)?;
+        crate::utils::audit::audit(conn, &session, "itemcat", category_id, Some(&before), None)?;
+        Ok(())
+    })
 }
 
 /* STOCK */
@@ -112,7 +138,11 @@ pub fn itemcat_delete(session: UserSession, category_id: u64) -> Result<()> {
 #[rocket::get("/admin/stock_list?<club_id>&<item_id>")]
 pub fn stock_list(session: UserSession, club_id: Option<u32>, item_id: Option<u32>) -> Result<Json<Vec<Stock>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_read {
+    let right = match club_id {
+        None => session.right.clone(),
+        Some(club_id) => session.club_right(club_id),
+    };
+    if !right.has(Permission::InventoryRead) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
@@ -123,7 +153,7 @@ pub fn stock_list(session: UserSession, club_id: Option<u32>, item_id: Option<u3
 #[rocket::post("/admin/stock_create", format = "application/json", data = "<stock>")]
 pub fn stock_create(session: UserSession, stock: Json<Stock>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.club_right(stock.club.id as u32).has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
@@ -131,99 +161,113 @@ pub fn stock_create(session: UserSession, stock: Json<Stock>) -> Result<()> {
         return Err(ErrorKind::InventoryStockLimit);
     }
 
-    crate::db::inventory::stock_create(conn, stock.club.id, stock.item.id, &stock.storage, stock.owned)?;
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::inventory::stock_create(conn, stock.club.id, stock.item.id, &stock.storage, stock.owned)?;
+        let after = crate::db::inventory::stock_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "stock", id, None, Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::post("/admin/stock_edit?<stock_id>", format = "application/json", data = "<stock>")]
 pub fn stock_edit(session: UserSession, stock_id: u64, stock: Json<Stock>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
-        return Err(ErrorKind::RightInventoryMissing);
-    };
-
     if stock.owned > 100 {
         return Err(ErrorKind::InventoryStockLimit);
     }
 
-    let db_stock = crate::db::inventory::stock_info(conn, stock_id)?;
+    crate::db::transaction(conn, |conn| {
+        let db_stock = crate::db::inventory::stock_info(conn, stock_id)?;
 
-    let delta = stock.owned as i64 - db_stock.owned as i64;
+        if !session
+            .club_right(db_stock.club.id as u32)
+            .has(Permission::InventoryWrite)
+        {
+            return Err(ErrorKind::RightInventoryMissing);
+        };
 
-    // No change, useless request
-    if delta == 0 && db_stock.storage == stock.storage {
-        return Ok(());
-    }
+        let delta = stock.owned as i64 - db_stock.owned as i64;
 
-    // Do not remove loaned items
-    if stock.owned < db_stock.loaned {
-        return Err(ErrorKind::InventoryStockConflict);
-    }
+        // No change, useless request
+        if delta == 0 && db_stock.storage == stock.storage {
+            return Ok(());
+        }
 
-    // Check if the client has a different loan information
-    if db_stock.loaned != stock.loaned {
-        return Err(ErrorKind::InventoryStockConflict);
-    }
+        // Do not remove loaned items
+        if stock.owned < db_stock.loaned {
+            return Err(ErrorKind::InventoryStockConflict);
+        }
+
+        // Check if the client has a different loan information
+        if db_stock.loaned != stock.loaned {
+            return Err(ErrorKind::InventoryStockConflict);
+        }
 
-    crate::db::inventory::stock_edit(conn, stock_id, &stock.storage, stock.owned, stock.loaned)?;
+        crate::db::inventory::stock_edit(conn, stock_id, &stock.storage, stock.owned, stock.loaned)?;
+        let after = crate::db::inventory::stock_info(conn, stock_id)?;
+        crate::utils::audit::audit(conn, &session, "stock", stock_id, Some(&db_stock), Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/stock_delete?<stock_id>")]
 pub fn stock_delete(session: UserSession, stock_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    let stock = crate::db::inventory::stock_info(conn, stock_id)?;
+
+    if !session.club_right(stock.club.id as u32).has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    let stock = crate::db::inventory::stock_info(conn, stock_id)?;
-
     // Cannot delete a stock that is incomplete
     if stock.loaned > 0 {
         return Err(ErrorKind::InventoryLoanConflict);
     }
 
-    crate::db::inventory::stock_delete(conn, stock_id)?;
+    crate::db::transaction(conn, |conn| {
+        crate::db::inventory::stock_delete(conn, stock_id)?;
+        crate::utils::audit::audit(conn, &session, "stock", stock_id, Some(&stock), None)?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/item_loan?<stock_id>&<user_id>")]
 pub fn item_loan(session: UserSession, stock_id: u64, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    let stock = crate::db::inventory::stock_info(conn, stock_id)?;
+
+    if !session.club_right(stock.club.id as u32).has(Permission::InventoryLoan) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    let stock = crate::db::inventory::stock_info(conn, stock_id)?;
-
     // No items available to loan
     if stock.owned <= stock.loaned {
         return Err(ErrorKind::InventoryStockConflict);
     }
 
-    crate::db::inventory::stock_edit(conn, stock_id, &stock.storage, stock.owned, stock.loaned + 1)?;
-    crate::db::inventory::possession_create(
-        conn,
-        user_id,
-        stock.item.id,
-        chrono::Utc::now().date_naive(),
-        false,
-        Some(stock_id),
-    )?;
-
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::inventory::stock_edit(conn, stock_id, &stock.storage, stock.owned, stock.loaned + 1)?;
+        let id = crate::db::inventory::possession_create(
+            conn,
+            user_id,
+            stock.item.id,
+            chrono::Utc::now().date_naive(),
+            false,
+            Some(stock_id),
+        )?;
+        let after = crate::db::inventory::possession_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "possession", id, None, Some(&after))?;
+
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/item_return?<possession_id>")]
 pub fn item_return(session: UserSession, possession_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
-        return Err(ErrorKind::RightInventoryMissing);
-    };
-
     let possession = crate::db::inventory::possession_info(conn, possession_id)?;
     let stock = crate::db::inventory::possession_ownership(conn, possession_id)?;
 
@@ -238,24 +282,27 @@ pub fn item_return(session: UserSession, possession_id: u64) -> Result<()> {
         (false, Some(stock)) => stock,
     };
 
+    if !session.club_right(stock.club.id as u32).has(Permission::InventoryLoan) {
+        return Err(ErrorKind::RightInventoryMissing);
+    };
+
     // Should not happen, but make sure that there are loaned items that can be returned
     if stock.loaned < 1 {
         return Err(ErrorKind::DatabaseError);
     }
 
-    crate::db::inventory::stock_edit(conn, stock.id, &stock.storage, stock.owned, stock.loaned - 1)?;
-    crate::db::inventory::possession_delete(conn, possession_id)?;
+    crate::db::transaction(conn, |conn| {
+        crate::db::inventory::stock_edit(conn, stock.id, &stock.storage, stock.owned, stock.loaned - 1)?;
+        crate::db::inventory::possession_delete(conn, possession_id)?;
+        crate::utils::audit::audit(conn, &session, "possession", possession_id, Some(&possession), None)?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/item_handout?<possession_id>")]
 pub fn item_handout(session: UserSession, possession_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
-        return Err(ErrorKind::RightInventoryMissing);
-    };
-
     let possession = crate::db::inventory::possession_info(conn, possession_id)?;
     let stock = crate::db::inventory::possession_ownership(conn, possession_id)?;
 
@@ -270,27 +317,42 @@ pub fn item_handout(session: UserSession, possession_id: u64) -> Result<()> {
         (false, Some(stock)) => stock,
     };
 
+    if !session.club_right(stock.club.id as u32).has(Permission::InventoryLoan) {
+        return Err(ErrorKind::RightInventoryMissing);
+    };
+
     // Should not happen, but make sure that there are loaned items that can be handed out
     if stock.owned < 1 || stock.loaned < 1 {
         return Err(ErrorKind::InventoryLoanConflict);
     }
 
-    crate::db::inventory::possession_edit(conn, possession_id, &possession, None)?;
-    crate::db::inventory::stock_edit(conn, stock.id, &stock.storage, stock.owned - 1, stock.loaned - 1)?;
-
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::inventory::possession_edit(conn, possession_id, &possession, None)?;
+        crate::db::inventory::stock_edit(conn, stock.id, &stock.storage, stock.owned - 1, stock.loaned - 1)?;
+        let after = crate::db::inventory::possession_info(conn, possession_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "possession",
+            possession_id,
+            Some(&possession),
+            Some(&after),
+        )?;
+
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/item_restock?<possession_id>&<stock_id>")]
 pub fn item_restock(session: UserSession, possession_id: u64, stock_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
-        return Err(ErrorKind::RightInventoryMissing);
-    };
-
     let possession = crate::db::inventory::possession_info(conn, possession_id)?;
     let stock = crate::db::inventory::stock_info(conn, stock_id)?;
 
+    if !session.club_right(stock.club.id as u32).has(Permission::InventoryLoan) {
+        return Err(ErrorKind::RightInventoryMissing);
+    };
+
     // Cannot restock items on a stock of a different item type
     if possession.item.id != stock.item.id {
         return Err(ErrorKind::InventoryStockConflict);
@@ -301,10 +363,21 @@ pub fn item_restock(session: UserSession, possession_id: u64, stock_id: u64) ->
         return Err(ErrorKind::InventoryLoanConflict);
     };
 
-    crate::db::inventory::possession_edit(conn, possession_id, &possession, Some(stock_id))?;
-    crate::db::inventory::stock_edit(conn, stock_id, &stock.storage, stock.owned + 1, stock.loaned + 1)?;
-
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::inventory::possession_edit(conn, possession_id, &possession, Some(stock_id))?;
+        crate::db::inventory::stock_edit(conn, stock_id, &stock.storage, stock.owned + 1, stock.loaned + 1)?;
+        let after = crate::db::inventory::possession_info(conn, possession_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "possession",
+            possession_id,
+            Some(&possession),
+            Some(&after),
+        )?;
+
+        Ok(())
+    })
 }
 
 /* POSSESSIONS */
@@ -318,7 +391,7 @@ pub fn possession_list(
     club_id: Option<u32>,
 ) -> Result<Json<Vec<Possession>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_read {
+    if !session.right.has(Permission::InventoryRead) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
@@ -330,28 +403,42 @@ pub fn possession_list(
 #[rocket::head("/admin/possession_create?<user_id>&<item_id>")]
 pub fn possession_create(session: UserSession, user_id: u64, item_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    crate::db::inventory::possession_create(conn, user_id, item_id, chrono::Utc::now().date_naive(), true, None)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::inventory::possession_create(
+            conn,
+            user_id,
+            item_id,
+            chrono::Utc::now().date_naive(),
+            true,
+            None,
+        )?;
+        let after = crate::db::inventory::possession_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "possession", id, None, Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/possession_delete?<possession_id>")]
 pub fn possession_delete(session: UserSession, possession_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_inventory_write {
+    if !session.right.has(Permission::InventoryWrite) {
         return Err(ErrorKind::RightInventoryMissing);
     };
 
-    let possession = crate::db::inventory::possession_info(conn, possession_id)?;
+    crate::db::transaction(conn, |conn| {
+        let possession = crate::db::inventory::possession_info(conn, possession_id)?;
 
-    // Cannot delete items one does not own
-    if !&possession.owned {
-        return Err(ErrorKind::Default);
-    }
+        // Cannot delete items one does not own
+        if !&possession.owned {
+            return Err(ErrorKind::Default);
+        }
 
-    crate::db::inventory::possession_delete(conn, possession_id)?;
-    Ok(())
+        crate::db::inventory::possession_delete(conn, possession_id)?;
+        crate::utils::audit::audit(conn, &session, "possession", possession_id, Some(&possession), None)?;
+        Ok(())
+    })
 }
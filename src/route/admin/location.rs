@@ -1,13 +1,13 @@
 use rocket::serde::json::Json;
 
-use crate::common::Location;
+use crate::common::{Location, Permission};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/location_list")]
 pub fn location_list(session: UserSession) -> Result<Json<Vec<Location>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_location_read {
+    if !session.right.has(Permission::LocationRead) {
         return Err(ErrorKind::RightLocationMissing);
     };
 
@@ -18,12 +18,16 @@ pub fn location_list(session: UserSession) -> Result<Json<Vec<Location>>> {
 #[rocket::post("/admin/location_create", format = "application/json", data = "<location>")]
 pub fn location_create(session: UserSession, location: Json<Location>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_location_write {
+    if !session.right.has(Permission::LocationWrite) {
         return Err(ErrorKind::RightLocationMissing);
     };
 
-    let id = crate::db::location::location_create(conn, &location)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::location::location_create(conn, &location)?;
+        let after = crate::db::location::location_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "location", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post(
@@ -33,21 +37,37 @@ pub fn location_create(session: UserSession, location: Json<Location>) -> Result
 )]
 pub fn location_edit(session: UserSession, location_id: u32, location: Json<Location>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_location_write {
+    if !session.right.has(Permission::LocationWrite) {
         return Err(ErrorKind::RightLocationMissing);
     };
 
-    crate::db::location::location_edit(conn, location_id, &location)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::location::location_info(conn, location_id)?;
+        crate::db::location::location_edit(conn, location_id, &location)?;
+        let after = crate::db::location::location_info(conn, location_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "location",
+            location_id as u64,
+            Some(&before),
+            Some(&after),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/location_delete?<location_id>")]
 pub fn location_delete(session: UserSession, location_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_location_write {
+    if !session.right.has(Permission::LocationWrite) {
         return Err(ErrorKind::RightLocationMissing);
     };
 
-    crate::db::location::location_delete(conn, location_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::location::location_info(conn, location_id)?;
+        crate::db::location::location_delete(conn, location_id)?;
+        crate::utils::audit::audit(conn, &session, "location", location_id as u64, Some(&before), None)?;
+        Ok(())
+    })
 }
@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::Affiliation;
+use crate::common::{Affiliation, Permission};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -11,7 +11,7 @@ pub fn affiliation_list(
     organisation_id: Option<u32>,
 ) -> Result<Json<Vec<Affiliation>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_read {
+    if !session.right.has(Permission::OrganisationRead) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
@@ -22,7 +22,7 @@ pub fn affiliation_list(
 #[rocket::get("/admin/affiliation_info?<user_id>&<organisation_id>")]
 pub fn affiliation_info(session: UserSession, user_id: u64, organisation_id: u32) -> Result<Json<Option<Affiliation>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_read {
+    if !session.right.has(Permission::OrganisationRead) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
@@ -33,12 +33,16 @@ pub fn affiliation_info(session: UserSession, user_id: u64, organisation_id: u32
 #[rocket::head("/admin/affiliation_create?<user_id>&<organisation_id>")]
 pub fn affiliation_create(session: UserSession, user_id: u64, organisation_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_write {
+    if !session.right.has(Permission::OrganisationWrite) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
-    crate::db::organisation::affiliation_create(conn, user_id, organisation_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::organisation::affiliation_create(conn, user_id, organisation_id)?;
+        let after = crate::db::organisation::affiliation_info(conn, user_id, organisation_id)?;
+        crate::utils::audit::audit(conn, &session, "affiliation", user_id, None, after.as_ref())?;
+        Ok(())
+    })
 }
 
 #[rocket::post(
@@ -53,21 +57,30 @@ pub fn affiliation_edit(
     affiliation: Json<Affiliation>,
 ) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_write {
+    if !session.right.has(Permission::OrganisationWrite) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
-    crate::db::organisation::affiliation_edit(conn, user_id, organisation_id, &affiliation)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::organisation::affiliation_info(conn, user_id, organisation_id)?;
+        crate::db::organisation::affiliation_edit(conn, user_id, organisation_id, &affiliation)?;
+        let after = crate::db::organisation::affiliation_info(conn, user_id, organisation_id)?;
+        crate::utils::audit::audit(conn, &session, "affiliation", user_id, before.as_ref(), after.as_ref())?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/affiliation_delete?<user_id>&<organisation_id>")]
 pub fn affiliation_delete(session: UserSession, user_id: u64, organisation_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_write {
+    if !session.right.has(Permission::OrganisationWrite) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
-    crate::db::organisation::affiliation_delete(conn, user_id, organisation_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::organisation::affiliation_info(conn, user_id, organisation_id)?;
+        crate::db::organisation::affiliation_delete(conn, user_id, organisation_id)?;
+        crate::utils::audit::audit(conn, &session, "affiliation", user_id, before.as_ref(), None)?;
+        Ok(())
+    })
 }
@@ -1,13 +1,13 @@
 use rocket::serde::json::Json;
 
-use crate::common::Organisation;
+use crate::common::{Organisation, Permission};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/organisation_list")]
 pub fn organisation_list(session: UserSession) -> Result<Json<Vec<Organisation>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_read {
+    if !session.right.has(Permission::OrganisationRead) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
@@ -18,7 +18,7 @@ pub fn organisation_list(session: UserSession) -> Result<Json<Vec<Organisation>>
 #[rocket::get("/admin/organisation_info?<organisation_id>")]
 pub fn organisation_info(session: UserSession, organisation_id: u32) -> Result<Json<Organisation>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_read {
+    if !session.right.has(Permission::OrganisationRead) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
@@ -29,12 +29,16 @@ pub fn organisation_info(session: UserSession, organisation_id: u32) -> Result<J
 #[rocket::post("/admin/organisation_create", format = "application/json", data = "<organisation>")]
 pub fn organisation_create(session: UserSession, organisation: Json<Organisation>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_write {
+    if !session.right.has(Permission::OrganisationWrite) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
-    let id = crate::db::organisation::organisation_create(conn, &organisation)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::organisation::organisation_create(conn, &organisation)?;
+        let after = crate::db::organisation::organisation_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "organisation", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post(
@@ -44,21 +48,44 @@ pub fn organisation_create(session: UserSession, organisation: Json<Organisation
 )]
 pub fn organisation_edit(session: UserSession, organisation_id: u32, organisation: Json<Organisation>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_write {
+    if !session.right.has(Permission::OrganisationWrite) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
-    crate::db::organisation::organisation_edit(conn, organisation_id, &organisation)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::organisation::organisation_info(conn, organisation_id)?;
+        crate::db::organisation::organisation_edit(conn, organisation_id, &organisation)?;
+        let after = crate::db::organisation::organisation_info(conn, organisation_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "organisation",
+            organisation_id as u64,
+            Some(&before),
+            Some(&after),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/organisation_delete?<organisation_id>")]
 pub fn organisation_delete(session: UserSession, organisation_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_organisation_write {
+    if !session.right.has(Permission::OrganisationWrite) {
         return Err(ErrorKind::RightOrganisationMissing);
     };
 
-    crate::db::organisation::organisation_delete(conn, organisation_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::organisation::organisation_info(conn, organisation_id)?;
+        crate::db::organisation::organisation_delete(conn, organisation_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "organisation",
+            organisation_id as u64,
+            Some(&before),
+            None,
+        )?;
+        Ok(())
+    })
 }
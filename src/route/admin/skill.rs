@@ -1,13 +1,13 @@
 use rocket::serde::json::Json;
 
-use crate::common::Skill;
+use crate::common::{Permission, Skill};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
 #[rocket::get("/admin/skill_list")]
 pub fn skill_list(session: UserSession) -> Result<Json<Vec<Skill>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_read {
+    if !session.right.has(Permission::CompetenceRead) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
@@ -18,32 +18,45 @@ pub fn skill_list(session: UserSession) -> Result<Json<Vec<Skill>>> {
 #[rocket::post("/admin/skill_create", format = "application/json", data = "<skill>")]
 pub fn skill_create(session: UserSession, skill: Json<Skill>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_write {
+    if !session.right.has(Permission::CompetenceWrite) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
-    let id = crate::db::skill::skill_create(conn, &skill)?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::skill::skill_create(conn, &skill)?;
+        let after = crate::db::skill::skill_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "skill", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post("/admin/skill_edit?<skill_id>", format = "application/json", data = "<skill>")]
 pub fn skill_edit(session: UserSession, skill_id: u32, skill: Json<Skill>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_write {
+    if !session.right.has(Permission::CompetenceWrite) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
-    crate::db::skill::skill_edit(conn, skill_id, &skill)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::skill::skill_info(conn, skill_id)?;
+        crate::db::skill::skill_edit(conn, skill_id, &skill)?;
+        let after = crate::db::skill::skill_info(conn, skill_id)?;
+        crate::utils::audit::audit(conn, &session, "skill", skill_id as u64, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/skill_delete?<skill_id>")]
 pub fn skill_delete(session: UserSession, skill_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_competence_write {
+    if !session.right.has(Permission::CompetenceWrite) {
         return Err(ErrorKind::RightCompetenceMissing);
     };
 
-    crate::db::skill::skill_delete(conn, skill_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::skill::skill_info(conn, skill_id)?;
+        crate::db::skill::skill_delete(conn, skill_id)?;
+        crate::utils::audit::audit(conn, &session, "skill", skill_id as u64, Some(&before), None)?;
+        Ok(())
+    })
 }
@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::{Right, Team, User};
+use crate::common::{Permission, Right, Team, User};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -9,7 +9,7 @@ use crate::session::UserSession;
 #[rocket::get("/admin/team_list")]
 pub fn team_list(session: UserSession) -> Result<Json<Vec<Team>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_read {
+    if !session.right.has(Permission::TeamRead) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
@@ -20,7 +20,7 @@ pub fn team_list(session: UserSession) -> Result<Json<Vec<Team>>> {
 #[rocket::get("/admin/team_info?<team_id>")]
 pub fn team_info(session: UserSession, team_id: u32) -> Result<Json<Team>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_read {
+    if !session.right.has(Permission::TeamRead) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
@@ -31,51 +31,80 @@ pub fn team_info(session: UserSession, team_id: u32) -> Result<Json<Team>> {
 #[rocket::post("/admin/team_create", format = "application/json", data = "<team>")]
 pub fn team_create(session: UserSession, team: Json<Team>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_write {
+    if !session.right.has(Permission::TeamWrite) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
-    let team_id = crate::db::team::team_create(conn, &team)?;
-    Ok(team_id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let team_id = crate::db::team::team_create(conn, &team)?;
+        let after = crate::db::team::team_info(conn, &team_id)?;
+        crate::utils::audit::audit(conn, &session, "team", team_id as u64, None, Some(&after))?;
+        Ok(team_id.to_string())
+    })
 }
 
 #[rocket::post("/admin/team_edit?<team_id>", format = "application/json", data = "<team>")]
 pub fn team_edit(session: UserSession, team_id: u32, team: Json<Team>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_write {
+    if !session.right.has(Permission::TeamWrite) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
-    crate::db::team::team_edit(conn, &team_id, &team)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::team::team_info(conn, &team_id)?;
+        crate::db::team::team_edit(conn, &team_id, &team)?;
+        crate::utils::session::refresh_team_rights(conn, team_id)?;
+        let after = crate::db::team::team_info(conn, &team_id)?;
+        crate::utils::audit::audit(conn, &session, "team", team_id as u64, Some(&before), Some(&after))?;
+        Ok(())
+    })
+}
+
+#[rocket::get("/admin/permission_list")]
+pub fn permission_list(session: UserSession) -> Result<Json<Vec<Permission>>> {
+    if !session.right.has(Permission::TeamRead) {
+        return Err(ErrorKind::RightTeamMissing);
+    };
+
+    Ok(Json(Permission::ALL.to_vec()))
 }
 
 #[rocket::post("/admin/team_right_edit?<team_id>", format = "application/json", data = "<right>")]
 pub fn team_right_edit(session: UserSession, team_id: u32, right: Json<Right>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_write {
+    if !session.right.has(Permission::TeamWrite) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
-    crate::db::team::team_right_edit(conn, &team_id, &right)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::team::team_info(conn, &team_id)?;
+        crate::db::team::team_right_edit(conn, &team_id, &right)?;
+        let after = crate::db::team::team_info(conn, &team_id)?;
+        crate::utils::audit::audit(conn, &session, "team", team_id as u64, Some(&before), Some(&after))?;
+        crate::utils::session::refresh_team_rights(conn, team_id)?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/team_delete?<team_id>")]
 pub fn team_delete(session: UserSession, team_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_write {
+    if !session.right.has(Permission::TeamWrite) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
-    crate::db::team::team_delete(conn, &team_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::team::team_info(conn, &team_id)?;
+        crate::db::team::team_delete(conn, &team_id)?;
+        crate::utils::audit::audit(conn, &session, "team", team_id as u64, Some(&before), None)?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/team_member_list?<team_id>")]
 pub fn team_member_list(session: UserSession, team_id: u32) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_read {
+    if !session.right.has(Permission::TeamRead) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
@@ -86,25 +115,43 @@ pub fn team_member_list(session: UserSession, team_id: u32) -> Result<Json<Vec<U
 #[rocket::head("/admin/team_member_add?<team_id>&<user_id>")]
 pub fn team_member_add(session: UserSession, team_id: u32, user_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_write {
+    if !session.right.has(Permission::TeamWrite) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
-    crate::db::team::team_member_add(conn, &team_id, &user_id)?;
-    Ok(())
-
-    // TODO: remove/add permissions of currently logged-in users
+    crate::db::transaction(conn, |conn| {
+        crate::db::team::team_member_add(conn, &team_id, &user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "team_member",
+            team_id as u64,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id })),
+        )?;
+        crate::utils::session::refresh_user_rights(conn, user_id as u64)?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/team_member_remove?<team_id>&<user_id>")]
 pub fn team_member_remove(session: UserSession, team_id: u32, user_id: u32) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_team_write {
+    if !session.right.has(Permission::TeamWrite) {
         return Err(ErrorKind::RightTeamMissing);
     };
 
-    crate::db::team::team_member_remove(conn, &team_id, &user_id)?;
-    Ok(())
-
-    // TODO: remove/add permissions of currently logged-in users
+    crate::db::transaction(conn, |conn| {
+        crate::db::team::team_member_remove(conn, &team_id, &user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "team_member",
+            team_id as u64,
+            Some(&serde_json::json!({ "user_id": user_id })),
+            None,
+        )?;
+        crate::utils::session::refresh_user_rights(conn, user_id as u64)?;
+        Ok(())
+    })
 }
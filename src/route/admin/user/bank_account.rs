@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::BankAccount;
+use crate::common::{BankAccount, Permission};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -13,13 +13,18 @@ use crate::session::UserSession;
 )]
 pub fn user_bank_account_create(session: UserSession, user_id: u64, bank_account: Json<BankAccount>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserBankAccountWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_bank_account_create(conn, user_id, &bank_account)?;
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_bank_account_create(conn, user_id, &bank_account)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::post(
@@ -29,22 +34,32 @@ pub fn user_bank_account_create(session: UserSession, user_id: u64, bank_account
 )]
 pub fn user_bank_account_edit(session: UserSession, user_id: u64, bank_account: Json<BankAccount>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserBankAccountWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_bank_account_edit(conn, user_id, &bank_account)?;
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_bank_account_edit(conn, user_id, &bank_account)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/user_bank_account_delete?<user_id>")]
 pub fn user_bank_account_delete(session: UserSession, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserBankAccountWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_bank_account_delete(conn, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_bank_account_delete(conn, user_id)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
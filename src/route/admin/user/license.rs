@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::License;
+use crate::common::{License, Permission};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -13,13 +13,18 @@ use crate::session::UserSession;
 )]
 pub fn user_license_main_create(session: UserSession, user_id: u64, license: Json<License>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserLicenseWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_license_main_create(conn, user_id, &license)?;
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_license_main_create(conn, user_id, &license)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::post(
@@ -29,13 +34,18 @@ pub fn user_license_main_create(session: UserSession, user_id: u64, license: Jso
 )]
 pub fn user_license_extra_create(session: UserSession, user_id: u64, license: Json<License>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserLicenseWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_license_extra_create(conn, user_id, &license)?;
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_license_extra_create(conn, user_id, &license)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::post(
@@ -45,13 +55,18 @@ pub fn user_license_extra_create(session: UserSession, user_id: u64, license: Js
 )]
 pub fn user_license_main_edit(session: UserSession, user_id: u64, license: Json<License>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserLicenseWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_license_main_edit(conn, user_id, &license)?;
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_license_main_edit(conn, user_id, &license)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::post(
@@ -61,33 +76,48 @@ pub fn user_license_main_edit(session: UserSession, user_id: u64, license: Json<
 )]
 pub fn user_license_extra_edit(session: UserSession, user_id: u64, license: Json<License>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserLicenseWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_license_extra_edit(conn, user_id, &license)?;
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_license_extra_edit(conn, user_id, &license)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/user_license_main_delete?<user_id>")]
 pub fn user_license_main_delete(session: UserSession, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserLicenseWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_license_main_delete(conn, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_license_main_delete(conn, user_id)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/user_license_extra_delete?<user_id>")]
 pub fn user_license_extra_delete(session: UserSession, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserLicenseWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    crate::db::user::user_license_extra_delete(conn, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_license_extra_delete(conn, user_id)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
@@ -0,0 +1,35 @@
+use rocket::serde::json::Json;
+
+use crate::common::{LoginCategory, LoginFailure, Permission};
+use crate::error::{ErrorKind, Result};
+use crate::session::UserSession;
+
+/* ROUTES */
+
+#[rocket::get("/admin/login_lockout_list")]
+pub fn login_lockout_list(session: UserSession) -> Result<Json<Vec<LoginFailure>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session.right.has(Permission::UserRead) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+
+    let failures = crate::db::login::login_failure_list(conn)?;
+    Ok(Json(failures))
+}
+
+#[rocket::head("/admin/login_lockout_clear?<category>&<subject>")]
+pub fn login_lockout_clear(session: UserSession, category: LoginCategory, subject: String) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session.right.has(Permission::UserWrite) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::login::login_failure_info(conn, &category, &subject)?;
+        crate::db::login::login_failure_delete(conn, &category, &subject)?;
+
+        // Login failures have no id of their own, the subject is part of the snapshot
+        crate::utils::audit::audit(conn, &session, "login_failure", 0, before.as_ref(), None)?;
+        Ok(())
+    })
+}
@@ -1,7 +1,11 @@
 mod bank_account;
 mod license;
+mod login;
+mod session;
 mod user;
 
 pub use bank_account::*;
 pub use license::*;
+pub use login::*;
+pub use session::*;
 pub use user::*;
@@ -0,0 +1,51 @@
+use rocket::serde::json::Json;
+
+use crate::common::{Permission, Session};
+use crate::error::{ErrorKind, Result};
+use crate::session::UserSession;
+
+/* ROUTES */
+
+#[rocket::get("/admin/user_session_list?<user_id>")]
+pub fn user_session_list(session: UserSession, user_id: u64) -> Result<Json<Vec<Session>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session.right.has(Permission::UserRead) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+
+    let sessions = crate::db::session::user_session_list(conn, user_id)?;
+    Ok(Json(sessions))
+}
+
+#[rocket::head("/admin/user_session_revoke?<user_id>")]
+pub fn user_session_revoke(session: UserSession, user_id: u64) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+    if !session.right.has(Permission::UserWrite) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+    session.deny_superuser_target(conn, user_id)?;
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::session::user_session_delete(conn, user_id)?;
+        crate::utils::audit::audit_action(conn, &session, "user_session", user_id)?;
+        Ok(())
+    })
+}
+
+#[rocket::head("/admin/user_totp_delete?<user_id>")]
+pub fn user_totp_delete(session: UserSession, user_id: u64) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+    if !session.right.has(Permission::UserWrite) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+    session.deny_superuser_target(conn, user_id)?;
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::user_totp_delete(conn, user_id)?;
+        crate::utils::audit::audit_action(conn, &session, "user_totp", user_id)?;
+        crate::utils::session::refresh_user_rights(conn, user_id)?;
+        Ok(())
+    })
+}
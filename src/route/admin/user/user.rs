@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::{Credential, User, WebBool};
+use crate::common::{Credential, Permission, User, UserDataExport, WebBool, USER_SENSITIVE_PERMISSIONS};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -9,7 +9,7 @@ use crate::session::UserSession;
 #[rocket::get("/admin/user_list?<active>")]
 pub fn user_list(session: UserSession, active: Option<WebBool>) -> Result<Json<Vec<User>>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_read {
+    if !session.right.has(Permission::UserRead) {
         return Err(ErrorKind::RightUserMissing);
     };
 
@@ -20,52 +20,109 @@ pub fn user_list(session: UserSession, active: Option<WebBool>) -> Result<Json<V
 #[rocket::get("/admin/user_detailed?<user_id>")]
 pub fn user_detailed(session: UserSession, user_id: u64) -> Result<Json<User>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_read {
+    if !session.right.has(Permission::UserRead) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    let user = crate::db::user::user_detailed(conn, user_id)?;
+    let mut user = crate::db::user::user_detailed(conn, user_id)?;
+    user.redact(&session.right);
     Ok(Json(user))
 }
 
+#[rocket::get("/admin/user_data_export?<user_id>")]
+pub fn user_data_export(session: UserSession, user_id: u64) -> Result<Json<UserDataExport>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+
+    // An export is only complete with the rights to see all personal data
+    if !session.right.has(Permission::UserRead) || !USER_SENSITIVE_PERMISSIONS.iter().all(|p| session.right.has(*p)) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+
+    crate::db::transaction(conn, |conn| {
+        let export = crate::db::user::user_data_export(conn, user_id)?;
+        crate::utils::audit::audit_action(conn, &session, "user_export", user_id)?;
+        Ok(Json(export))
+    })
+}
+
 #[rocket::post("/admin/user_create", format = "application/json", data = "<user>")]
 pub fn user_create(session: UserSession, mut user: Json<User>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
 
-    let user_id = crate::db::user::user_create(conn, &mut user)?;
+    crate::db::transaction(conn, |conn| {
+        let user_id = crate::db::user::user_create(conn, &mut user)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, None, Some(&after))?;
 
-    Ok(user_id.to_string())
+        Ok(user_id.to_string())
+    })
 }
 
 #[rocket::post("/admin/user_edit?<user_id>", format = "application/json", data = "<user>")]
 pub fn user_edit(session: UserSession, user_id: u64, mut user: Json<User>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
-
-    crate::db::user::user_edit(conn, user_id, &mut user)?;
-    Ok(())
+    session.deny_superuser_target(conn, user_id)?;
+
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        user.retain_hidden(&before, &session.right);
+        crate::db::user::user_edit(conn, user_id, &mut user)?;
+        let after = crate::db::user::user_detailed(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), Some(&after))?;
+
+        // Disabled users should not be able to continue with their current sessions
+        if user.enabled == Some(false) {
+            crate::db::session::user_session_delete(conn, user_id)?;
+        }
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/user_delete?<user_id>")]
 pub fn user_delete(session: UserSession, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    if !session.right.has(Permission::UserWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
+    session.deny_superuser_target(conn, user_id)?;
+
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::user::user_detailed(conn, user_id)?;
+        crate::db::user::user_delete(conn, user_id)?;
+        crate::utils::audit::audit_user(conn, &session, user_id, Some(&before), None)?;
+        Ok(())
+    })
+}
 
-    crate::db::user::user_delete(conn, user_id)?;
-    Ok(())
+#[rocket::head("/admin/user_anonymise?<user_id>")]
+pub fn user_anonymise(session: UserSession, user_id: u64) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !session.right.has(Permission::UserWrite) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+    session.deny_superuser_target(conn, user_id)?;
+
+    crate::db::transaction(conn, |conn| {
+        // Ensure that the user exists, the snapshot must not enter the audit log
+        crate::db::user::user_info(conn, user_id)?;
+
+        crate::db::user::user_anonymise(conn, user_id, &crate::common::user_pseudonym())?;
+        crate::utils::audit::audit_action(conn, &session, "user", user_id)?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/admin/user_password_info?<user_id>")]
 pub fn user_password_info(session: UserSession, user_id: u64) -> Result<Json<Credential>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_read {
+    if !session.right.has(Permission::UserRead) {
         return Err(ErrorKind::RightUserMissing);
     };
 
@@ -84,18 +141,23 @@ pub fn user_password_info(session: UserSession, user_id: u64) -> Result<Json<Cre
 )]
 pub fn user_password_create(session: UserSession, user_id: u64, credit: Json<Credential>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    session.deny_api_token()?;
+    if !session.right.has(Permission::UserWrite) {
         return Err(ErrorKind::RightUserMissing)?;
     };
+    session.deny_superuser_target(conn, user_id)?;
 
     let (hash, salt) = match (&credit.password, &credit.salt) {
         (Some(p), Some(s)) => (p, s),
         _ => return Err(ErrorKind::UserPasswordInvalid)?,
     };
 
-    crate::db::user::user_password_create(conn, user_id, hash, salt)?;
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::user_password_create(conn, user_id, hash, salt)?;
+        crate::utils::audit::audit_action(conn, &session, "user_credential", user_id)?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
 #[rocket::post(
@@ -105,26 +167,59 @@ pub fn user_password_create(session: UserSession, user_id: u64, credit: Json<Cre
 )]
 pub fn user_password_edit(session: UserSession, user_id: u64, credit: Json<Credential>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    session.deny_api_token()?;
+    if !session.right.has(Permission::UserWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
+    session.deny_superuser_target(conn, user_id)?;
 
     let (hash, salt) = match (&credit.password, &credit.salt) {
         (Some(p), Some(s)) => (p, s),
         _ => return Err(ErrorKind::UserPasswordInvalid),
     };
 
-    crate::db::user::user_password_edit(conn, user_id, hash, salt)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::user_password_edit(conn, user_id, hash, salt)?;
+        crate::utils::audit::audit_action(conn, &session, "user_credential", user_id)?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/admin/user_password_delete?<user_id>")]
 pub fn user_password_delete(session: UserSession, user_id: u64) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    if !session.right.right_user_write {
+    session.deny_api_token()?;
+    if !session.right.has(Permission::UserWrite) {
         return Err(ErrorKind::RightUserMissing);
     };
+    session.deny_superuser_target(conn, user_id)?;
 
-    crate::db::user::user_password_delete(conn, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::user_password_delete(conn, user_id)?;
+        crate::utils::audit::audit_action(conn, &session, "user_credential", user_id)?;
+        Ok(())
+    })
+}
+
+#[rocket::head("/admin/user_oidc_subject_edit?<user_id>&<subject>")]
+pub fn user_oidc_subject_edit(session: UserSession, user_id: u64, subject: Option<String>) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+    if !session.right.has(Permission::UserWrite) {
+        return Err(ErrorKind::RightUserMissing);
+    };
+    session.deny_superuser_target(conn, user_id)?;
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::user_oidc_subject_edit(conn, user_id, subject.as_deref().filter(|s| !s.is_empty()))?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "user_credential",
+            user_id,
+            None,
+            Some(&serde_json::json!({ "oidc_subject": subject })),
+        )?;
+        Ok(())
+    })
 }
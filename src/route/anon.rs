@@ -1,9 +1,10 @@
 extern crate lazy_static;
 
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use rocket::serde::json::Json;
+use std::net::IpAddr;
 
-use crate::common::{Club, Course, Location, Organisation, Skill};
+use crate::common::{Acceptance, Club, Course, Credential, Location, LoginCategory, Organisation, Skill};
 
 use crate::error::{ErrorKind, Result};
 
@@ -75,6 +76,97 @@ pub fn course_list() -> Result<Json<Vec<Course>>> {
     Ok(Json(courses))
 }
 
+#[rocket::get("/anon/course_calendar?<course_id>")]
+pub fn course_calendar(course_id: u32) -> Result<(ContentType, String)> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let course = crate::db::course::course_info(conn, course_id)?;
+
+    if !course.public {
+        return Err(ErrorKind::CourseMissing);
+    }
+
+    let begin = (chrono::Utc::now() - crate::config::EVENT_CALENDAR_HISTORY()).naive_utc();
+    let events = crate::db::event::event_list(
+        conn,
+        Some(begin),
+        None,
+        None,
+        None,
+        Some(Acceptance::Accepted),
+        Some(true),
+        Some(course_id),
+        None,
+        None,
+    )?;
+
+    let feed = crate::common::ical_feed(&course.title, &events, chrono::Utc::now().naive_utc());
+    Ok((ContentType::Calendar, feed))
+}
+
+#[rocket::get("/anon/location_calendar?<location_id>")]
+pub fn location_calendar(location_id: u32) -> Result<(ContentType, String)> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let location = crate::db::location::location_info(conn, location_id)?;
+
+    let begin = (chrono::Utc::now() - crate::config::EVENT_CALENDAR_HISTORY()).naive_utc();
+    let events = crate::db::event::event_list(
+        conn,
+        Some(begin),
+        None,
+        Some(location_id as u64),
+        None,
+        Some(Acceptance::Accepted),
+        None,
+        None,
+        None,
+        None,
+    )?;
+
+    // Only events which are public themselves or belong to a public course are revealed
+    let courses: Vec<u32> = crate::db::course::course_list(conn, None, None, Some(true))?
+        .iter()
+        .map(|course| course.id)
+        .collect();
+    let events: Vec<_> = events
+        .into_iter()
+        .filter(|event| event.public == Some(true) || event.course_id.is_some_and(|id| courses.contains(&id)))
+        .collect();
+
+    let feed = crate::common::ical_feed(&location.name, &events, chrono::Utc::now().naive_utc());
+    Ok((ContentType::Calendar, feed))
+}
+
+/// Calendar clients cannot send headers, so the feed token is part of the URI and redacted in the logs
+#[rocket::get("/anon/user_calendar?<token>")]
+pub fn user_calendar(token: String) -> Result<(ContentType, String)> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let token_hash = crate::common::hash_sha256(token.as_bytes(), &[]);
+
+    let user_id = match crate::db::user::feed_token_user(conn, &token_hash)? {
+        None => return Err(ErrorKind::FeedTokenInvalid),
+        Some(user_id) => user_id,
+    };
+
+    let begin = (chrono::Utc::now() - crate::config::EVENT_CALENDAR_HISTORY()).naive_utc();
+    let events = crate::db::event::event_list(
+        conn,
+        Some(begin),
+        None,
+        None,
+        None,
+        Some(Acceptance::Accepted),
+        None,
+        None,
+        None,
+        Some(user_id),
+    )?;
+
+    let user = crate::db::user::user_info(conn, user_id)?;
+    let name = format!("{} {}", user.firstname, user.lastname);
+    let feed = crate::common::ical_feed(&name, &events, chrono::Utc::now().naive_utc());
+    Ok((ContentType::Calendar, feed))
+}
+
 #[rocket::get("/anon/user_salt?<user_key>")]
 pub fn user_salt(user_key: String) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
@@ -86,3 +178,77 @@ pub fn user_salt(user_key: String) -> Result<String> {
         Ok(salt) => Ok(hex::encode(salt)),
     }
 }
+
+#[rocket::head("/anon/password_reset_request?<user_key>")]
+pub fn password_reset_request(user_key: String, client_ip: Option<IpAddr>) -> Result<()> {
+    // Keys which cannot exist get the same response as all others
+    if crate::common::validate_user_key(&user_key).is_err() {
+        return Ok(());
+    }
+
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let client = client_ip.map(|ip| ip.to_string());
+
+    // Every request counts against the key and the address, whether the user exists or not
+    crate::utils::login::verify_login_lockout(conn, &LoginCategory::ResetUser, &user_key)?;
+    if let Some(ip) = &client {
+        crate::utils::login::verify_login_lockout(conn, &LoginCategory::ResetIp, ip)?;
+    }
+
+    crate::utils::login::register_login_failure(conn, &LoginCategory::ResetUser, &user_key)?;
+    if let Some(ip) = &client {
+        crate::utils::login::register_login_failure(conn, &LoginCategory::ResetIp, ip)?;
+    }
+
+    // Looking up the user and sending the mail happen off the request path, so the response time reveals nothing
+    rocket::tokio::task::spawn_blocking(move || {
+        if let Err(e) = crate::utils::login::password_reset_send(&user_key) {
+            log::error!("Password reset for {} not sent: {:?}", user_key, e);
+        }
+    });
+
+    Ok(())
+}
+
+#[rocket::post("/anon/password_reset_confirm", format = "application/json", data = "<credit>")]
+pub fn password_reset_confirm(credit: Json<Credential>) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+
+    let token = credit.token.as_ref().ok_or(ErrorKind::PasswordResetInvalid)?;
+    let token_hash = crate::common::hash_sha256(token.as_bytes(), &[]);
+    let user_id = match crate::db::user::password_reset_user(conn, &token_hash)? {
+        None => return Err(ErrorKind::PasswordResetInvalid),
+        Some(user_id) => user_id,
+    };
+
+    let (hash, salt) = match (&credit.password, &credit.salt) {
+        (Some(p), Some(s)) => (p, s),
+        _ => return Err(ErrorKind::UserPasswordInvalid),
+    };
+
+    match crate::db::user::user_password_info(conn, user_id)? {
+        None => crate::db::user::user_password_create(conn, user_id, hash, salt)?,
+        Some(..) => crate::db::user::user_password_edit(conn, user_id, hash, salt)?,
+    };
+
+    // The token is spent and sessions opened with the old password are revoked
+    crate::db::user::password_reset_delete(conn, user_id)?;
+    crate::db::session::user_session_delete(conn, user_id)?;
+
+    let user = crate::db::user::user_info(conn, user_id)?;
+    if let Some(user_key) = &user.key {
+        crate::utils::login::reset_login_failure(conn, &LoginCategory::User, user_key)?;
+    }
+
+    // Holding the token proves the identity of the user, who is recorded as the actor
+    crate::db::audit::audit_create(
+        conn,
+        Some(user_id),
+        None,
+        "/anon/password_reset_confirm",
+        "user_credential",
+        user_id,
+        None,
+    )?;
+    Ok(())
+}
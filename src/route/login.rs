@@ -1,11 +1,15 @@
+use rocket::http::{Cookie, CookieJar, SameSite};
 use rocket::serde::json::Json;
+use std::net::IpAddr;
 
-use crate::common::{Credential, Right};
+use crate::common::{Credential, LoginCategory, TotpInfo};
 use crate::error::{ErrorKind, Result};
-use crate::session::{EventSession, UserSession, ADMINSESSION, EVENTSESSIONS, USERSESSIONS};
+use crate::session::UserSession;
+
+const OIDC_STATE_COOKIE: &str = "cpt_oidc_state";
 
 #[rocket::post("/user_login", format = "application/json", data = "<credit>")]
-pub fn user_login(credit: Json<Credential>) -> Result<String> {
+pub fn user_login(credit: Json<Credential>, client_ip: Option<IpAddr>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
 
     let user_key: &str = match &credit.login {
@@ -13,42 +17,61 @@ pub fn user_login(credit: Json<Credential>) -> Result<String> {
         None => return Err(ErrorKind::UserKeyMissing),
     };
 
-    // If the user is a preconfigured admin, return him an admin session
-    if ADMINSESSION.lock().unwrap().as_deref() == Some(user_key) {
-        let user_id = match crate::db::user::user_created_true(conn, user_key)? {
-            Some(id) => id,
-            None => return Err(ErrorKind::UserMissing),
-        };
-        let user = crate::db::user::user_info(conn, user_id)?;
-        let adminsession = UserSession::admin(&user);
-        let token = crate::common::random_string(30);
-        USERSESSIONS.lock().unwrap().insert(token.clone(), adminsession);
-        return Ok(token);
-    }
+    // Clean up sessions that ran out in the meantime
+    crate::db::session::session_expired_delete(conn)?;
 
     let user_hash: Vec<u8> = match &credit.password {
         Some(hash_string) => crate::common::decode_hash256(hash_string)?,
         None => return Err(ErrorKind::UserPasswordMissing),
     };
 
-    let user = crate::db::login::user_login(conn, user_key, &user_hash)?;
-    let session_token: String = crate::common::random_string(30);
-    let session_expiry = chrono::Utc::now() + crate::config::SESSION_DURATION();
+    let client = client_ip.map(|ip| ip.to_string());
 
-    let user_right: Right = crate::db::login::user_right(conn, user.id)?;
-    let session: UserSession = UserSession {
-        expiry: session_expiry,
-        user,
-        right: user_right,
+    crate::utils::login::verify_login_lockout(conn, &LoginCategory::User, user_key)?;
+    if let Some(ip) = &client {
+        crate::utils::login::verify_login_lockout(conn, &LoginCategory::Ip, ip)?;
+    }
+
+    let user = match crate::db::login::user_login(conn, user_key, &user_hash) {
+        Err(e @ (ErrorKind::UserLoginFail | ErrorKind::UserMissing)) => {
+            crate::utils::login::register_login_failure(conn, &LoginCategory::User, user_key)?;
+            if let Some(ip) = &client {
+                crate::utils::login::register_login_failure(conn, &LoginCategory::Ip, ip)?;
+            }
+            return Err(e);
+        }
+        result => result?,
     };
 
-    USERSESSIONS.lock().unwrap().insert(session_token.clone(), session);
+    // Second login step for users with confirmed TOTP
+    if let Some(TotpInfo {
+        secret,
+        enabled: true,
+        step: last_step,
+    }) = crate::db::user::user_totp_info(conn, user.id)?
+    {
+        let code = match &credit.code {
+            None => return Err(ErrorKind::TotpRequired),
+            Some(code) => code,
+        };
 
-    Ok(session_token)
+        if let Err(e) = crate::utils::totp::verify_second_factor(conn, user.id, &secret, last_step, code) {
+            crate::utils::login::register_login_failure(conn, &LoginCategory::User, user_key)?;
+            if let Some(ip) = &client {
+                crate::utils::login::register_login_failure(conn, &LoginCategory::Ip, ip)?;
+            }
+            return Err(e);
+        }
+    }
+
+    crate::utils::login::reset_login_failure(conn, &LoginCategory::User, user_key)?;
+
+    // Users without TOTP have no second factor to give, so the session holds all their rights
+    crate::utils::session::user_session_open(conn, user.id, true, client)
 }
 
 #[rocket::post("/event_login", format = "application/json", data = "<credit>")]
-pub fn event_login(credit: Json<Credential>) -> Result<String> {
+pub fn event_login(credit: Json<Credential>, client_ip: Option<IpAddr>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
 
     let event_key = match &credit.login {
@@ -71,60 +94,123 @@ pub fn event_login(credit: Json<Credential>) -> Result<String> {
         }
     };
 
-    println!("Event {} login attempt with password {}", event_key, event_pwd);
-
-    let (event_id, event_pwd_check) = crate::db::login::event_login(conn, event_key)?;
-
-    if *event_pwd != event_pwd_check {
-        return Err(ErrorKind::EventLoginFail);
-    };
+    let client = client_ip.map(|ip| ip.to_string());
 
-    let session_token: String = crate::common::random_string(30);
-    let session_expiry = chrono::Utc::now() + crate::config::SESSION_DURATION();
+    crate::utils::login::verify_login_lockout(conn, &LoginCategory::Event, event_key)?;
+    if let Some(ip) = &client {
+        crate::utils::login::verify_login_lockout(conn, &LoginCategory::Ip, ip)?;
+    }
 
-    let session: EventSession = EventSession {
-        token: session_token.to_string(),
-        expiry: session_expiry,
-        event_id,
+    let event_id = match crate::db::login::event_login(conn, event_key, event_pwd) {
+        Err(e @ (ErrorKind::EventLoginFail | ErrorKind::EventMissing)) => {
+            crate::utils::login::register_login_failure(conn, &LoginCategory::Event, event_key)?;
+            if let Some(ip) = &client {
+                crate::utils::login::register_login_failure(conn, &LoginCategory::Ip, ip)?;
+            }
+            return Err(e);
+        }
+        result => result?,
     };
+    crate::utils::login::reset_login_failure(conn, &LoginCategory::Event, event_key)?;
 
-    EVENTSESSIONS.lock().unwrap().insert(session_token.to_string(), session);
-
-    Ok(session_token)
+    crate::utils::session::event_session_open(conn, event_id, client)
 }
 
 #[rocket::get("/course_login?<course_key>")]
-pub fn course_login(course_key: String) -> Result<String> {
+pub fn course_login(course_key: String, client_ip: Option<IpAddr>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
     let begin = (chrono::Utc::now() - crate::config::EVENT_LOGIN_BUFFER()).naive_utc();
     let end = (chrono::Utc::now() + crate::config::EVENT_LOGIN_BUFFER()).naive_utc();
-    let (event_key, event_pwd) = crate::db::login::course_current_event(conn, &course_key, &begin, &end)?;
-
-    let credentials = Credential {
-        id: None,
-        login: Some(event_key),
-        password: Some(event_pwd),
-        salt: None,
-        since: None,
-    };
+    let event_id = crate::db::login::course_current_event(conn, &course_key, &begin, &end)?;
 
-    event_login(Json(credentials))
+    crate::utils::session::event_session_open(conn, event_id, client_ip.map(|ip| ip.to_string()))
 }
 
 #[rocket::get("/location_login?<location_key>")]
-pub fn location_login(location_key: String) -> Result<String> {
+pub fn location_login(location_key: String, client_ip: Option<IpAddr>) -> Result<String> {
     let conn = &mut crate::utils::db::get_db_conn()?;
     let begin = (chrono::Utc::now() - crate::config::EVENT_LOGIN_BUFFER()).naive_utc();
     let end = (chrono::Utc::now() + crate::config::EVENT_LOGIN_BUFFER()).naive_utc();
-    let (event_key, event_pwd) = crate::db::login::location_current_event(conn, &location_key, &begin, &end)?;
-
-    let credentials = Credential {
-        id: None,
-        login: Some(event_key),
-        password: Some(event_pwd),
-        salt: None,
-        since: None,
+    let event_id = crate::db::login::location_current_event(conn, &location_key, &begin, &end)?;
+
+    crate::utils::session::event_session_open(conn, event_id, client_ip.map(|ip| ip.to_string()))
+}
+
+#[rocket::get("/oidc_login")]
+pub fn oidc_login(cookies: &CookieJar<'_>) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let provider = crate::utils::oidc::oidc_provider()?;
+
+    let state = crate::common::random_string(30);
+    let verifier = crate::common::random_string(64);
+    let expiry = chrono::Utc::now() + chrono::Duration::minutes(10);
+    crate::db::login::oidc_state_create(conn, &state, &verifier, &expiry.naive_utc())?;
+
+    // The callback is only accepted from the browser which started the login
+    let cookie = Cookie::build((OIDC_STATE_COOKIE, state.clone()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax);
+    cookies.add(cookie);
+
+    provider.authorization_url(&state, &verifier)
+}
+
+#[rocket::get("/oidc_callback?<code>&<state>")]
+pub fn oidc_callback(
+    code: String,
+    state: String,
+    cookies: &CookieJar<'_>,
+    client_ip: Option<IpAddr>,
+) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let provider = crate::utils::oidc::oidc_provider()?;
+
+    let cookie_state = cookies.get(OIDC_STATE_COOKIE).map(|cookie| cookie.value().to_string());
+    cookies.remove(Cookie::from(OIDC_STATE_COOKIE));
+
+    match cookie_state {
+        Some(cookie_state) if crate::common::digest_eq(cookie_state.as_bytes(), state.as_bytes()) => (),
+        _ => return Err(ErrorKind::OidcStateInvalid),
     };
 
-    event_login(Json(credentials))
+    let verifier = match crate::db::login::oidc_state_take(conn, &state)? {
+        None => return Err(ErrorKind::OidcStateInvalid),
+        Some(verifier) => verifier,
+    };
+
+    let info = provider.user_info(&code, &verifier)?;
+
+    let user = match crate::config::OIDC_CLAIM() {
+        "email" => match (&info.email, info.email_verified) {
+            (Some(email), Some(true)) => crate::db::login::oidc_login(conn, None, Some(email))?,
+            _ => return Err(ErrorKind::UserMissing),
+        },
+        _ => crate::db::login::oidc_login(conn, Some(&info.sub), None)?,
+    };
+
+    // The identity provider does not vouch for the TOTP of the user, so permissions requiring it are left out
+    crate::utils::session::user_session_open(conn, user.id, false, client_ip.map(|ip| ip.to_string()))
+}
+
+#[rocket::get("/session_refresh")]
+pub fn session_refresh(session: UserSession) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+
+    session.deny_api_token()?;
+
+    let session_token: String = crate::common::random_string(30);
+    let session_expiry = crate::utils::session::session_expiry(session.created);
+
+    crate::db::session::session_refresh(conn, &session.token, &session_token, &session_expiry.naive_utc())?;
+    Ok(session_token)
+}
+
+#[rocket::head("/logout")]
+pub fn logout(session: UserSession) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+
+    session.deny_api_token()?;
+    crate::db::session::session_delete(conn, &session.token)?;
+    Ok(())
 }
@@ -1,4 +1,5 @@
 pub mod admin {
+    pub mod audit;
     pub mod club;
     pub mod competence;
     pub mod course;
@@ -21,11 +22,13 @@ pub mod moderator {
 }
 
 pub mod regular {
+    pub mod api_token;
     pub mod competence;
     pub mod course;
     pub mod event;
     pub mod inventory;
     pub mod team;
+    pub mod totp;
     pub mod user;
 }
 
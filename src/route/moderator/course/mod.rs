@@ -38,8 +38,18 @@ pub fn course_moderator_add(session: UserSession, course_id: u32, user_id: u64)
         return Err(ErrorKind::CourseModeratorPermission);
     };
 
-    crate::db::course::moderator::course_moderator_add(conn, course_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::moderator::course_moderator_add(conn, course_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_moderator",
+            course_id as u64,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/mod/course_moderator_remove?<course_id>&<user_id>")]
@@ -49,6 +59,16 @@ pub fn course_moderator_remove(session: UserSession, course_id: u32, user_id: u6
         return Err(ErrorKind::CourseModeratorPermission);
     };
 
-    crate::db::course::moderator::course_moderator_remove(conn, course_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::course::moderator::course_moderator_remove(conn, course_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "course_moderator",
+            course_id as u64,
+            Some(&serde_json::json!({ "user_id": user_id })),
+            None,
+        )?;
+        Ok(())
+    })
 }
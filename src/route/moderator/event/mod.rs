@@ -1,3 +1,5 @@
+pub mod series;
+
 use rocket::serde::json::Json;
 
 use crate::common::{Acceptance, Event};
@@ -11,7 +13,18 @@ pub fn event_list(session: UserSession, course_id: u32) -> Result<Json<Vec<Event
         return Err(ErrorKind::CourseModeratorPermission);
     };
 
-    let events = crate::db::event::event_list(conn, None, None, None, None, None, Some(true), Some(course_id), None)?;
+    let events = crate::db::event::event_list(
+        conn,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(true),
+        Some(course_id),
+        None,
+        None,
+    )?;
     Ok(Json(events))
 }
 
@@ -24,8 +37,12 @@ pub fn event_create(session: UserSession, course_id: u32, mut event: Json<Event>
 
     crate::utils::event::validate_event_dates(&mut event)?;
 
-    let id = crate::db::event::event_create(conn, &event, &Acceptance::Accepted, Some(course_id))?;
-    Ok(id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let id = crate::db::event::event_create(conn, &event, &Acceptance::Accepted, Some(course_id))?;
+        let after = crate::db::event::event_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "event", id, None, Some(&after))?;
+        Ok(id.to_string())
+    })
 }
 
 #[rocket::post("/mod/event_edit?<event_id>", format = "application/json", data = "<event>")]
@@ -37,8 +54,21 @@ pub fn event_edit(session: UserSession, event_id: u64, mut event: Json<Event>) -
 
     crate::utils::event::validate_event_dates(&mut event)?;
 
-    crate::db::event::event_edit(conn, event_id, &event)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+
+        // Moderators cannot override conflicts of accepted events
+        event.id = event_id;
+        event.acceptance = before.acceptance.clone();
+        if event.claims_location() && !crate::db::event::event_free_true(conn, &event)? {
+            return Err(ErrorKind::EventWindowConflict);
+        }
+
+        crate::db::event::event_edit(conn, event_id, &event)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::post("/mod/event_edit_password?<event_id>", format = "text/plain", data = "<password>")]
@@ -48,9 +78,12 @@ pub fn event_edit_password(session: UserSession, event_id: u64, password: String
         return Err(ErrorKind::CourseModeratorPermission);
     };
 
-    let password = crate::utils::event::validate_clear_password(password)?;
-    crate::db::event::event_password_edit(conn, event_id, password)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let password = crate::utils::event::validate_clear_password(password)?;
+        crate::db::event::event_password_edit(conn, event_id, &password)?;
+        crate::utils::audit::audit_action(conn, &session, "event_password", event_id)?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/mod/event_delete?<event_id>")]
@@ -60,6 +93,10 @@ pub fn event_delete(session: UserSession, event_id: u64) -> Result<()> {
         return Err(ErrorKind::CourseModeratorPermission);
     };
 
-    crate::db::event::event_delete(conn, event_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_delete(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), None)?;
+        Ok(())
+    })
 }
@@ -0,0 +1,103 @@
+use rocket::serde::json::Json;
+
+use crate::common::{Acceptance, EventSeries, SeriesScope};
+use crate::error::{ErrorKind, Result};
+use crate::session::UserSession;
+
+#[rocket::get("/mod/event_series_list?<course_id>")]
+pub fn series_list(session: UserSession, course_id: u32) -> Result<Json<Vec<EventSeries>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !crate::db::course::moderator::course_moderator_true(conn, course_id, session.user.id)? {
+        return Err(ErrorKind::CourseModeratorPermission);
+    };
+
+    let series = crate::db::event::series::event_series_list(conn, course_id)?;
+    Ok(Json(series))
+}
+
+#[rocket::post(
+    "/mod/event_series_create?<course_id>",
+    format = "application/json",
+    data = "<series>"
+)]
+pub fn series_create(session: UserSession, course_id: u32, mut series: Json<EventSeries>) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !crate::db::course::moderator::course_moderator_true(conn, course_id, session.user.id)? {
+        return Err(ErrorKind::CourseModeratorPermission);
+    };
+
+    crate::db::transaction(conn, |conn| {
+        let id = crate::utils::event_series::event_series_create(conn, course_id, &mut series, &Acceptance::Accepted)?;
+        let after = crate::db::event::series::event_series_info(conn, id)?;
+        crate::utils::audit::audit(conn, &session, "event_series", id as u64, None, Some(&after))?;
+        Ok(id.to_string())
+    })
+}
+
+#[rocket::post(
+    "/mod/event_series_edit?<series_id>&<event_id>&<scope>",
+    format = "application/json",
+    data = "<series>"
+)]
+pub fn series_edit(
+    session: UserSession,
+    series_id: u32,
+    event_id: Option<u64>,
+    scope: SeriesScope,
+    mut series: Json<EventSeries>,
+) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let before = crate::db::event::series::event_series_info(conn, series_id)?;
+    if !crate::db::course::moderator::course_moderator_true(conn, before.course_id, session.user.id)? {
+        return Err(ErrorKind::CourseModeratorPermission);
+    };
+
+    crate::db::transaction(conn, |conn| {
+        let id = crate::utils::event_series::event_series_edit(
+            conn,
+            series_id,
+            event_id,
+            &scope,
+            &mut series,
+            &Acceptance::Accepted,
+        )?;
+        let after = crate::db::event::series::event_series_info(conn, series_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_series",
+            series_id as u64,
+            Some(&before),
+            Some(&after),
+        )?;
+
+        if id != series_id {
+            let split = crate::db::event::series::event_series_info(conn, id)?;
+            crate::utils::audit::audit(conn, &session, "event_series", id as u64, None, Some(&split))?;
+        }
+        Ok(id.to_string())
+    })
+}
+
+#[rocket::head("/mod/event_series_delete?<series_id>&<event_id>&<scope>")]
+pub fn series_delete(session: UserSession, series_id: u32, event_id: Option<u64>, scope: SeriesScope) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let before = crate::db::event::series::event_series_info(conn, series_id)?;
+    if !crate::db::course::moderator::course_moderator_true(conn, before.course_id, session.user.id)? {
+        return Err(ErrorKind::CourseModeratorPermission);
+    };
+
+    crate::db::transaction(conn, |conn| {
+        crate::utils::event_series::event_series_delete(conn, series_id, event_id, &scope)?;
+        let after = crate::db::event::series::event_series_info(conn, series_id).ok();
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_series",
+            series_id as u64,
+            Some(&before),
+            after.as_ref(),
+        )?;
+        Ok(())
+    })
+}
@@ -32,8 +32,18 @@ pub fn filter_edit(session: UserSession, event_id: u64, user_id: u64, role: Stri
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    crate::db::event::attendance::event_attendance_filter_edit(conn, event_id, user_id, role, access)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_filter_edit(conn, event_id, user_id, role.clone(), access)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_filter",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role, "access": access })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/owner/event_attendance_filter_remove?<event_id>&<user_id>&<role>")]
@@ -43,8 +53,18 @@ pub fn filter_remove(session: UserSession, event_id: u64, user_id: u64, role: St
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    crate::db::event::attendance::event_attendance_filter_remove(conn, event_id, user_id, role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_filter_remove(conn, event_id, user_id, role.clone())?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_filter",
+            event_id,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+            None,
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/owner/event_attendance_presence_pool?<event_id>&<role>")]
@@ -76,14 +96,24 @@ pub fn presence_add(session: UserSession, event_id: u64, user_id: u64, role: Str
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    let pool = crate::db::event::attendance::event_attendance_presence_pool(conn, event_id, &role, true)?;
-
-    if !pool.iter().any(|user| user.id == user_id) {
-        return Err(ErrorKind::EventPresenceForbidden);
-    }
-
-    crate::db::event::attendance::event_attendance_presence_add(conn, event_id, user_id, &role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let pool = crate::db::event::attendance::event_attendance_presence_pool(conn, event_id, &role, true)?;
+
+        if !pool.iter().any(|user| user.id == user_id) {
+            return Err(ErrorKind::EventPresenceForbidden);
+        }
+
+        crate::db::event::attendance::event_attendance_presence_add(conn, event_id, user_id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/owner/event_attendance_presence_remove?<event_id>&<user_id>&<role>")]
@@ -93,6 +123,16 @@ pub fn presence_remove(session: UserSession, event_id: u64, user_id: u64, role:
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    crate::db::event::attendance::event_attendance_presence_remove(conn, event_id, user_id, &role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_presence_remove(conn, event_id, user_id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            event_id,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+            None,
+        )?;
+        Ok(())
+    })
 }
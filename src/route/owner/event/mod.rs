@@ -1,7 +1,7 @@
 pub mod attendance;
 pub mod owner;
 
-use crate::common::{Acceptance, Course, Event, Occurrence, WebDateTime};
+use crate::common::{Acceptance, Course, Credential, Event, Occurrence, WebDateTime};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 use rocket::serde::json::Json;
@@ -31,6 +31,7 @@ pub fn event_list(
         Some(false),
         None,
         Some(session.user.id),
+        None,
     )?;
     Ok(Json(events))
 }
@@ -55,9 +56,14 @@ pub fn event_edit(session: UserSession, event_id: u64, mut event: Json<Event>) -
 
     crate::utils::event::validate_event_dates(&mut event)?;
 
-    crate::db::event::event_edit(conn, event_id, &event)?;
-    crate::db::event::event_acceptance_edit(conn, event.id, &Acceptance::Draft)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_edit(conn, event_id, &event)?;
+        crate::db::event::event_acceptance_edit(conn, event.id, &Acceptance::Draft)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::post("/owner/event_password_edit?<event_id>", format = "text/plain", data = "<password>")]
@@ -67,9 +73,26 @@ pub fn event_password_edit(session: UserSession, event_id: u64, password: String
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    let password = crate::utils::event::validate_clear_password(password)?;
-    crate::db::event::event_password_edit(conn, event_id, password)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let password = crate::utils::event::validate_clear_password(password)?;
+        crate::db::event::event_password_edit(conn, event_id, &password)?;
+        crate::utils::audit::audit_action(conn, &session, "event_password", event_id)?;
+        Ok(())
+    })
+}
+
+#[rocket::post("/owner/event_password_rotate?<event_id>")]
+pub fn event_password_rotate(session: UserSession, event_id: u64) -> Result<Json<Credential>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    if !crate::db::event::owner::event_owner_true(conn, event_id, session.user.id)? {
+        return Err(ErrorKind::EventOwnerPermission);
+    };
+
+    crate::db::transaction(conn, |conn| {
+        let credential = crate::utils::event::rotate_event_password(conn, event_id)?;
+        crate::utils::audit::audit_action(conn, &session, "event_password", event_id)?;
+        Ok(Json(credential))
+    })
 }
 
 #[rocket::get("/owner/event_course_info?<event_id>")]
@@ -90,20 +113,32 @@ pub fn event_course_edit(session: UserSession, event_id: u64, course_id: Option<
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    if let Some(old_course) = crate::db::event::event_course_info(conn, event_id)? {
-        if !crate::db::course::moderator::course_moderator_true(conn, old_course.id, session.user.id)? {
-            return Err(ErrorKind::CourseModeratorPermission);
+    crate::db::transaction(conn, |conn| {
+        if let Some(old_course) = crate::db::event::event_course_info(conn, event_id)? {
+            if !crate::db::course::moderator::course_moderator_true(conn, old_course.id, session.user.id)? {
+                return Err(ErrorKind::CourseModeratorPermission);
+            };
         };
-    };
 
-    if let Some(new_course_id) = course_id {
-        if !crate::db::course::moderator::course_moderator_true(conn, new_course_id, session.user.id)? {
-            return Err(ErrorKind::CourseModeratorPermission);
+        if let Some(new_course_id) = course_id {
+            if !crate::db::course::moderator::course_moderator_true(conn, new_course_id, session.user.id)? {
+                return Err(ErrorKind::CourseModeratorPermission);
+            };
         };
-    };
 
-    crate::db::event::event_course_edit(conn, event_id, course_id)?;
-    Ok(())
+        let before = crate::db::event::event_course_info(conn, event_id)?;
+        crate::db::event::event_course_edit(conn, event_id, course_id)?;
+        let after = crate::db::event::event_course_info(conn, event_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_course",
+            event_id,
+            before.as_ref(),
+            after.as_ref(),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/owner/event_submit?<event_id>")]
@@ -113,25 +148,29 @@ pub fn event_submit(session: UserSession, event_id: u64) -> Result<()> {
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    let event: Event = crate::db::event::event_info(conn, event_id)?;
+    crate::db::transaction(conn, |conn| {
+        let event: Event = crate::db::event::event_info(conn, event_id)?;
 
-    // The check is here intentional to be able to return early although it is also checked during is_event_free
-    if !crate::utils::event::is_event_valid(&event) {
-        return Err(ErrorKind::EventWindowInvalid);
-    }
+        // The check is here intentional to be able to return early although it is also checked during is_event_free
+        if !crate::utils::event::is_event_valid(&event) {
+            return Err(ErrorKind::EventWindowInvalid);
+        }
 
-    let is_free: bool = crate::db::event::event_free_true(conn, &event)?;
+        let is_free: bool = crate::db::event::event_free_true(conn, &event)?;
 
-    let acceptance = match crate::config::EVENT_ACCEPTENCE_AUTO() {
-        false => Acceptance::Pending,
-        true => match is_free {
-            true => Acceptance::Accepted,
-            false => Acceptance::Rejected,
-        },
-    };
+        let acceptance = match crate::config::EVENT_ACCEPTENCE_AUTO() {
+            false => Acceptance::Pending,
+            true => match is_free {
+                true => Acceptance::Accepted,
+                false => Acceptance::Rejected,
+            },
+        };
 
-    crate::db::event::event_acceptance_edit(conn, event.id, &acceptance)?;
-    Ok(())
+        crate::db::event::event_acceptance_edit(conn, event.id, &acceptance)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&event), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/owner/event_withdraw?<event_id>")]
@@ -141,8 +180,13 @@ pub fn event_withdraw(session: UserSession, event_id: u64) -> Result<()> {
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Draft)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_acceptance_edit(conn, event_id, &Acceptance::Draft)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/owner/event_delete?<event_id>")]
@@ -152,6 +196,10 @@ pub fn event_delete(session: UserSession, event_id: u64) -> Result<()> {
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    crate::db::event::event_delete(conn, event_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, event_id)?;
+        crate::db::event::event_delete(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, Some(&before), None)?;
+        Ok(())
+    })
 }
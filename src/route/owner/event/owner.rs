@@ -21,8 +21,18 @@ pub fn event_owner_add(session: UserSession, event_id: u64, user_id: u64) -> Res
         return Err(ErrorKind::EventOwnerPermission);
     };
 
-    crate::db::event::owner::event_owner_add(conn, event_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::owner::event_owner_add(conn, event_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_owner",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/owner/event_owner_remove?<event_id>&<user_id>")]
@@ -36,6 +46,16 @@ pub fn event_owner_remove(session: UserSession, event_id: u64, user_id: u64) ->
         return Err(ErrorKind::EventOwnerProtection);
     };
 
-    crate::db::event::owner::event_owner_remove(conn, event_id, user_id)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::owner::event_owner_remove(conn, event_id, user_id)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_owner",
+            event_id,
+            Some(&serde_json::json!({ "user_id": user_id })),
+            None,
+        )?;
+        Ok(())
+    })
 }
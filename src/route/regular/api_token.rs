@@ -0,0 +1,71 @@
+use rocket::serde::json::Json;
+
+use crate::common::{ApiToken, Right};
+use crate::error::{ErrorKind, Result};
+use crate::session::{UserSession, API_TOKEN_PREFIX};
+
+/*
+ * ROUTES
+ */
+
+#[rocket::get("/regular/api_token_list")]
+pub fn api_token_list(session: UserSession) -> Result<Json<Vec<ApiToken>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let tokens = crate::db::api_token::api_token_list(conn, session.user.id)?;
+    Ok(Json(tokens))
+}
+
+#[rocket::post(
+    "/regular/api_token_create?<name>&<expiry_days>",
+    format = "application/json",
+    data = "<right>"
+)]
+pub fn api_token_create(
+    session: UserSession,
+    name: String,
+    expiry_days: Option<u16>,
+    right: Json<Right>,
+) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+
+    // Tokens must not be able to extend their own lifetime
+    session.deny_api_token()?;
+
+    if name.is_empty() || name.len() > 40 {
+        return Err(ErrorKind::ApiTokenNameInvalid);
+    }
+
+    if !session.right.contains(&right) {
+        return Err(ErrorKind::ApiTokenScopeInvalid);
+    }
+
+    let token = format!("{}{}", API_TOKEN_PREFIX, crate::common::random_string(40));
+    let token_hash = crate::common::hash_sha256(token.as_bytes(), &[]);
+    let expiry = expiry_days.map(|days| (chrono::Utc::now() + chrono::Duration::days(days as i64)).naive_utc());
+
+    crate::db::transaction(conn, |conn| {
+        let token_id =
+            crate::db::api_token::api_token_create(conn, session.user.id, &name, &token_hash, &right, expiry)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "api_token",
+            token_id as u64,
+            None,
+            Some(&serde_json::json!({ "name": name, "expiry": expiry, "right": &*right })),
+        )?;
+        Ok(token)
+    })
+}
+
+#[rocket::head("/regular/api_token_revoke?<token_id>")]
+pub fn api_token_revoke(session: UserSession, token_id: u32) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::api_token::api_token_delete(conn, session.user.id, token_id)?;
+        crate::utils::audit::audit_action(conn, &session, "api_token", token_id as u64)?;
+        Ok(())
+    })
+}
@@ -34,6 +34,7 @@ pub fn event_list(
         course_true.map(|b| b.to_bool()),
         course_id,
         None,
+        None,
     )?;
     Ok(Json(events))
 }
@@ -43,9 +44,13 @@ pub fn event_create(session: UserSession, mut event: Json<Event>) -> Result<Stri
     crate::utils::event::validate_event_dates(&mut event)?;
     let conn = &mut crate::utils::db::get_db_conn()?;
 
-    let event_id = crate::db::event::event_create(conn, &event, &Acceptance::Draft, None)?;
-    crate::db::event::owner::event_owner_add(conn, event_id, session.user.id)?;
-    Ok(event_id.to_string())
+    crate::db::transaction(conn, |conn| {
+        let event_id = crate::db::event::event_create(conn, &event, &Acceptance::Draft, None)?;
+        crate::db::event::owner::event_owner_add(conn, event_id, session.user.id)?;
+        let after = crate::db::event::event_info(conn, event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", event_id, None, Some(&after))?;
+        Ok(event_id.to_string())
+    })
 }
 
 #[rocket::get("/regular/event_owner_true?<event_id>")]
@@ -79,15 +84,36 @@ pub fn event_attendance_presence_add(session: UserSession, event_id: u64, role:
         return Err(ErrorKind::EventPresenceForbidden);
     }
 
-    crate::db::event::attendance::event_attendance_presence_add(conn, event_id, session.user.id, &role)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_presence_add(conn, event_id, session.user.id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": session.user.id, "role": role })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/regular/event_attendance_presence_remove?<event_id>&<role>")]
 pub fn event_attendance_presence_remove(session: UserSession, event_id: u64, role: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    crate::db::event::attendance::event_attendance_presence_remove(conn, event_id, session.user.id, &role)?;
-    Ok(())
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_presence_remove(conn, event_id, session.user.id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            event_id,
+            Some(&serde_json::json!({ "user_id": session.user.id, "role": role })),
+            None,
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/regular/event_bookmark_true?<event_id>")]
@@ -104,11 +130,22 @@ pub fn event_bookmark_edit(session: UserSession, event_id: u64, bookmark: bool)
     let conn = &mut crate::utils::db::get_db_conn()?;
     // TODO check if you can participate
 
-    match bookmark {
-        true => crate::db::event::event_bookmark_add(conn, event_id, session.user.id)?,
-        false => crate::db::event::event_bookmark_remove(conn, event_id, session.user.id)?,
-    }
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        match bookmark {
+            true => crate::db::event::event_bookmark_add(conn, event_id, session.user.id)?,
+            false => crate::db::event::event_bookmark_remove(conn, event_id, session.user.id)?,
+        }
+
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_bookmark",
+            event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": session.user.id, "bookmark": bookmark })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/regular/event_attendance_registration_info?<event_id>&<role>")]
@@ -131,17 +168,55 @@ pub fn event_attendance_registration_edit(
     let conn = &mut crate::utils::db::get_db_conn()?;
     // TODO check if you can register (requirement)
 
-    match status {
-        Confirmation::Null => {
-            crate::db::event::attendance::event_attendance_registration_remove(conn, event_id, session.user.id, role)?
-        }
-        _ => crate::db::event::attendance::event_attendance_registration_edit(
+    crate::db::transaction(conn, |conn| {
+        // Concurrent registrations wait for the lock on the event, so that the count stays valid until the write
+        crate::db::transaction(conn, |conn| {
+            crate::db::event::event_lock(conn, event_id)?;
+
+            if status == Confirmation::Positive {
+                let event = crate::db::event::event_info(conn, event_id)?;
+                let capacity = event
+                    .location
+                    .as_ref()
+                    .and_then(|location| location.event_capacity(event.areas));
+
+                if let Some(capacity) = capacity {
+                    let count = crate::db::event::attendance::event_attendance_registration_count(
+                        conn,
+                        event_id,
+                        session.user.id,
+                    )?;
+                    if count >= capacity {
+                        return Err(ErrorKind::EventCapacityReached);
+                    }
+                }
+            }
+
+            match status {
+                Confirmation::Null => crate::db::event::attendance::event_attendance_registration_remove(
+                    conn,
+                    event_id,
+                    session.user.id,
+                    role.clone(),
+                ),
+                _ => crate::db::event::attendance::event_attendance_registration_edit(
+                    conn,
+                    event_id,
+                    session.user.id,
+                    role.clone(),
+                    status.clone(),
+                ),
+            }
+        })?;
+
+        crate::utils::audit::audit(
             conn,
+            &session,
+            "event_registration",
             event_id,
-            session.user.id,
-            role,
-            status,
-        )?,
-    }
-    Ok(())
+            None,
+            Some(&serde_json::json!({ "user_id": session.user.id, "role": role, "status": status.to_string() })),
+        )?;
+        Ok(())
+    })
 }
@@ -0,0 +1,98 @@
+use rocket::serde::json::Json;
+
+use crate::common::{Credential, TotpInfo};
+use crate::error::{ErrorKind, Result};
+use crate::session::UserSession;
+
+/*
+ * ROUTES
+ */
+
+#[rocket::post("/regular/totp_enrol")]
+pub fn totp_enrol(session: UserSession) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+
+    crate::db::transaction(conn, |conn| {
+        if let Some(TotpInfo { enabled: true, .. }) = crate::db::user::user_totp_info(conn, session.user.id)? {
+            return Err(ErrorKind::AlreadyExists);
+        }
+
+        let secret = crate::common::random_bytes(20);
+        crate::db::user::user_totp_create(conn, session.user.id, &secret)?;
+        crate::utils::audit::audit_action(conn, &session, "user_totp", session.user.id)?;
+
+        let user_key = session.user.key.unwrap_or_default();
+        Ok(crate::utils::totp::totp_uri(&user_key, &secret))
+    })
+}
+
+#[rocket::post("/regular/totp_confirm", format = "application/json", data = "<credit>")]
+pub fn totp_confirm(session: UserSession, credit: Json<Credential>) -> Result<Json<Vec<String>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+    let code = credit.code.as_ref().ok_or(ErrorKind::TotpInvalid)?;
+
+    crate::db::transaction(conn, |conn| {
+        let secret = match crate::db::user::user_totp_info(conn, session.user.id)? {
+            Some(TotpInfo { enabled: true, .. }) => return Err(ErrorKind::AlreadyExists),
+            Some(totp) => totp.secret,
+            None => return Err(ErrorKind::Missing),
+        };
+
+        let step = crate::common::totp_step(chrono::Utc::now());
+        let step = match crate::common::totp_verify(&secret, code, step) {
+            None => return Err(ErrorKind::TotpInvalid),
+            Some(step) => step,
+        };
+
+        crate::db::user::user_totp_enable(conn, session.user.id, step)?;
+        crate::utils::audit::audit_action(conn, &session, "user_totp", session.user.id)?;
+        let codes = crate::utils::totp::create_recovery_codes(conn, session.user.id)?;
+
+        crate::utils::session::refresh_user_rights(conn, session.user.id)?;
+        Ok(Json(codes))
+    })
+}
+
+#[rocket::post("/regular/totp_recovery_create", format = "application/json", data = "<credit>")]
+pub fn totp_recovery_create(session: UserSession, credit: Json<Credential>) -> Result<Json<Vec<String>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+    let code = credit.code.as_ref().ok_or(ErrorKind::TotpInvalid)?;
+
+    crate::db::transaction(conn, |conn| {
+        let totp = match crate::db::user::user_totp_info(conn, session.user.id)? {
+            Some(totp) if totp.enabled => totp,
+            _ => return Err(ErrorKind::Missing),
+        };
+
+        crate::utils::totp::verify_second_factor(conn, session.user.id, &totp.secret, totp.step, code)?;
+
+        let codes = crate::utils::totp::create_recovery_codes(conn, session.user.id)?;
+        crate::utils::audit::audit_action(conn, &session, "user_totp", session.user.id)?;
+        Ok(Json(codes))
+    })
+}
+
+#[rocket::post("/regular/totp_delete", format = "application/json", data = "<credit>")]
+pub fn totp_delete(session: UserSession, credit: Json<Credential>) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+
+    crate::db::transaction(conn, |conn| {
+        match crate::db::user::user_totp_info(conn, session.user.id)? {
+            Some(totp) if totp.enabled => {
+                let code = credit.code.as_ref().ok_or(ErrorKind::TotpInvalid)?;
+                crate::utils::totp::verify_second_factor(conn, session.user.id, &totp.secret, totp.step, code)?
+            }
+            Some(..) => (),
+            None => return Err(ErrorKind::Missing),
+        };
+
+        crate::db::user::user_totp_delete(conn, session.user.id)?;
+        crate::utils::audit::audit_action(conn, &session, "user_totp", session.user.id)?;
+        crate::utils::session::refresh_user_rights(conn, session.user.id)?;
+        Ok(())
+    })
+}
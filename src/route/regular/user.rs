@@ -1,6 +1,6 @@
 use rocket::serde::json::Json;
 
-use crate::common::{Credential, Right, User};
+use crate::common::{Credential, Right, Session, User, UserDataExport};
 use crate::error::{ErrorKind, Result};
 use crate::session::UserSession;
 
@@ -20,6 +20,50 @@ pub fn user_right(session: UserSession) -> Json<Right> {
     Json(session.right)
 }
 
+#[rocket::get("/regular/user_data_export")]
+pub fn user_data_export(session: UserSession) -> Result<Json<UserDataExport>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+    let export = crate::db::user::user_data_export(conn, session.user.id)?;
+    Ok(Json(export))
+}
+
+#[rocket::post("/regular/user_calendar_token_rotate")]
+pub fn user_calendar_token_rotate(session: UserSession) -> Result<String> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+
+    session.deny_api_token()?;
+
+    // The token is only revealed once, the previous one stops working
+    let token = crate::common::random_string(40);
+    let token_hash = crate::common::hash_sha256(token.as_bytes(), &[]);
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::feed_token_edit(conn, session.user.id, Some(&token_hash))?;
+        crate::utils::audit::audit_action(conn, &session, "user_feed_token", session.user.id)?;
+        Ok(token)
+    })
+}
+
+#[rocket::head("/regular/user_calendar_token_revoke")]
+pub fn user_calendar_token_revoke(session: UserSession) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::feed_token_edit(conn, session.user.id, None)?;
+        crate::utils::audit::audit_action(conn, &session, "user_feed_token", session.user.id)?;
+        Ok(())
+    })
+}
+
+#[rocket::get("/regular/session_list")]
+pub fn session_list(session: UserSession) -> Result<Json<Vec<Session>>> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+    let sessions = crate::db::session::user_session_list(conn, session.user.id)?;
+    Ok(Json(sessions))
+}
+
 #[rocket::get("/regular/user_password_info")]
 pub fn user_password_info(session: UserSession) -> Result<Json<Credential>> {
     let conn = &mut crate::utils::db::get_db_conn()?;
@@ -34,14 +78,18 @@ pub fn user_password_info(session: UserSession) -> Result<Json<Credential>> {
 #[rocket::post("/regular/user_password_edit", format = "application/json", data = "<credit>")]
 pub fn user_password_set(session: UserSession, credit: Json<Credential>) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
+    session.deny_api_token()?;
 
     let (hash, salt) = match (&credit.password, &credit.salt) {
         (Some(p), Some(s)) => (p, s),
         _ => return Err(ErrorKind::UserPasswordInvalid),
     };
 
-    crate::db::user::user_password_edit(conn, session.user.id, hash, salt)?;
-    Ok(())
+    crate::db::transaction(conn, |conn| {
+        crate::db::user::user_password_edit(conn, session.user.id, hash, salt)?;
+        crate::utils::audit::audit_action(conn, &session, "user_credential", session.user.id)?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/regular/user_list")]
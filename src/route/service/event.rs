@@ -13,8 +13,14 @@ pub fn event_info(session: EventSession) -> Result<Json<Event>> {
 #[rocket::post("/service/event_note_edit", format = "text/plain", data = "<note>")]
 pub fn event_note_edit(session: EventSession, note: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    crate::db::event::event_note_edit(conn, session.event_id, &note)?;
-    Ok(())
+
+    crate::db::transaction(conn, |conn| {
+        let before = crate::db::event::event_info(conn, session.event_id)?;
+        crate::db::event::event_note_edit(conn, session.event_id, &note)?;
+        let after = crate::db::event::event_info(conn, session.event_id)?;
+        crate::utils::audit::audit(conn, &session, "event", session.event_id, Some(&before), Some(&after))?;
+        Ok(())
+    })
 }
 
 #[rocket::get("/service/event_attendance_presence_pool?<role>")]
@@ -36,14 +42,37 @@ pub fn event_attendance_presence_add(session: EventSession, user_id: u64, role:
     let conn = &mut crate::utils::db::get_db_conn()?;
     let pool = crate::db::event::attendance::event_attendance_presence_pool(conn, session.event_id, &role, true)?;
 
-    if !pool.iter().any(|user| user.id == user_id) {
-        return Err(ErrorKind::EventPresenceForbidden);
-    }
-    crate::db::event::attendance::event_attendance_presence_add(conn, session.event_id, user_id, &role)
+    crate::db::transaction(conn, |conn| {
+        if !pool.iter().any(|user| user.id == user_id) {
+            return Err(ErrorKind::EventPresenceForbidden);
+        }
+        crate::db::event::attendance::event_attendance_presence_add(conn, session.event_id, user_id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            session.event_id,
+            None,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+        )?;
+        Ok(())
+    })
 }
 
 #[rocket::head("/service/event_attendance_presence_remove?<user_id>&<role>")]
 pub fn event_attendance_presence_remove(session: EventSession, user_id: u64, role: String) -> Result<()> {
     let conn = &mut crate::utils::db::get_db_conn()?;
-    crate::db::event::attendance::event_attendance_presence_remove(conn, session.event_id, user_id, &role)
+
+    crate::db::transaction(conn, |conn| {
+        crate::db::event::attendance::event_attendance_presence_remove(conn, session.event_id, user_id, &role)?;
+        crate::utils::audit::audit(
+            conn,
+            &session,
+            "event_presence",
+            session.event_id,
+            Some(&serde_json::json!({ "user_id": user_id, "role": role })),
+            None,
+        )?;
+        Ok(())
+    })
 }
@@ -1,19 +1,11 @@
-extern crate lazy_static;
-
 use rocket::outcome::Outcome::Success;
 use rocket::request::{FromRequest, Outcome, Request};
 
-use std::collections::HashMap;
-use std::sync::Mutex;
-
-use crate::common::{Right, User};
+use crate::common::{ClubRights, Right, User};
 use crate::error::ErrorKind;
 
-lazy_static::lazy_static! {
-    pub static ref ADMINSESSION: Mutex<Option<String>> = Mutex::new(None);
-    pub static ref USERSESSIONS: Mutex<HashMap<String,UserSession>> = Mutex::new(HashMap::new());
-    pub static ref EVENTSESSIONS: Mutex<HashMap<String,EventSession>> = Mutex::new(HashMap::new());
-}
+/// API tokens are told apart from the alphanumeric session tokens by this prefix
+pub const API_TOKEN_PREFIX: &str = "cpt_";
 
 /*
  * STRUCTS
@@ -21,9 +13,15 @@ lazy_static::lazy_static! {
 
 #[derive(Debug, Clone)]
 pub struct UserSession {
+    pub token: String,
+    pub created: chrono::DateTime<chrono::Utc>,
     pub expiry: chrono::DateTime<chrono::Utc>,
     pub user: User,
     pub right: Right,
+    pub club_rights: ClubRights,
+    pub api_token: Option<u32>,
+    /// Path of the requested route, which write operations are recorded with
+    pub route: String,
 }
 
 #[rocket::async_trait]
@@ -36,48 +34,115 @@ impl<'r> FromRequest<'r> for UserSession {
             Some(token) => token,
         };
 
-        let session: UserSession = match USERSESSIONS.lock().unwrap().get(&head_token.to_string()).cloned() {
-            None => {
-                return ErrorKind::SessionTokenInvalid.outcome();
-            }
-            Some(session) => session,
+        let conn = &mut match crate::utils::db::get_db_conn() {
+            Err(e) => return e.outcome(),
+            Ok(conn) => conn,
+        };
+
+        if head_token.starts_with(API_TOKEN_PREFIX) {
+            return match UserSession::api(conn, head_token, request_route(request)) {
+                Err(e) => e.outcome(),
+                Ok(session) => Success(session),
+            };
+        }
+
+        let mut session: UserSession = match crate::db::session::user_session_info(conn, head_token) {
+            Err(e) => return e.outcome(),
+            Ok(None) => return ErrorKind::SessionTokenInvalid.outcome(),
+            Ok(Some((user, right, club_rights, info))) => UserSession {
+                token: head_token.to_string(),
+                created: info.created.and_utc(),
+                expiry: info.expiry.and_utc(),
+                user,
+                right,
+                club_rights,
+                api_token: None,
+                route: request_route(request),
+            },
         };
 
         if session.expiry < chrono::Utc::now() {
-            USERSESSIONS.lock().unwrap().remove(head_token);
+            let _ = crate::db::session::session_delete(conn, &session.token);
             return ErrorKind::SessionTokenExpired.outcome();
         }
 
+        // Extend the session on activity, but never beyond its maximum lifetime
+        if crate::config::SESSION_EXPIRY_SLIDING() {
+            let expiry = crate::utils::session::session_expiry(session.created);
+            if crate::db::session::session_expiry_edit(conn, &session.token, &expiry.naive_utc()).is_ok() {
+                session.expiry = expiry;
+            }
+        }
+
         Success(session)
     }
 }
 
 impl UserSession {
-    pub fn admin(user: &User) -> Self {
-        UserSession {
-            expiry: chrono::Utc::now() + chrono::Duration::hours(1),
-            user: user.clone(),
-            right: Right {
-                right_club_write: true,
-                right_club_read: true,
-                right_competence_write: true,
-                right_competence_read: true,
-                right_course_write: true,
-                right_course_read: true,
-                right_event_write: true,
-                right_event_read: true,
-                right_inventory_write: true,
-                right_inventory_read: true,
-                right_location_write: true,
-                right_location_read: true,
-                right_organisation_write: true,
-                right_organisation_read: true,
-                right_team_write: true,
-                right_team_read: true,
-                right_user_write: true,
-                right_user_read: true,
-            },
+    /// Rights within a club, which add the rights of teams confined to it to the global ones
+    pub fn club_right(&self, club_id: u32) -> Right {
+        match self.club_rights.get(&club_id) {
+            None => self.right.clone(),
+            Some(club_right) => self.right.union(club_right),
+        }
+    }
+
+    /// Rights for a course, which are scoped to its club if it belongs to one
+    pub fn course_right(&self, conn: &mut mysql::PooledConn, course_id: u64) -> Result<Right, ErrorKind> {
+        match crate::db::course::course_club_info(conn, course_id)? {
+            None => Ok(self.right.clone()),
+            Some(club_id) => Ok(self.club_right(club_id)),
+        }
+    }
+
+    /// Refuse API token sessions for routes which only an interactive login may reach
+    pub fn deny_api_token(&self) -> Result<(), ErrorKind> {
+        crate::common::api_token_deny(self.api_token)
+    }
+
+    /// Refuse edits of a superuser, unless this session holds the full rights of a superuser itself
+    pub fn deny_superuser_target(&self, conn: &mut mysql::PooledConn, user_id: u64) -> Result<(), ErrorKind> {
+        let target_superuser = crate::db::user::user_superuser_true(conn, user_id)?;
+        let actor_superuser =
+            self.right.contains(&Right::full()) && crate::db::user::user_superuser_true(conn, self.user.id)?;
+        crate::common::user_superuser_deny(actor_superuser, target_superuser)
+    }
+
+    /// Session of an API token, whose scope is capped by the current rights of its owner
+    fn api(conn: &mut mysql::PooledConn, token: &str, route: String) -> Result<Self, ErrorKind> {
+        let token_hash = crate::common::hash_sha256(token.as_bytes(), &[]);
+
+        let (user, api_token) = match crate::db::api_token::api_token_info(conn, &token_hash)? {
+            None => return Err(ErrorKind::SessionTokenInvalid),
+            Some(info) => info,
+        };
+
+        let expiry = match api_token.expiry {
+            None => chrono::DateTime::<chrono::Utc>::MAX_UTC,
+            Some(expiry) => expiry.and_utc(),
+        };
+
+        if expiry < chrono::Utc::now() {
+            return Err(ErrorKind::SessionTokenExpired);
         }
+
+        let right = crate::db::login::user_right(conn, user.id, true)?.intersect(&api_token.right);
+        let club_rights = crate::db::login::user_club_right(conn, user.id, true)?
+            .into_iter()
+            .map(|(club_id, club_right)| (club_id, club_right.intersect(&api_token.right)))
+            .collect();
+        crate::db::api_token::api_token_used(conn, api_token.id)?;
+
+        Ok(UserSession {
+            token: token.to_string(),
+            created: api_token.created.and_utc(),
+            expiry,
+            user,
+            right,
+            club_rights,
+            api_token: Some(api_token.id),
+            route,
+        })
     }
 }
 
@@ -86,6 +151,8 @@ pub struct EventSession {
     pub token: String,
     pub expiry: chrono::DateTime<chrono::Utc>,
     pub event_id: u64,
+    /// Path of the requested route, which write operations are recorded with
+    pub route: String,
 }
 
 #[rocket::async_trait]
@@ -98,22 +165,35 @@ impl<'r> FromRequest<'r> for EventSession {
             Some(token) => token,
         };
 
-        let session: EventSession = match EVENTSESSIONS.lock().unwrap().get(&head_token.to_string()).cloned() {
-            None => {
-                return ErrorKind::SessionTokenInvalid.outcome();
-            }
-            Some(session) => session,
+        let conn = &mut match crate::utils::db::get_db_conn() {
+            Err(e) => return e.outcome(),
+            Ok(conn) => conn,
         };
 
-        if session.token != *head_token {
-            return ErrorKind::SessionTokenInvalid.outcome();
-        }
+        let session: EventSession = match crate::db::session::event_session_info(conn, head_token) {
+            Err(e) => return e.outcome(),
+            Ok(None) => return ErrorKind::SessionTokenInvalid.outcome(),
+            Ok(Some((event_id, expiry))) => EventSession {
+                token: head_token.to_string(),
+                expiry: expiry.and_utc(),
+                event_id,
+                route: request_route(request),
+            },
+        };
 
         if session.expiry < chrono::Utc::now() {
-            EVENTSESSIONS.lock().unwrap().remove(&session.token);
+            let _ = crate::db::session::session_delete(conn, &session.token);
             return ErrorKind::SessionTokenExpired.outcome();
         }
 
         Success(session)
     }
 }
+
+/// Path of the route matched by the request, without its dynamic query
+fn request_route(request: &Request<'_>) -> String {
+    request
+        .route()
+        .map(|route| route.uri.path().to_string())
+        .unwrap_or_default()
+}
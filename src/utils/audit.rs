@@ -0,0 +1,95 @@
+use crate::common::User;
+use crate::error::Result;
+use crate::session::{EventSession, UserSession};
+use mysql::PooledConn;
+use serde::Serialize;
+
+/// Originator of a write operation
+pub trait AuditActor {
+    fn audit_user_id(&self) -> Option<u64>;
+    fn audit_event_id(&self) -> Option<u64>;
+    fn audit_route(&self) -> &str;
+}
+
+impl AuditActor for UserSession {
+    fn audit_user_id(&self) -> Option<u64> {
+        Some(self.user.id)
+    }
+
+    fn audit_event_id(&self) -> Option<u64> {
+        None
+    }
+
+    fn audit_route(&self) -> &str {
+        &self.route
+    }
+}
+
+impl AuditActor for EventSession {
+    fn audit_user_id(&self) -> Option<u64> {
+        None
+    }
+
+    fn audit_event_id(&self) -> Option<u64> {
+        Some(self.event_id)
+    }
+
+    fn audit_route(&self) -> &str {
+        &self.route
+    }
+}
+
+/// Record a write operation on an entity together with the fields it changed
+pub fn audit<T: Serialize>(
+    conn: &mut PooledConn,
+    actor: &impl AuditActor,
+    entity: &str,
+    entity_id: u64,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<()> {
+    let before = before.and_then(|before| serde_json::to_value(before).ok());
+    let after = after.and_then(|after| serde_json::to_value(after).ok());
+    let diff = crate::common::audit_diff(before, after);
+
+    crate::db::audit::audit_create(
+        conn,
+        actor.audit_user_id(),
+        actor.audit_event_id(),
+        actor.audit_route(),
+        entity,
+        entity_id,
+        diff.as_ref(),
+    )?;
+    Ok(())
+}
+
+/// Record a write operation on a user, personal data is only recorded as changed without its values
+pub fn audit_user(
+    conn: &mut PooledConn,
+    actor: &impl AuditActor,
+    user_id: u64,
+    before: Option<&User>,
+    after: Option<&User>,
+) -> Result<()> {
+    let before = before.and_then(|before| serde_json::to_value(before).ok());
+    let after = after.and_then(|after| serde_json::to_value(after).ok());
+    let diff = crate::common::audit_diff(before, after);
+    let diff = crate::common::audit_mask(diff, crate::common::USER_AUDIT_MASKED);
+
+    crate::db::audit::audit_create(
+        conn,
+        actor.audit_user_id(),
+        actor.audit_event_id(),
+        actor.audit_route(),
+        "user",
+        user_id,
+        diff.as_ref(),
+    )?;
+    Ok(())
+}
+
+/// Record a write operation without snapshots, for credentials or entities without one at hand
+pub fn audit_action(conn: &mut PooledConn, actor: &impl AuditActor, entity: &str, entity_id: u64) -> Result<()> {
+    audit::<()>(conn, actor, entity, entity_id, None, None)
+}
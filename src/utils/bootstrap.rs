@@ -0,0 +1,42 @@
+use crate::error::{ErrorKind, Result};
+use mysql::PooledConn;
+
+/// Create the first superuser, or promote an existing user to it, and set his password
+///
+/// The password is salted like the clients do it (SHA-256 over the password followed by the salt),
+/// so the superuser can log in through the regular user login afterwards.
+pub fn bootstrap_superuser(conn: &mut PooledConn, user_key: &str, password: &str) -> Result<u64> {
+    crate::common::validate_user_key(user_key)?;
+
+    if password.is_empty() {
+        return Err(ErrorKind::UserPasswordMissing);
+    }
+
+    // The bootstrap is one-shot, further superusers are out of its reach
+    if crate::db::user::user_superuser_count(conn)? > 0 {
+        return Err(ErrorKind::AlreadyExists);
+    }
+
+    let user_id = match crate::db::user::user_created_true(conn, user_key)? {
+        Some(user_id) => user_id,
+        None => {
+            let mut user =
+                crate::common::User::from_info(0, user_key.to_string(), "Admin".to_string(), "Admin".to_string(), None);
+            crate::db::user::user_create(conn, &mut user)?
+        }
+    };
+
+    let salt = crate::common::random_bytes(16);
+    let salted_hash = crate::common::hash_sha256(password.as_bytes(), &salt);
+
+    match crate::db::user::user_password_info(conn, user_id)? {
+        None => crate::db::user::user_password_create(conn, user_id, &hex::encode(&salted_hash), &hex::encode(&salt))?,
+        Some(_) => crate::db::user::user_password_edit(conn, user_id, &hex::encode(&salted_hash), &hex::encode(&salt))?,
+    };
+
+    crate::db::user::user_superuser_edit(conn, user_id, true)?;
+    crate::db::session::user_session_delete(conn, user_id)?;
+
+    log::info!("Bootstrapped superuser {}", user_key);
+    Ok(user_id)
+}
@@ -1,6 +1,7 @@
-use crate::common::Event;
+use crate::common::{Acceptance, Credential, Event, EventImport, IcalEvent, Location, Occurrence};
 use crate::error::{ErrorKind, Result};
 use chrono::DurationRound;
+use mysql::PooledConn;
 
 pub fn validate_clear_password(password: String) -> Result<String> {
     if password.len() < 6 || password.len() > 50 {
@@ -10,6 +11,24 @@ pub fn validate_clear_password(password: String) -> Result<String> {
     Ok(password.to_string())
 }
 
+/// Replace the event password with a random one, which is only revealed through the return value
+pub fn rotate_event_password(conn: &mut PooledConn, event_id: u64) -> Result<Credential> {
+    let event = crate::db::event::event_info(conn, event_id)?;
+    let password = crate::common::random_string(12);
+
+    crate::db::event::event_password_edit(conn, event_id, &password)?;
+
+    Ok(Credential {
+        id: None,
+        login: Some(event.key),
+        password: Some(password),
+        salt: None,
+        since: None,
+        code: None,
+        token: None,
+    })
+}
+
 pub fn is_event_valid(event: &Event) -> bool {
     event.begin + crate::config::EVENT_OCCURRENCE_DURATION_MIN() < event.end
         || event.begin + crate::config::EVENT_OCCURRENCE_DURATION_MAX() > event.end
@@ -52,3 +71,99 @@ pub fn verify_event_search_window(
     }
     Ok(())
 }
+
+/// Create draft events from the VEVENTs of a calendar, those which are invalid or conflict are reported and skipped
+pub fn event_import(
+    conn: &mut PooledConn,
+    calendar: &str,
+    utc_offset: chrono::Duration,
+    dry_run: bool,
+) -> Result<Vec<EventImport>> {
+    let locations = crate::db::location::location_list(conn)?;
+    let mut imported: Vec<Event> = Vec::new();
+    let mut imports: Vec<EventImport> = Vec::new();
+
+    for ical in crate::common::ical_parse(calendar, utc_offset) {
+        let mut import = EventImport {
+            uid: ical.uid.clone(),
+            title: ical.summary.clone(),
+            begin: ical.begin,
+            end: ical.end,
+            location_key: ical.location.clone(),
+            event_id: None,
+            error: None,
+        };
+
+        match event_import_single(conn, &ical, &locations, &imported, dry_run) {
+            Err(e) => import.error = Some(format!("{:?}", e)),
+            Ok(event) => {
+                import.begin = Some(event.begin);
+                import.end = Some(event.end);
+                import.event_id = (!dry_run).then_some(event.id);
+                imported.push(event);
+            }
+        }
+
+        imports.push(import);
+    }
+
+    Ok(imports)
+}
+
+fn event_import_single(
+    conn: &mut PooledConn,
+    ical: &IcalEvent,
+    locations: &[Location],
+    imported: &[Event],
+    dry_run: bool,
+) -> Result<Event> {
+    // Recurrences are not expanded, event series cover those
+    if ical.recurring {
+        return Err(ErrorKind::EventSeriesInvalid);
+    }
+
+    if ical.zone.is_some() {
+        return Err(ErrorKind::EventTimezoneInvalid);
+    }
+
+    let (Some(begin), Some(end)) = (ical.begin, ical.end) else {
+        return Err(ErrorKind::EventWindowInvalid);
+    };
+
+    let location = locations
+        .iter()
+        .find(|location| ical.location.as_ref() == Some(&location.key))
+        .ok_or(ErrorKind::Missing)?;
+
+    let title: String = ical.summary.clone().unwrap_or_default().chars().take(100).collect();
+    let mut event = Event::from_info(
+        0,
+        crate::common::random_string(12),
+        title,
+        begin,
+        end,
+        Some(location.clone()),
+    );
+    event.occurrence = Some(Occurrence::Occurring.to_string());
+    event.public = Some(false);
+    event.scrutable = Some(true);
+    event.note = Some(String::new());
+
+    validate_event_dates(&mut event)?;
+
+    if !crate::db::event::event_free_true(conn, &event)?
+        || !crate::common::location_share_free(
+            &event,
+            &imported.iter().collect::<Vec<&Event>>(),
+            location.areas.unwrap_or(1),
+        )
+    {
+        return Err(ErrorKind::EventWindowConflict);
+    }
+
+    if !dry_run {
+        event.id = crate::db::event::event_create(conn, &event, &Acceptance::Draft, None)?;
+    }
+
+    Ok(event)
+}
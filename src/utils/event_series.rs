@@ -0,0 +1,251 @@
+use chrono::NaiveDate;
+use mysql::PooledConn;
+
+use crate::common::{Acceptance, Event, EventSeries, Occurrence, SeriesScope};
+use crate::error::{ErrorKind, Result};
+
+/// Snap time and duration like those of single events and bound the number of occurrences
+pub fn validate_event_series(series: &mut EventSeries) -> Result<()> {
+    if series.weekdays.is_empty() || series.interval == 0 || series.until < series.begin {
+        return Err(ErrorKind::EventSeriesInvalid);
+    }
+
+    let span = crate::config::EVENT_SERIES_OCCURRENCE_MAX() as i64 * 7 * series.interval as i64;
+    if (series.until - series.begin).num_days() > span {
+        return Err(ErrorKind::EventSeriesInvalid);
+    }
+
+    validate_event_series_occurrence(series, series.begin)?;
+
+    let count = series.occurrence_dates().len();
+    if count == 0 || count > crate::config::EVENT_SERIES_OCCURRENCE_MAX() {
+        return Err(ErrorKind::EventSeriesInvalid);
+    }
+
+    Ok(())
+}
+
+/// Snap time and duration of the series like those of a single event on the given date
+fn validate_event_series_occurrence(series: &mut EventSeries, date: NaiveDate) -> Result<()> {
+    let (begin, end) = series.occurrence_window(date);
+    let mut event = Event::from_info(0, String::new(), series.title.clone(), begin, end, None);
+    crate::utils::event::validate_event_dates(&mut event)?;
+    series.time = event.begin.time();
+    series.duration = (event.end - event.begin).num_minutes() as u16;
+    Ok(())
+}
+
+pub fn event_series_create(
+    conn: &mut PooledConn,
+    course_id: u32,
+    series: &mut EventSeries,
+    acceptance: &Acceptance,
+) -> Result<u32> {
+    validate_event_series(series)?;
+
+    crate::db::transaction(conn, |conn| {
+        let series_id = crate::db::event::series::event_series_create(conn, course_id, series)?;
+        event_series_materialise(conn, series_id, series.begin, acceptance)?;
+        Ok(series_id)
+    })
+}
+
+/// Apply an edited series to a single occurrence, to an occurrence and all following ones, or to all upcoming ones.
+/// Editing following occurrences splits the series, the returned id is the one holding the edited occurrences.
+/// A single edited occurrence is detached, so that later edits of the series keep it as is.
+pub fn event_series_edit(
+    conn: &mut PooledConn,
+    series_id: u32,
+    event_id: Option<u64>,
+    scope: &SeriesScope,
+    series: &mut EventSeries,
+    acceptance: &Acceptance,
+) -> Result<u32> {
+    crate::db::transaction(conn, |conn| {
+        event_series_scope_edit(conn, series_id, event_id, scope, series, acceptance)
+    })
+}
+
+fn event_series_scope_edit(
+    conn: &mut PooledConn,
+    series_id: u32,
+    event_id: Option<u64>,
+    scope: &SeriesScope,
+    series: &mut EventSeries,
+    acceptance: &Acceptance,
+) -> Result<u32> {
+    let stored = crate::db::event::series::event_series_info(conn, series_id)?;
+
+    match scope {
+        SeriesScope::This => {
+            let (event_id, date) = event_series_occurrence(conn, series_id, event_id)?;
+            validate_event_series_occurrence(series, date)?;
+            event_series_occurrence_edit(conn, series, event_id)?;
+            crate::db::event::series::event_series_occurrence_detach(conn, event_id)?;
+            Ok(series_id)
+        }
+        SeriesScope::Following => {
+            let (_, date) = event_series_occurrence(conn, series_id, event_id)?;
+            series.begin = date;
+            validate_event_series(series)?;
+
+            if date <= stored.begin {
+                crate::db::event::series::event_series_edit(conn, series_id, series)?;
+                event_series_materialise(conn, series_id, date, acceptance)?;
+                return Ok(series_id);
+            }
+
+            let mut head = stored.clone();
+            head.until = date.pred_opt().ok_or(ErrorKind::EventSeriesInvalid)?;
+            crate::db::event::series::event_series_edit(conn, series_id, &head)?;
+
+            let tail_id = crate::db::event::series::event_series_create(conn, stored.course_id, series)?;
+            crate::db::event::series::event_series_occurrence_move(conn, series_id, tail_id, date)?;
+            event_series_materialise(conn, tail_id, date, acceptance)?;
+            Ok(tail_id)
+        }
+        SeriesScope::All => {
+            validate_event_series(series)?;
+            crate::db::event::series::event_series_edit(conn, series_id, series)?;
+
+            // Past occurrences are left untouched to keep their attendance accurate
+            let today = chrono::Utc::now().date_naive();
+            event_series_materialise(conn, series_id, today, acceptance)?;
+            Ok(series_id)
+        }
+    }
+}
+
+/// Remove a single occurrence, an occurrence and all following ones, or the series with all upcoming occurrences
+pub fn event_series_delete(
+    conn: &mut PooledConn,
+    series_id: u32,
+    event_id: Option<u64>,
+    scope: &SeriesScope,
+) -> Result<()> {
+    crate::db::transaction(conn, |conn| event_series_scope_delete(conn, series_id, event_id, scope))
+}
+
+fn event_series_scope_delete(
+    conn: &mut PooledConn,
+    series_id: u32,
+    event_id: Option<u64>,
+    scope: &SeriesScope,
+) -> Result<()> {
+    let mut series = crate::db::event::series::event_series_info(conn, series_id)?;
+
+    match scope {
+        SeriesScope::This => {
+            let (event_id, date) = event_series_occurrence(conn, series_id, event_id)?;
+            series.exceptions.push(date);
+            crate::db::event::series::event_series_edit(conn, series_id, &series)?;
+            crate::db::event::series::event_series_occurrence_remove(conn, event_id)?;
+        }
+        SeriesScope::Following => {
+            let (_, date) = event_series_occurrence(conn, series_id, event_id)?;
+            event_series_purge(conn, series_id, date)?;
+
+            if date <= series.begin {
+                crate::db::event::series::event_series_delete(conn, series_id)?;
+            } else {
+                series.until = date.pred_opt().ok_or(ErrorKind::EventSeriesInvalid)?;
+                crate::db::event::series::event_series_edit(conn, series_id, &series)?;
+            }
+        }
+        SeriesScope::All => {
+            // Past occurrences remain as standalone events
+            event_series_purge(conn, series_id, chrono::Utc::now().date_naive())?;
+            crate::db::event::series::event_series_delete(conn, series_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Bring the events of a series from a given date onwards in line with its recurrence
+fn event_series_materialise(
+    conn: &mut PooledConn,
+    series_id: u32,
+    from: NaiveDate,
+    acceptance: &Acceptance,
+) -> Result<()> {
+    let series = crate::db::event::series::event_series_info(conn, series_id)?;
+    let occurrences = crate::db::event::series::event_series_occurrence_list(conn, series_id, from)?;
+    let plan = series.occurrence_plan(from, &occurrences);
+
+    for event_id in &plan.restore {
+        crate::db::event::series::event_series_occurrence_restore(conn, *event_id)?;
+    }
+
+    for event_id in &plan.edit {
+        event_series_occurrence_edit(conn, &series, *event_id)?;
+    }
+
+    for event_id in &plan.remove {
+        crate::db::event::series::event_series_occurrence_remove(conn, *event_id)?;
+    }
+
+    for date in &plan.create {
+        let (begin, end) = series.occurrence_window(*date);
+        let mut event = Event::from_info(
+            0,
+            crate::common::random_string(12),
+            series.title.clone(),
+            begin,
+            end,
+            Some(series.location.clone()),
+        );
+        event.occurrence = Some(Occurrence::Occurring.to_string());
+        event.public = Some(false);
+        event.scrutable = Some(true);
+        event.note = Some(String::new());
+        event.areas = series.areas;
+        event.acceptance = Some(acceptance.to_string());
+
+        if event.claims_location() && !crate::db::event::event_free_true(conn, &event)? {
+            return Err(ErrorKind::EventWindowConflict);
+        }
+
+        let event_id = crate::db::event::event_create(conn, &event, acceptance, Some(series.course_id))?;
+        crate::db::event::series::event_series_occurrence_add(conn, series_id, event_id)?;
+    }
+
+    Ok(())
+}
+
+fn event_series_purge(conn: &mut PooledConn, series_id: u32, from: NaiveDate) -> Result<()> {
+    for occurrence in crate::db::event::series::event_series_occurrence_list(conn, series_id, from)? {
+        crate::db::event::series::event_series_occurrence_remove(conn, occurrence.event_id)?;
+    }
+    Ok(())
+}
+
+/// Copy title, time window and location of the series onto one of its events, without double booking the location
+fn event_series_occurrence_edit(conn: &mut PooledConn, series: &EventSeries, event_id: u64) -> Result<()> {
+    let mut event = crate::db::event::event_info(conn, event_id)?;
+    let (begin, end) = series.occurrence_window(event.begin.date());
+
+    event.title = series.title.clone();
+    event.begin = begin;
+    event.end = end;
+    event.location = Some(series.location.clone());
+    event.areas = series.areas;
+
+    if event.claims_location() && !crate::db::event::event_free_true(conn, &event)? {
+        return Err(ErrorKind::EventWindowConflict);
+    }
+
+    crate::db::event::event_edit(conn, event_id, &event)
+}
+
+/// Event and date of the occurrence an edit refers to
+fn event_series_occurrence(conn: &mut PooledConn, series_id: u32, event_id: Option<u64>) -> Result<(u64, NaiveDate)> {
+    let event_id = event_id.ok_or(ErrorKind::EventMissing)?;
+    let event = crate::db::event::event_info(conn, event_id)?;
+
+    if event.series_id != Some(series_id) {
+        return Err(ErrorKind::EventSeriesMissing);
+    }
+
+    Ok((event_id, event.begin.date()))
+}
@@ -0,0 +1,52 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Logger that writes one key=value line per record to stdout, which journald picks up as is.
+///
+/// Records of this crate are filtered by the `cpt_log_level`, everything else (Rocket, Hyper, ...)
+/// by the `rocket_log_level`.
+struct CptLogger {
+    level: LevelFilter,
+    level_rocket: LevelFilter,
+}
+
+impl CptLogger {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        if target.starts_with(env!("CARGO_CRATE_NAME")) {
+            self.level
+        } else {
+            self.level_rocket
+        }
+    }
+}
+
+impl Log for CptLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        println!(
+            "level={} target={} msg={:?}",
+            record.level(),
+            record.target().trim_end_matches('_'),
+            crate::common::redact_query(&record.args().to_string())
+        );
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger, which takes precedence over Rocket's own logger
+pub fn init_logger() {
+    let logger = CptLogger {
+        level: crate::config::LOG_LEVEL(),
+        level_rocket: crate::config::ROCKET_LOG_LEVEL(),
+    };
+
+    log::set_max_level(logger.level.max(logger.level_rocket));
+    let _ = log::set_boxed_logger(Box::new(logger));
+}
@@ -0,0 +1,91 @@
+use crate::common::LoginCategory;
+use crate::error::{ErrorKind, Result};
+use mysql::PooledConn;
+
+/// Deny the login while the subject is locked out
+pub fn verify_login_lockout(conn: &mut PooledConn, category: &LoginCategory, subject: &str) -> Result<()> {
+    let failure = match crate::db::login::login_failure_info(conn, category, subject)? {
+        None => return Ok(()),
+        Some(failure) => failure,
+    };
+
+    match failure.locked_until {
+        Some(locked_until) if locked_until > chrono::Utc::now().naive_utc() => Err(ErrorKind::LoginLocked),
+        _ => Ok(()),
+    }
+}
+
+/// Count a failed login and lock the subject with an exponential backoff once the free attempts are used up
+pub fn register_login_failure(conn: &mut PooledConn, category: &LoginCategory, subject: &str) -> Result<()> {
+    // Forget about failures that happened long ago
+    if let Some(failure) = crate::db::login::login_failure_info(conn, category, subject)? {
+        if failure.last_attempt + crate::config::LOGIN_LOCKOUT_MAX() < chrono::Utc::now().naive_utc() {
+            crate::db::login::login_failure_delete(conn, category, subject)?;
+        }
+    }
+
+    let attempts = crate::db::login::login_failure_add(conn, category, subject)?;
+
+    if attempts < crate::config::LOGIN_ATTEMPTS_MAX() {
+        return Ok(());
+    }
+
+    let exponent = (attempts - crate::config::LOGIN_ATTEMPTS_MAX()).min(16) as u32;
+    let backoff = (crate::config::LOGIN_LOCKOUT_BASE() * 2i32.pow(exponent)).min(crate::config::LOGIN_LOCKOUT_MAX());
+    let locked_until = chrono::Utc::now().naive_utc() + backoff;
+    log::warn!("Login for {} {} locked until {}", category, subject, locked_until);
+
+    crate::db::login::login_failure_lock(conn, category, subject, &locked_until)
+}
+
+/// Clear the failures of a subject after a successful login
+pub fn reset_login_failure(conn: &mut PooledConn, category: &LoginCategory, subject: &str) -> Result<()> {
+    crate::db::login::login_failure_delete(conn, category, subject)
+}
+
+/// Mail a password reset token to the user, unknown or unreachable users are skipped silently
+pub fn password_reset_send(user_key: &str) -> Result<()> {
+    let conn = &mut crate::utils::db::get_db_conn()?;
+
+    let user_id = match crate::db::user::user_created_true(conn, user_key)? {
+        None => return Ok(()),
+        Some(user_id) => user_id,
+    };
+
+    let user = crate::db::user::user_detailed(conn, user_id)?;
+    let email = match (user.enabled, user.email) {
+        (Some(true), Some(email)) => email,
+        _ => return Ok(()),
+    };
+
+    // Without a transport the token could never be delivered, so none is handed out
+    let transport = crate::utils::mail::mail_transport()?;
+
+    let token = crate::common::random_string(30);
+    let token_hash = crate::common::hash_sha256(token.as_bytes(), &[]);
+    let expiry = chrono::Utc::now() + crate::config::PASSWORD_RESET_DURATION();
+
+    crate::db::user::password_reset_expired_delete(conn)?;
+    crate::db::user::password_reset_delete(conn, user_id)?;
+    crate::db::user::password_reset_create(conn, user_id, &token_hash, &expiry.naive_utc())?;
+
+    let mail = crate::utils::mail::Mail {
+        recipient: email,
+        subject: "Password reset".to_string(),
+        body: format!(
+            "A password reset was requested for the account {}.\n\n\
+            Use the following token until {} UTC to set a new password:\n\n{}\n\n\
+            If you did not request the reset, you can ignore this mail.",
+            user_key,
+            expiry.format("%Y-%m-%d %H:%M"),
+            token
+        ),
+    };
+
+    if let Err(e) = transport.send(&mail) {
+        crate::db::user::password_reset_delete(conn, user_id)?;
+        return Err(e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::io::Write;
+
+use crate::error::{ErrorKind, Result};
+
+pub struct Mail {
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivery backend for outgoing mails, chosen by the `cpt_mail_transport` setting
+pub trait MailTransport {
+    fn send(&self, mail: &Mail) -> Result<()>;
+}
+
+/// Relay mails to a SMTP server
+pub struct SmtpTransport;
+
+impl MailTransport for SmtpTransport {
+    fn send(&self, mail: &Mail) -> Result<()> {
+        use lettre::Transport;
+
+        let message = lettre::Message::builder()
+            .from(
+                crate::config::MAIL_SENDER()
+                    .parse()
+                    .map_err(|_| ErrorKind::MailFailed)?,
+            )
+            .to(mail.recipient.parse().map_err(|_| ErrorKind::MailFailed)?)
+            .subject(&mail.subject)
+            .header(lettre::message::header::ContentType::TEXT_PLAIN)
+            .body(mail.body.clone())
+            .map_err(|_| ErrorKind::MailFailed)?;
+
+        let mut builder = lettre::SmtpTransport::starttls_relay(crate::config::MAIL_SMTP_SERVER())
+            .map_err(|_| ErrorKind::MailFailed)?
+            .port(crate::config::MAIL_SMTP_PORT());
+
+        if let Some((user, password)) = crate::config::MAIL_SMTP_CREDENTIALS() {
+            builder = builder.credentials(lettre::transport::smtp::authentication::Credentials::new(
+                user, password,
+            ));
+        }
+
+        builder.build().send(&message).map_err(|_| ErrorKind::MailFailed)?;
+        Ok(())
+    }
+}
+
+/// Append mails to a local file instead of delivering them
+pub struct FileTransport;
+
+impl MailTransport for FileTransport {
+    fn send(&self, mail: &Mail) -> Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(crate::config::MAIL_FILE_PATH())
+            .map_err(|_| ErrorKind::MailFailed)?;
+
+        writeln!(file, "{}", format_mail(mail)).map_err(|_| ErrorKind::MailFailed)?;
+        Ok(())
+    }
+}
+
+/// Print mails to stdout, bypassing the logger on purpose as they can contain tokens
+pub struct StdoutTransport;
+
+impl MailTransport for StdoutTransport {
+    fn send(&self, mail: &Mail) -> Result<()> {
+        println!("{}", format_mail(mail));
+        Ok(())
+    }
+}
+
+fn format_mail(mail: &Mail) -> String {
+    format!(
+        "From: {}\nTo: {}\nSubject: {}\n\n{}\n",
+        crate::config::MAIL_SENDER(),
+        mail.recipient,
+        mail.subject,
+        mail.body
+    )
+}
+
+pub fn mail_transport() -> Result<Box<dyn MailTransport>> {
+    match crate::config::MAIL_TRANSPORT() {
+        "SMTP" => Ok(Box::new(SmtpTransport)),
+        "FILE" => Ok(Box::new(FileTransport)),
+        "STDOUT" => Ok(Box::new(StdoutTransport)),
+        _ => Err(ErrorKind::MailDisabled),
+    }
+}
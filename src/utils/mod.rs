@@ -1,2 +1,12 @@
+pub mod audit;
+pub mod bootstrap;
 pub mod db;
 pub mod event;
+pub mod event_series;
+pub mod logger;
+pub mod login;
+pub mod mail;
+pub mod oidc;
+pub mod retention;
+pub mod session;
+pub mod totp;
@@ -0,0 +1,27 @@
+use std::sync::OnceLock;
+
+use crate::common::OidcProvider;
+use crate::error::{ErrorKind, Result};
+
+static PROVIDER: OnceLock<OidcProvider> = OnceLock::new();
+
+/// Provider from the configuration, discovered on first use
+pub fn oidc_provider() -> Result<&'static OidcProvider> {
+    if let Some(provider) = PROVIDER.get() {
+        return Ok(provider);
+    }
+
+    let issuer = match crate::config::OIDC_ISSUER() {
+        None => return Err(ErrorKind::OidcDisabled),
+        Some(issuer) => issuer,
+    };
+
+    let provider = OidcProvider::discover(
+        issuer,
+        crate::config::OIDC_CLIENT_ID(),
+        crate::config::OIDC_CLIENT_SECRET(),
+        crate::config::OIDC_REDIRECT_URI(),
+    )?;
+
+    Ok(PROVIDER.get_or_init(|| provider))
+}
@@ -0,0 +1,81 @@
+use mysql::PooledConn;
+
+use crate::common::RetentionReport;
+use crate::error::Result;
+
+/// Data which is due for deletion according to the configured retention policies
+pub fn retention_report(conn: &mut PooledConn) -> Result<RetentionReport> {
+    let now = chrono::Utc::now().naive_utc();
+    let mut report = RetentionReport::default();
+
+    if let Some(period) = crate::config::RETENTION_USER_INACTIVE() {
+        report.users = crate::db::retention::retention_user_list(conn, now - period)?;
+    }
+
+    if let Some(years) = crate::config::RETENTION_ATTENDANCE_YEARS() {
+        let cutoff = crate::common::retention_cutoff_years(now, years);
+        report.events = crate::db::retention::retention_event_list(conn, cutoff)?;
+    }
+
+    if let Some(period) = crate::config::RETENTION_AUDIT() {
+        report.audit_entries = crate::db::retention::retention_audit_count(conn, now - period)?;
+    }
+
+    Ok(report)
+}
+
+/// Apply the configured retention policies and return what was purged
+pub fn retention_purge(conn: &mut PooledConn) -> Result<RetentionReport> {
+    let now = chrono::Utc::now().naive_utc();
+    let report = retention_report(conn)?;
+
+    for user in &report.users {
+        crate::db::user::user_anonymise(conn, user.id, &crate::common::user_pseudonym())?;
+        crate::db::audit::audit_create(conn, None, None, "retention", "user", user.id, None)?;
+    }
+
+    if let Some(years) = crate::config::RETENTION_ATTENDANCE_YEARS() {
+        let cutoff = crate::common::retention_cutoff_years(now, years);
+        crate::db::retention::retention_attendance_delete(conn, cutoff)?;
+    }
+
+    if let Some(period) = crate::config::RETENTION_AUDIT() {
+        crate::db::retention::retention_audit_delete(conn, now - period)?;
+    }
+
+    Ok(report)
+}
+
+/// Run the purge periodically in the background, if any retention policy is configured
+pub fn retention_task_spawn() {
+    if crate::config::RETENTION_USER_INACTIVE().is_none()
+        && crate::config::RETENTION_ATTENDANCE_YEARS().is_none()
+        && crate::config::RETENTION_AUDIT().is_none()
+    {
+        return;
+    }
+
+    rocket::tokio::spawn(async {
+        let mut interval = rocket::tokio::time::interval(crate::config::RETENTION_INTERVAL());
+
+        loop {
+            interval.tick().await;
+
+            let purge = rocket::tokio::task::spawn_blocking(|| {
+                let conn = &mut crate::utils::db::get_db_conn()?;
+                retention_purge(conn)
+            });
+
+            match purge.await {
+                Ok(Ok(report)) => log::info!(
+                    "Retention purged {} users, attendance of {} events and {} audit entries",
+                    report.users.len(),
+                    report.events.len(),
+                    report.audit_entries
+                ),
+                Ok(Err(e)) => log::error!("Retention purge failed: {:?}", e),
+                Err(e) => log::error!("Retention purge panicked: {}", e),
+            }
+        }
+    });
+}
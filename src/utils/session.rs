@@ -0,0 +1,64 @@
+use crate::error::Result;
+use mysql::PooledConn;
+
+/// Expiry of a session from now on, capped by the maximum lifetime since its creation
+pub fn session_expiry(created: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+    let expiry = chrono::Utc::now() + crate::config::SESSION_DURATION();
+    let expiry_max = created + crate::config::SESSION_LIFETIME_MAX();
+    expiry.min(expiry_max)
+}
+
+/// Open a new user session with the current team rights and return its token
+pub fn user_session_open(
+    conn: &mut PooledConn,
+    user_id: u64,
+    second_factor: bool,
+    client: Option<String>,
+) -> Result<String> {
+    let session_token: String = crate::common::random_string(30);
+    let session_expiry = session_expiry(chrono::Utc::now());
+
+    let user_right = crate::db::login::user_right(conn, user_id, second_factor)?;
+    let club_rights = crate::db::login::user_club_right(conn, user_id, second_factor)?;
+    crate::db::session::user_session_create(
+        conn,
+        &session_token,
+        user_id,
+        second_factor,
+        &user_right,
+        &club_rights,
+        &session_expiry.naive_utc(),
+        client,
+    )?;
+
+    Ok(session_token)
+}
+
+/// Open a new event session and return its token
+pub fn event_session_open(conn: &mut PooledConn, event_id: u64, client: Option<String>) -> Result<String> {
+    let session_token: String = crate::common::random_string(30);
+    let session_expiry = chrono::Utc::now() + crate::config::SESSION_DURATION();
+
+    crate::db::session::session_expired_delete(conn)?;
+    crate::db::session::event_session_create(conn, &session_token, event_id, &session_expiry.naive_utc(), client)?;
+
+    Ok(session_token)
+}
+
+/// Re-evaluate the rights of all live sessions of a user
+pub fn refresh_user_rights(conn: &mut PooledConn, user_id: u64) -> Result<()> {
+    for second_factor in [true, false] {
+        let right = crate::db::login::user_right(conn, user_id, second_factor)?;
+        let club_rights = crate::db::login::user_club_right(conn, user_id, second_factor)?;
+        crate::db::session::user_session_right_edit(conn, user_id, second_factor, &right, &club_rights)?;
+    }
+    Ok(())
+}
+
+/// Re-evaluate the rights of all live sessions of the team members
+pub fn refresh_team_rights(conn: &mut PooledConn, team_id: u32) -> Result<()> {
+    for member in crate::db::team::team_member_list(conn, team_id)? {
+        refresh_user_rights(conn, member.id)?;
+    }
+    Ok(())
+}
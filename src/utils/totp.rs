@@ -0,0 +1,54 @@
+use mysql::PooledConn;
+
+use crate::error::{ErrorKind, Result};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// URI for authenticator apps, usually presented as QR code
+pub fn totp_uri(user_key: &str, secret: &[u8]) -> String {
+    format!(
+        "otpauth://totp/CPT:{}?secret={}&issuer=CPT&digits={}&period={}",
+        user_key,
+        crate::common::base32_encode(secret),
+        crate::common::TOTP_DIGITS,
+        crate::common::TOTP_PERIOD
+    )
+}
+
+/// Accept either a TOTP code, which can only be used once, or an unused recovery code
+pub fn verify_second_factor(
+    conn: &mut PooledConn,
+    user_id: u64,
+    secret: &[u8],
+    last_step: Option<u64>,
+    code: &str,
+) -> Result<()> {
+    let step = crate::common::totp_step(chrono::Utc::now());
+
+    if let Some(step) = crate::common::totp_verify(secret, code, step) {
+        if last_step.is_some_and(|last_step| step <= last_step) {
+            return Err(ErrorKind::TotpInvalid);
+        }
+        return crate::db::user::user_totp_step_edit(conn, user_id, step);
+    }
+
+    let code_hash = crate::common::hash_sha256(code.as_bytes(), &[]);
+    match crate::db::user::user_recovery_code_use(conn, user_id, &code_hash)? {
+        true => Ok(()),
+        false => Err(ErrorKind::TotpInvalid),
+    }
+}
+
+/// Replace the recovery codes of a user and return them in clear text for a single time
+pub fn create_recovery_codes(conn: &mut PooledConn, user_id: u64) -> Result<Vec<String>> {
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| crate::common::random_string(12))
+        .collect();
+    let code_hashes: Vec<Vec<u8>> = codes
+        .iter()
+        .map(|code| crate::common::hash_sha256(code.as_bytes(), &[]))
+        .collect();
+
+    crate::db::user::user_recovery_code_create(conn, user_id, &code_hashes)?;
+    Ok(codes)
+}
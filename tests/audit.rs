@@ -0,0 +1,57 @@
+use serde_json::json;
+
+#[test]
+fn audit_diff() {
+    let before = json!({ "id": 1, "title": "old", "public": true });
+    let after = json!({ "id": 1, "title": "new", "public": true, "note": "added" });
+
+    assert_eq!(
+        cptserver::common::audit_diff(Some(before.clone()), Some(after)),
+        Some(json!({
+            "before": { "title": "old", "note": null },
+            "after": { "title": "new", "note": "added" },
+        }))
+    );
+
+    assert_eq!(
+        cptserver::common::audit_diff(None, Some(before.clone())),
+        Some(json!({ "before": null, "after": before }))
+    );
+
+    assert_eq!(cptserver::common::audit_diff(None, None), None);
+}
+
+#[test]
+fn audit_mask() {
+    let before = json!({ "firstname": "Old", "email": "old@example.com", "phone": null });
+    let after = json!({ "firstname": "New", "email": "new@example.com", "phone": "123" });
+    let diff = cptserver::common::audit_diff(Some(before), Some(after));
+
+    assert_eq!(
+        cptserver::common::audit_mask(diff, cptserver::common::USER_AUDIT_MASKED),
+        Some(json!({
+            "before": { "firstname": "Old", "email": "<redacted>", "phone": null },
+            "after": { "firstname": "New", "email": "<redacted>", "phone": "<redacted>" },
+        }))
+    );
+}
+
+#[test]
+fn export_audit() {
+    let entry = |entity: &str, entity_id: u64| cptserver::common::AuditEntry {
+        id: entity_id,
+        user_id: Some(1),
+        event_id: None,
+        route: "/admin/user_edit".to_string(),
+        entity: entity.to_string(),
+        entity_id,
+        diff: Some(json!({ "before": null, "after": { "firstname": "Other" } })),
+        time: chrono::NaiveDateTime::default(),
+    };
+
+    let entries = cptserver::common::export_audit(vec![entry("user", 1), entry("user", 2), entry("event", 3)], 1);
+
+    assert!(entries[0].diff.is_some());
+    assert!(entries[1].diff.is_none());
+    assert!(entries[2].diff.is_some());
+}
@@ -0,0 +1,42 @@
+use cptserver::error::ErrorKind;
+
+use cptserver::common::User;
+
+mod common;
+
+#[test]
+fn credential() -> Result<(), ErrorKind> {
+    let conn = &mut common::get_dbt_conn()?;
+
+    let mut user = User::from_info(0, "credential".into(), "first".into(), "last".into(), None);
+    user.enabled = Some(true);
+
+    let user_id = cptserver::db::user::user_create(conn, &mut user)?;
+
+    let salt = hex::encode(cptserver::common::random_bytes(16));
+    let salted_hash = cptserver::common::random_bytes(32);
+
+    cptserver::db::user::user_password_create(conn, user_id, &hex::encode(&salted_hash), &salt)?;
+    let login = cptserver::db::login::user_login(conn, "credential", &salted_hash)?;
+    assert_eq!(login.id, user_id);
+
+    let wrong_hash = cptserver::common::random_bytes(32);
+    let login = cptserver::db::login::user_login(conn, "credential", &wrong_hash);
+    assert!(matches!(login, Err(ErrorKind::UserLoginFail)));
+
+    let token_hash = cptserver::common::hash_sha256(b"token", &[]);
+    let expiry = (chrono::Utc::now() + chrono::Duration::minutes(5)).naive_utc();
+    cptserver::db::user::password_reset_create(conn, user_id, &token_hash, &expiry)?;
+    assert_eq!(
+        cptserver::db::user::password_reset_user(conn, &token_hash)?,
+        Some(user_id)
+    );
+
+    cptserver::db::user::password_reset_delete(conn, user_id)?;
+    assert_eq!(cptserver::db::user::password_reset_user(conn, &token_hash)?, None);
+
+    cptserver::db::user::user_password_delete(conn, user_id)?;
+    cptserver::db::user::user_delete(conn, user_id)?;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use chrono::{NaiveDate, Weekday};
+use cptserver::common::EventSeries;
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+}
+
+#[test]
+fn series_occurrence_dates() {
+    let mut series: EventSeries = serde_json::from_value(serde_json::json!({
+        "id": 0,
+        "title": "Training",
+        "location": { "id": 1, "key": "hall", "name": "Hall", "description": "" },
+        "time": "18:30:00",
+        "duration": 90,
+        "weekdays": ["Tue", "Thu"],
+        "interval": 2,
+        "begin": "2026-03-05",
+        "until": "2026-03-26",
+    }))
+    .unwrap();
+
+    assert_eq!(series.occurrence_dates(), vec![date(3, 5), date(3, 17), date(3, 19)]);
+
+    series.exceptions.push(date(3, 17));
+    assert_eq!(series.occurrence_dates(), vec![date(3, 5), date(3, 19)]);
+
+    let (begin, end) = series.occurrence_window(date(3, 5));
+    assert_eq!(begin, date(3, 5).and_hms_opt(18, 30, 0).unwrap());
+    assert_eq!(end, date(3, 5).and_hms_opt(20, 0, 0).unwrap());
+}
+
+#[test]
+fn series_weekday_mask() {
+    let weekdays = vec![Weekday::Mon, Weekday::Wed, Weekday::Sun];
+    let mask = EventSeries::weekdays_from_mask(0b1000101);
+    assert_eq!(mask, weekdays);
+}
+
+#[test]
+fn series_occurrence_plan() {
+    use cptserver::common::{OccurrencePlan, SeriesOccurrence, SeriesState};
+
+    let series: EventSeries = serde_json::from_value(serde_json::json!({
+        "id": 0,
+        "title": "Training",
+        "location": { "id": 1, "key": "hall", "name": "Hall", "description": "" },
+        "time": "18:30:00",
+        "duration": 90,
+        "weekdays": ["Tue"],
+        "interval": 1,
+        "begin": "2026-03-03",
+        "until": "2026-03-31",
+    }))
+    .unwrap();
+    let occurrence = |event_id, date, state| SeriesOccurrence { event_id, date, state };
+
+    let occurrences = vec![
+        occurrence(1, date(3, 3), SeriesState::Attached),
+        occurrence(2, date(3, 10), SeriesState::Detached),
+        occurrence(3, date(3, 17), SeriesState::Removed),
+        occurrence(4, date(3, 19), SeriesState::Attached),
+        occurrence(5, date(3, 20), SeriesState::Removed),
+        occurrence(6, date(3, 21), SeriesState::Detached),
+    ];
+
+    assert_eq!(
+        series.occurrence_plan(date(3, 10), &occurrences),
+        OccurrencePlan {
+            edit: vec![3],
+            restore: vec![3],
+            remove: vec![4],
+            create: vec![date(3, 24), date(3, 31)],
+        }
+    );
+}
@@ -0,0 +1,133 @@
+use chrono::NaiveDate;
+use cptserver::common::{ical_feed, Event};
+
+#[test]
+fn ical_feed_status() {
+    let begin = NaiveDate::from_ymd_opt(2026, 3, 5)
+        .unwrap()
+        .and_hms_opt(18, 30, 0)
+        .unwrap();
+    let end = begin + chrono::Duration::minutes(90);
+
+    let mut occurring = Event::from_info(1, "abc".into(), "Training; basics, part 1".into(), begin, end, None);
+    occurring.occurrence = Some("OCCURRING".into());
+    let mut canceled = Event::from_info(2, "def".into(), "Training".into(), begin, end, None);
+    canceled.occurrence = Some("CANCELED".into());
+    let mut voided = Event::from_info(3, "ghi".into(), "Training".into(), begin, end, None);
+    voided.occurrence = Some("VOIDED".into());
+
+    let feed = ical_feed("Course", &[occurring, canceled, voided], begin);
+
+    assert!(feed.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(feed.ends_with("END:VCALENDAR\r\n"));
+    assert!(feed.contains("DTSTART:20260305T183000Z\r\n"));
+    assert!(feed.contains("DTEND:20260305T200000Z\r\n"));
+    assert!(feed.contains("SUMMARY:Training\\; basics\\, part 1\r\n"));
+    assert!(feed.contains("UID:event-2@cptserver\r\nDTSTAMP"));
+    assert!(feed.contains("STATUS:CANCELLED\r\n"));
+    assert!(!feed.contains("UID:event-3@cptserver"));
+}
+
+#[test]
+fn ical_feed_fold() {
+    let begin = NaiveDate::from_ymd_opt(2026, 3, 5)
+        .unwrap()
+        .and_hms_opt(18, 30, 0)
+        .unwrap();
+    let event = Event::from_info(1, "abc".into(), "ü".repeat(60), begin, begin, None);
+    let feed = ical_feed("Course", &[event], begin);
+
+    assert!(feed.split("\r\n").all(|line| line.len() <= 75));
+    assert!(feed.contains("\r\n ü"));
+}
+
+#[test]
+fn ical_parse_events() {
+    let calendar = "BEGIN:VCALENDAR\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:1@hall\r\n\
+        SUMMARY:Court\\, north\r\n\
+        LOCATION:hall\r\n\
+        DTSTART:20260305T170000Z\r\n\
+        DURATION:PT1H30M\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        SUMMARY:Long title which is folded onto a \r\n second line\r\n\
+        DTSTART;TZID=Europe/Vienna:20260306T180000\r\n\
+        DTEND;TZID=Europe/Vienna:20260306T193000\r\n\
+        RRULE:FREQ=WEEKLY\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+
+    let events = cptserver::common::ical_parse(calendar, chrono::Duration::hours(1));
+    assert_eq!(events.len(), 2);
+
+    let day = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+    assert_eq!(events[0].uid.as_deref(), Some("1@hall"));
+    assert_eq!(events[0].summary.as_deref(), Some("Court, north"));
+    assert_eq!(events[0].location.as_deref(), Some("hall"));
+    assert_eq!(events[0].begin, day.and_hms_opt(17, 0, 0));
+    assert_eq!(events[0].end, day.and_hms_opt(18, 30, 0));
+
+    assert_eq!(
+        events[1].summary.as_deref(),
+        Some("Long title which is folded onto a second line")
+    );
+    assert_eq!(events[1].begin, None);
+    assert_eq!(events[1].end, None);
+    assert_eq!(events[1].zone.as_deref(), Some("Europe/Vienna"));
+    assert!(events[1].recurring);
+}
+
+#[test]
+fn ical_parse_zones() {
+    let calendar = "BEGIN:VCALENDAR\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART;TZID=Asia/Kolkata:20260305T180000\r\n\
+        DTEND;TZID=\"Europe/Vienna\":20260305T193000\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART;TZID=Asia/Kolkata:20260306T180000\r\n\
+        DURATION:PT1H\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART;TZID=UTC:20260307T180000\r\n\
+        DTEND:20260307T190000\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VTIMEZONE\r\n\
+        TZID:Asia/Kolkata\r\n\
+        BEGIN:STANDARD\r\n\
+        TZOFFSETFROM:+0530\r\n\
+        TZOFFSETTO:+0530\r\n\
+        END:STANDARD\r\n\
+        END:VTIMEZONE\r\n\
+        BEGIN:VTIMEZONE\r\n\
+        TZID:Europe/Vienna\r\n\
+        BEGIN:STANDARD\r\n\
+        TZOFFSETTO:+0100\r\n\
+        END:STANDARD\r\n\
+        BEGIN:DAYLIGHT\r\n\
+        TZOFFSETTO:+0200\r\n\
+        END:DAYLIGHT\r\n\
+        END:VTIMEZONE\r\n\
+        END:VCALENDAR\r\n";
+
+    let events = cptserver::common::ical_parse(calendar, chrono::Duration::hours(1));
+    assert_eq!(events.len(), 3);
+
+    // Zones with daylight saving time are reported instead of being guessed
+    let day = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+    assert_eq!(events[0].begin, day.and_hms_opt(12, 30, 0));
+    assert_eq!(events[0].end, None);
+    assert_eq!(events[0].zone.as_deref(), Some("Europe/Vienna"));
+
+    let day = NaiveDate::from_ymd_opt(2026, 3, 6).unwrap();
+    assert_eq!(events[1].begin, day.and_hms_opt(12, 30, 0));
+    assert_eq!(events[1].end, day.and_hms_opt(13, 30, 0));
+    assert_eq!(events[1].zone, None);
+
+    // Floating times are shifted by the offset, UTC needs no zone definition
+    let day = NaiveDate::from_ymd_opt(2026, 3, 7).unwrap();
+    assert_eq!(events[2].begin, day.and_hms_opt(18, 0, 0));
+    assert_eq!(events[2].end, day.and_hms_opt(18, 0, 0));
+}
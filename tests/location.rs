@@ -0,0 +1,90 @@
+use chrono::NaiveDate;
+use cptserver::common::{location_area_peak, location_free, location_share_free, Event, Location};
+
+#[test]
+fn location_event_capacity() {
+    let mut location: Location = serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "key": "hall",
+        "name": "Hall",
+        "description": "",
+    }))
+    .unwrap();
+    assert_eq!(location.event_capacity(None), None);
+
+    location.capacity = Some(30);
+    location.areas = Some(2);
+    assert_eq!(location.event_capacity(None), Some(30));
+    assert_eq!(location.event_capacity(Some(1)), Some(15));
+    assert_eq!(location.event_capacity(Some(3)), Some(30));
+}
+
+#[test]
+fn location_area_usage() {
+    let day = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+    let time = |hour| day.and_hms_opt(hour, 0, 0).unwrap();
+
+    let bookings = vec![
+        (time(16), time(18), 1),
+        (time(17), time(19), 1),
+        (time(19), time(20), 2),
+    ];
+
+    assert_eq!(location_area_peak(time(15), time(16), &bookings), 0);
+    assert_eq!(location_area_peak(time(15), time(17), &bookings), 1);
+    assert_eq!(location_area_peak(time(16), time(19), &bookings), 2);
+    assert_eq!(location_area_peak(time(18), time(19), &bookings), 1);
+    assert_eq!(location_area_peak(time(18), time(21), &bookings), 2);
+}
+
+#[test]
+fn location_free_claims() {
+    let day = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+    let time = |hour| day.and_hms_opt(hour, 0, 0).unwrap();
+    let location: Location = serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "key": "hall",
+        "name": "Hall",
+        "description": "",
+        "areas": 2,
+    }))
+    .unwrap();
+    let event = |id, begin, end, areas, acceptance: &str, occurrence: &str| {
+        let mut event = Event::from_info(
+            id,
+            String::new(),
+            String::new(),
+            time(begin),
+            time(end),
+            Some(location.clone()),
+        );
+        event.areas = areas;
+        event.acceptance = Some(acceptance.to_string());
+        event.occurrence = Some(occurrence.to_string());
+        event
+    };
+
+    let half = event(0, 17, 18, Some(1), "DRAFT", "OCCURRING");
+    let whole = event(0, 17, 18, None, "DRAFT", "OCCURRING");
+
+    // Events which are not accepted or do not take place leave the location free
+    let others = vec![
+        event(1, 16, 18, None, "PENDING", "OCCURRING"),
+        event(2, 16, 18, None, "ACCEPTED", "CANCELED"),
+    ];
+    assert!(location_free(&whole, &others, 2));
+
+    let others = vec![event(3, 17, 19, Some(1), "ACCEPTED", "OCCURRING")];
+    assert!(location_free(&half, &others, 2));
+    assert!(!location_free(&whole, &others, 2));
+
+    let others = vec![event(4, 16, 17, None, "ACCEPTED", "OCCURRING")];
+    assert!(location_free(&whole, &others, 2));
+
+    // Imported drafts count against each other by their sub-areas
+    let first = event(0, 16, 18, Some(1), "DRAFT", "OCCURRING");
+    let second = event(0, 17, 19, Some(1), "DRAFT", "OCCURRING");
+    assert!(location_share_free(&half, &[&first], 2));
+    assert!(!location_share_free(&half, &[&first, &second], 2));
+    assert!(!location_share_free(&whole, &[&first], 2));
+}
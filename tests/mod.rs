@@ -1,4 +1,5 @@
 use cptserver;
+use cptserver::db;
 use cptserver::error::ErrorKind;
 
 mod common;
@@ -8,3 +9,51 @@ fn dbt_test() -> Result<(), ErrorKind> {
     let _ = &mut common::get_dbt_conn()?;
     Ok(())
 }
+
+#[test]
+fn dbt_retention_user() -> Result<(), ErrorKind> {
+    use mysql::params;
+    use mysql::prelude::Queryable;
+
+    let conn = &mut common::get_dbt_conn()?;
+    let user_key = format!("ret{}", cptserver::common::random_string(12).to_lowercase());
+
+    conn.exec_drop(
+        "INSERT INTO users (user_key, firstname, lastname, active) VALUES (:user_key, 'Retention', 'Test', FALSE);",
+        params! { "user_key" => &user_key },
+    )?;
+    let user_id: u64 = conn
+        .exec_first(
+            "SELECT user_id FROM users WHERE user_key = :user_key;",
+            params! { "user_key" => &user_key },
+        )?
+        .unwrap();
+    let listed = |conn: &mut mysql::PooledConn, cutoff| -> Result<bool, ErrorKind> {
+        let users = db::retention::retention_user_list(conn, cutoff)?;
+        Ok(users.iter().any(|user| user.id == user_id))
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    assert!(listed(conn, now + chrono::Duration::hours(1))?);
+
+    // A recent login keeps the user
+    conn.exec_drop(
+        "UPDATE users SET last_login = UTC_TIMESTAMP() WHERE user_id = :user_id;",
+        params! { "user_id" => user_id },
+    )?;
+    assert!(!listed(conn, now - chrono::Duration::days(1))?);
+    assert!(listed(conn, now + chrono::Duration::hours(1))?);
+
+    // Anonymisation drops the lockout of the former key and removes the user from the report
+    conn.exec_drop(
+        "INSERT INTO login_failures (category, subject, attempts, last_attempt)
+        VALUES ('USER', :user_key, 1, UTC_TIMESTAMP());",
+        params! { "user_key" => &user_key },
+    )?;
+    db::user::user_anonymise(conn, user_id, &cptserver::common::user_pseudonym())?;
+    assert!(db::login::login_failure_info(conn, &cptserver::common::LoginCategory::User, &user_key)?.is_none());
+    assert!(!listed(conn, now + chrono::Duration::hours(1))?);
+
+    db::user::user_delete(conn, user_id)?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+
+use cptserver::common::OidcProvider;
+
+/// Minimal identity provider answering discovery, token and userinfo requests
+fn mock_idp() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let issuer = base.clone();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut content_length = 0;
+            let mut authorization = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                let lower = line.to_lowercase();
+                if let Some(value) = lower.strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+                if lower.starts_with("authorization:") {
+                    authorization = line.trim().to_string();
+                }
+            }
+
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let body = String::from_utf8(body).unwrap();
+
+            let response = if request_line.starts_with("GET /.well-known/openid-configuration") {
+                format!(
+                    r#"{{"authorization_endpoint":"{0}/authorize","token_endpoint":"{0}/token","userinfo_endpoint":"{0}/userinfo"}}"#,
+                    base
+                )
+            } else if request_line.starts_with("POST /token")
+                && body.contains("code=mockcode")
+                && body.contains("code_verifier=mockverifier")
+            {
+                r#"{"access_token":"mocktoken","token_type":"Bearer"}"#.to_string()
+            } else if request_line.starts_with("GET /userinfo") && authorization.ends_with("Bearer mocktoken") {
+                r#"{"sub":"mockuser","email":"mock@example.org","email_verified":true}"#.to_string()
+            } else {
+                let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+                continue;
+            };
+
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            );
+        }
+    });
+
+    issuer
+}
+
+#[test]
+fn oidc_flow() {
+    let issuer = mock_idp();
+
+    let provider = OidcProvider::discover(&issuer, "cpt", "secret", "http://localhost/callback").unwrap();
+    assert_eq!(provider.token_endpoint, format!("{}/token", issuer));
+
+    let url = provider.authorization_url("mockstate", "mockverifier").unwrap();
+    assert!(url.starts_with(&format!("{}/authorize?response_type=code", issuer)));
+    assert!(url.contains("state=mockstate"));
+    assert!(url.contains("redirect_uri=http%3A%2F%2Flocalhost%2Fcallback"));
+    assert!(url.contains("code_challenge_method=S256"));
+
+    let info = provider.user_info("mockcode", "mockverifier").unwrap();
+    assert_eq!(info.sub, "mockuser");
+    assert_eq!(info.email.as_deref(), Some("mock@example.org"));
+
+    assert!(provider.user_info("wrongcode", "mockverifier").is_err());
+    assert!(provider.user_info("mockcode", "wrongverifier").is_err());
+}
+
+#[test]
+fn oidc_pkce_challenge() {
+    // Example of RFC 7636 appendix B
+    assert_eq!(
+        cptserver::common::pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+}
@@ -0,0 +1,103 @@
+use cptserver::common::{FromStr, Permission, Right};
+
+#[test]
+fn permission_registry() {
+    for permission in Permission::ALL {
+        assert_eq!(Permission::from_str(permission.as_str()).unwrap(), *permission);
+        let json = serde_json::to_string(permission).unwrap();
+        assert_eq!(json, format!("\"{}\"", permission.as_str()));
+        assert_eq!(serde_json::from_str::<Permission>(&json).unwrap(), *permission);
+    }
+
+    assert!(serde_json::from_str::<Permission>(r#""event.unknown""#).is_err());
+
+    assert!(Permission::from_str("event.unknown").is_err());
+    assert!(Permission::UserBankAccountRead.is_read());
+    assert!(!Permission::EventAccept.is_read());
+}
+
+#[test]
+fn right_set() {
+    let right: Right = serde_json::from_str(r#"["event.read", "event.accept"]"#).unwrap();
+    assert!(right.has(Permission::EventAccept));
+    assert!(!right.has(Permission::EventWrite));
+
+    let token: Right = serde_json::from_str(r#"["event.read", "event.write"]"#).unwrap();
+    assert!(!right.contains(&token));
+    assert!(Right::full().contains(&token));
+    assert_eq!(right.intersect(&token).permissions.len(), 1);
+    assert_eq!(right.union(&token).permissions.len(), 3);
+
+    assert!(serde_json::from_str::<Right>(r#"["event.unknown"]"#).is_err());
+}
+
+#[test]
+fn right_stored() {
+    let right = Right::from_stored(r#"["event.read", "event.unknown"]"#).unwrap();
+    assert!(right.has(Permission::EventRead));
+    assert_eq!(right.permissions.len(), 1);
+
+    let club_rights = cptserver::common::club_rights_from_stored(r#"{"3": ["team.read", "team.renamed"]}"#).unwrap();
+    assert_eq!(club_rights[&3].permissions.len(), 1);
+
+    assert!(Right::from_stored("event.read").is_err());
+}
+
+#[test]
+fn user_redact() {
+    let mut user = cptserver::common::User::from_info(1, "user".into(), "First".into(), "Last".into(), None);
+    user.email = Some("user@example.com".into());
+    user.height = Some(180);
+    user.note = Some("Prefers the morning group".into());
+    let stored = user.clone();
+
+    let right: Right = serde_json::from_str(r#"["user.read", "user.physical.read"]"#).unwrap();
+    user.redact(&right);
+    assert_eq!(user.email, None);
+    assert_eq!(user.height, Some(180));
+    assert_eq!(user.note, None);
+
+    user.retain_hidden(&stored, &right);
+    assert_eq!(user.email, stored.email);
+    assert_eq!(user.note, stored.note);
+}
+
+#[test]
+fn api_token_deny() {
+    assert!(cptserver::common::api_token_deny(None).is_ok());
+    assert!(matches!(
+        cptserver::common::api_token_deny(Some(1)),
+        Err(cptserver::error::ErrorKind::ApiTokenDenied)
+    ));
+}
+
+#[test]
+fn event_force() {
+    let writer: Right = serde_json::from_str(r#"["event.write"]"#).unwrap();
+    let acceptor: Right = serde_json::from_str(r#"["event.write", "event.accept"]"#).unwrap();
+
+    assert!(!cptserver::common::event_force(&writer, false).unwrap());
+    assert!(cptserver::common::event_force(&writer, true).is_err());
+    assert!(!cptserver::common::event_force(&acceptor, false).unwrap());
+    assert!(cptserver::common::event_force(&acceptor, true).unwrap());
+}
+
+#[test]
+fn right_superuser() {
+    assert_eq!(Right::superuser(true), Right::full());
+
+    let right = Right::superuser(false);
+    assert!(right.has(Permission::UserRead));
+    assert!(!right.has(Permission::UserWrite));
+    assert!(right.permissions.iter().all(Permission::is_read));
+}
+
+#[test]
+fn user_superuser_deny() {
+    assert!(cptserver::common::user_superuser_deny(false, false).is_ok());
+    assert!(cptserver::common::user_superuser_deny(true, true).is_ok());
+    assert!(matches!(
+        cptserver::common::user_superuser_deny(false, true),
+        Err(cptserver::error::ErrorKind::RightSuperuserMissing)
+    ));
+}
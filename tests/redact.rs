@@ -0,0 +1,12 @@
+#[test]
+fn redact_query() {
+    assert_eq!(
+        cptserver::common::redact_query("GET /anon/user_calendar?token=secret text/calendar:"),
+        "GET /anon/user_calendar?token=*** text/calendar:"
+    );
+    assert_eq!(
+        cptserver::common::redact_query("GET /oidc/callback?state=abc&code=def&flag"),
+        "GET /oidc/callback?state=***&code=***&flag"
+    );
+    assert_eq!(cptserver::common::redact_query("Nothing to hide"), "Nothing to hide");
+}
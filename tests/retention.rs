@@ -0,0 +1,24 @@
+use chrono::{NaiveDate, NaiveDateTime};
+
+fn datetime(year: i32, month: u32, day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+}
+
+#[test]
+fn retention_cutoff_years() {
+    assert_eq!(
+        cptserver::common::retention_cutoff_years(datetime(2025, 3, 1), 4),
+        datetime(2021, 3, 1)
+    );
+    assert_eq!(
+        cptserver::common::retention_cutoff_years(datetime(2024, 2, 29), 1),
+        datetime(2023, 2, 28)
+    );
+    assert_eq!(
+        cptserver::common::retention_cutoff_years(datetime(2025, 6, 15), 0),
+        datetime(2025, 6, 15)
+    );
+}
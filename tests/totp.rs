@@ -0,0 +1,27 @@
+// Test vectors from RFC 6238 (SHA1) and RFC 4648
+
+#[test]
+fn totp_code() {
+    let secret = b"12345678901234567890";
+
+    assert_eq!(cptserver::common::totp_code(secret, 59 / 30), 287082);
+    assert_eq!(cptserver::common::totp_code(secret, 1111111109 / 30), 81804);
+    assert_eq!(cptserver::common::totp_code(secret, 2000000000 / 30), 279037);
+}
+
+#[test]
+fn totp_verify() {
+    let secret = b"12345678901234567890";
+    let step = 1111111109 / 30;
+
+    assert_eq!(cptserver::common::totp_verify(secret, "081804", step), Some(step));
+    assert_eq!(cptserver::common::totp_verify(secret, "081804", step + 1), Some(step));
+    assert_eq!(cptserver::common::totp_verify(secret, "081804", step + 2), None);
+    assert_eq!(cptserver::common::totp_verify(secret, "81804", step), None);
+}
+
+#[test]
+fn base32_encode() {
+    assert_eq!(cptserver::common::base32_encode(b"f"), "MY");
+    assert_eq!(cptserver::common::base32_encode(b"foobar"), "MZXW6YTBOI");
+}
@@ -0,0 +1,8 @@
+#[test]
+fn user_pseudonym() {
+    let pseudonym = cptserver::common::user_pseudonym();
+    assert!(cptserver::common::validate_user_key(&pseudonym).is_ok());
+    // The pseudonym must fit into the subject of login failures
+    assert!(pseudonym.len() <= 45);
+    assert_ne!(pseudonym, cptserver::common::user_pseudonym());
+}